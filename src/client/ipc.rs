@@ -0,0 +1,79 @@
+use std::env;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Read;
+use std::io::Write;
+use std::net::Shutdown;
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+
+pub fn socket_path() -> PathBuf {
+    if let Some(path) = env::var_os("WZRD_SOCKET") {
+        return PathBuf::from(path);
+    }
+
+    let display = env::var("DISPLAY")
+        .unwrap_or_default()
+        .replace(|c: char| !c.is_ascii_alphanumeric() && c != '.', "");
+
+    env::var_os("XDG_RUNTIME_DIR")
+        .map_or_else(env::temp_dir, PathBuf::from)
+        .join(format!("wzrd-{}.sock", display))
+}
+
+fn send(command: &str) -> Result<UnixStream, String> {
+    let path = socket_path();
+    let mut stream = UnixStream::connect(&path)
+        .map_err(|err| format!("could not connect to {}: {}", path.display(), err))?;
+
+    let request = match env::var("WZRD_IPC_TOKEN") {
+        Ok(token) => format!("token {}\n{}\n", token, command),
+        Err(_) => format!("{}\n", command),
+    };
+
+    stream
+        .write_all(request.as_bytes())
+        .and_then(|_| stream.shutdown(Shutdown::Write))
+        .map_err(|err| format!("could not communicate with wzrd: {}", err))?;
+
+    Ok(stream)
+}
+
+// sends a single command, yielding the lines of a successful reply
+pub fn request(command: &str) -> Result<Vec<String>, String> {
+    let mut stream = send(command)?;
+    let mut reply = String::new();
+
+    stream
+        .read_to_string(&mut reply)
+        .map_err(|err| format!("could not communicate with wzrd: {}", err))?;
+
+    let mut lines = reply.lines();
+
+    match lines.next() {
+        Some("ok") => Ok(lines.map(str::to_owned).collect()),
+        Some(error) => Err(error.to_owned()),
+        None => Err("no reply from wzrd".to_owned()),
+    }
+}
+
+// passes every status line on as it arrives, until wzrd hangs up
+pub fn subscribe(mut on_line: impl FnMut(&str)) -> Result<(), String> {
+    let mut lines = BufReader::new(send("subscribe")?).lines();
+
+    match lines.next() {
+        Some(Ok(line)) if line == "ok" => {},
+        Some(Ok(error)) => return Err(error),
+        Some(Err(err)) => return Err(format!("could not communicate with wzrd: {}", err)),
+        None => return Err("no reply from wzrd".to_owned()),
+    }
+
+    for line in lines {
+        match line {
+            Ok(line) => on_line(&line),
+            Err(err) => return Err(format!("could not communicate with wzrd: {}", err)),
+        }
+    }
+
+    Ok(())
+}
@@ -1 +1,28 @@
-pub fn main() {}
+use std::env;
+use std::process;
+
+mod ipc;
+
+pub fn main() {
+    let command = env::args().skip(1).collect::<Vec<String>>().join(" ");
+
+    if command.is_empty() {
+        eprintln!("usage: wzrdc <command> [arguments]");
+        process::exit(2);
+    }
+
+    let result = match command.as_str() {
+        "subscribe" => ipc::subscribe(|line| println!("{}", line)),
+        command => ipc::request(command).map(|lines| {
+            lines.iter().for_each(|line| println!("{}", line));
+        }),
+    };
+
+    match result {
+        Ok(()) => {},
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
+        },
+    }
+}
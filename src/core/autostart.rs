@@ -0,0 +1,147 @@
+use crate::consume::get_parent_pid;
+use crate::identify::Index;
+use crate::util::Util;
+
+use winsys::connection::Pid;
+
+use std::time::Duration;
+use std::time::Instant;
+
+use nix::sys::wait::WaitStatus;
+use serde::Deserialize;
+
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+const STABLE_RUNTIME: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AutostartEntry {
+    pub command: String,
+    #[serde(default)]
+    pub workspace: Option<Index>,
+    #[serde(default)]
+    pub restart: bool,
+}
+
+#[derive(Debug)]
+struct Supervised {
+    entry: AutostartEntry,
+    pid: Option<Pid>,
+    started: Instant,
+    backoff: Duration,
+    restart_at: Option<Instant>,
+}
+
+#[derive(Debug, Default)]
+pub struct Autostart {
+    supervised: Vec<Supervised>,
+}
+
+impl Autostart {
+    pub fn new(entries: &[AutostartEntry]) -> Self {
+        Self {
+            supervised: entries
+                .iter()
+                .map(|entry| Supervised {
+                    entry: entry.clone(),
+                    pid: None,
+                    started: Instant::now(),
+                    backoff: MIN_BACKOFF,
+                    restart_at: Some(Instant::now()),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.supervised.is_empty()
+    }
+
+    pub fn exited(
+        &mut self,
+        pid: Pid,
+        status: WaitStatus,
+    ) {
+        let now = Instant::now();
+
+        if let Some(supervised) = self
+            .supervised
+            .iter_mut()
+            .find(|supervised| supervised.pid == Some(pid))
+        {
+            info!(
+                "autostart entry {} exited: {:?}",
+                supervised.entry.command, status
+            );
+
+            supervised.pid = None;
+
+            if supervised.entry.restart {
+                if now.duration_since(supervised.started) >= STABLE_RUNTIME {
+                    supervised.backoff = MIN_BACKOFF;
+                }
+
+                supervised.restart_at = Some(now + supervised.backoff);
+                supervised.backoff = (supervised.backoff * 2).min(MAX_BACKOFF);
+            }
+        }
+
+        self.supervised
+            .retain(|supervised| supervised.pid.is_some() || supervised.restart_at.is_some());
+    }
+
+    // spawns an entry right away, outside of the configured ones
+    pub fn launch(
+        &mut self,
+        entry: AutostartEntry,
+    ) {
+        info!("starting {}", entry.command);
+
+        self.supervised.push(Supervised {
+            pid: Util::spawn_child(&entry.command),
+            entry,
+            started: Instant::now(),
+            backoff: MIN_BACKOFF,
+            restart_at: None,
+        });
+    }
+
+    // spawns entries that are pending (re)start
+    pub fn supervise(&mut self) {
+        let now = Instant::now();
+
+        for supervised in &mut self.supervised {
+            match supervised.restart_at {
+                Some(restart_at) if restart_at <= now => {},
+                _ => continue,
+            }
+
+            supervised.restart_at = None;
+            supervised.started = now;
+            info!("starting autostart entry {}", supervised.entry.command);
+            supervised.pid = Util::spawn_child(&supervised.entry.command);
+        }
+    }
+
+    pub fn workspace_for(
+        &self,
+        pid: Pid,
+    ) -> Option<Index> {
+        let wm_pid = std::process::id();
+        let mut pid = Some(pid);
+
+        while let Some(current) = pid.filter(|&pid| pid != wm_pid && pid > 1) {
+            if let Some(supervised) = self
+                .supervised
+                .iter()
+                .find(|supervised| supervised.pid == Some(current))
+            {
+                return supervised.entry.workspace;
+            }
+
+            pid = get_parent_pid(current);
+        }
+
+        None
+    }
+}
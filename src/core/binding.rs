@@ -5,8 +5,17 @@ use winsys::input::MouseInput;
 use winsys::window::Window;
 
 use std::collections::HashMap;
+use std::collections::HashSet;
 
 pub type KeyAction = fn(&mut Model<'_>);
 pub type MouseAction = fn(&mut Model<'_>, Option<Window>) -> bool;
 pub type KeyBindings = HashMap<KeyInput, KeyAction>;
 pub type MouseBindings = HashMap<MouseInput, MouseAction>;
+pub type RepeatableKeys = HashSet<KeyInput>;
+
+// what the compiled-in bindings do, as listed in the default configuration
+#[derive(Debug, Default)]
+pub struct BindingNames {
+    pub keys: HashMap<KeyInput, &'static str>,
+    pub buttons: HashMap<MouseInput, &'static str>,
+}
@@ -55,6 +55,7 @@ pub struct Client {
     instance: RefCell<String>,
     context: Cell<usize>,
     workspace: Cell<usize>,
+    monitor: Cell<Option<usize>>,
     window_type: WindowType,
     active_region: Cell<Region>,
     previous_region: Cell<Region>,
@@ -63,6 +64,7 @@ pub struct Client {
     tile_region: Cell<Region>,
     decoration: Cell<Decoration>,
     size_hints: Cell<Option<SizeHints>>,
+    opaque_region: RefCell<Vec<Region>>,
     warp_pos: Cell<Option<Pos>>,
     parent: Option<Window>,
     children: RefCell<Vec<Window>>,
@@ -73,6 +75,8 @@ pub struct Client {
     mapped: Cell<bool>,
     managed: Cell<bool>,
     urgent: Cell<bool>,
+    paused: Cell<bool>,
+    flashing: Cell<bool>,
     floating: Cell<bool>,
     fullscreen: Cell<bool>,
     contained: Cell<bool>,
@@ -126,6 +130,7 @@ impl Client {
             instance: RefCell::new(instance.into()),
             context: Cell::new(0),
             workspace: Cell::new(0),
+            monitor: Cell::new(None),
             window_type,
             active_region: Cell::new(Default::default()),
             previous_region: Cell::new(Default::default()),
@@ -134,6 +139,7 @@ impl Client {
             tile_region: Cell::new(Default::default()),
             decoration: Cell::new(Default::default()),
             size_hints: Cell::new(None),
+            opaque_region: RefCell::new(Vec::new()),
             warp_pos: Cell::new(None),
             parent: None,
             children: RefCell::new(Vec::new()),
@@ -144,6 +150,8 @@ impl Client {
             mapped: Cell::new(false),
             managed: Cell::new(true),
             urgent: Cell::new(false),
+            paused: Cell::new(false),
+            flashing: Cell::new(false),
             floating: Cell::new(false),
             fullscreen: Cell::new(false),
             contained: Cell::new(false),
@@ -281,6 +289,19 @@ impl Client {
         self.workspace.get()
     }
 
+    #[inline]
+    pub fn set_monitor(
+        &self,
+        monitor: Option<usize>,
+    ) {
+        self.monitor.set(monitor);
+    }
+
+    #[inline]
+    pub fn monitor(&self) -> Option<usize> {
+        self.monitor.get()
+    }
+
     #[inline]
     pub fn window_type(&self) -> WindowType {
         self.window_type
@@ -377,11 +398,35 @@ impl Client {
         self.decoration.get().to_owned()
     }
 
+    // a flashing client has the colors of its decoration inverted
     #[inline(always)]
     pub fn decoration_colors(&self) -> (Option<(u32, Color)>, Option<Color>) {
+        let (border, frame) = self.state_colors();
+
+        if self.flashing.get() {
+            (
+                border.map(|(width, color)| (width, color ^ 0xffffff)),
+                frame.map(|color| color ^ 0xffffff),
+            )
+        } else {
+            (border, frame)
+        }
+    }
+
+    #[inline(always)]
+    fn state_colors(&self) -> (Option<(u32, Color)>, Option<Color>) {
         let outside_state = self.outside_state();
         let decoration = self.decoration.get();
 
+        if self.paused.get() {
+            return (
+                decoration
+                    .border
+                    .map(|border| (border.width, border.colors.paused)),
+                decoration.frame.map(|frame| frame.colors.paused),
+            );
+        }
+
         match outside_state {
             OutsideState::Focused => (
                 decoration
@@ -451,6 +496,19 @@ impl Client {
         self.size_hints.get()
     }
 
+    #[inline]
+    pub fn set_opaque_region(
+        &self,
+        opaque_region: Vec<Region>,
+    ) {
+        self.opaque_region.replace(opaque_region);
+    }
+
+    #[inline]
+    pub fn opaque_region(&self) -> Vec<Region> {
+        self.opaque_region.borrow().clone()
+    }
+
     #[inline]
     pub fn set_warp_pos(
         &self,
@@ -556,6 +614,11 @@ impl Client {
         self.consumers.borrow().len()
     }
 
+    #[inline]
+    pub fn consumers(&self) -> Vec<Window> {
+        self.consumers.borrow().clone()
+    }
+
     #[inline]
     pub fn is_consuming(&self) -> bool {
         self.producer.get().is_some()
@@ -569,6 +632,10 @@ impl Client {
         if Toggle::from(self.focused.get()) != toggle {
             self.focused.set(toggle.eval(self.focused.get()));
             self.outside_state.set(!self.outside_state.get());
+
+            if self.focused.get() {
+                self.last_focused.set(SystemTime::now());
+            }
         }
     }
 
@@ -621,6 +688,32 @@ impl Client {
         self.urgent.get()
     }
 
+    #[inline]
+    pub fn set_paused(
+        &self,
+        toggle: Toggle,
+    ) {
+        self.paused.set(toggle.eval(self.paused.get()));
+    }
+
+    #[inline]
+    pub fn is_paused(&self) -> bool {
+        self.paused.get()
+    }
+
+    #[inline]
+    pub fn set_flashing(
+        &self,
+        toggle: Toggle,
+    ) {
+        self.flashing.set(toggle.eval(self.flashing.get()));
+    }
+
+    #[inline]
+    pub fn is_flashing(&self) -> bool {
+        self.flashing.get()
+    }
+
     #[inline]
     pub fn is_free(&self) -> bool {
         self.floating.get() && (!self.fullscreen.get() || self.contained.get())
@@ -882,6 +975,7 @@ impl std::fmt::Debug for Client {
             .field("sticky", &self.sticky)
             .field("invincible", &self.invincible)
             .field("urgent", &self.urgent)
+            .field("paused", &self.paused)
             .field("consuming", &self.consuming)
             .field("pid", &self.pid)
             .field("ppid", &self.ppid)
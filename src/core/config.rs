@@ -1 +1,997 @@
+use crate::autostart::AutostartEntry;
+use crate::binding::RepeatableKeys;
+use crate::decoration::ColorScheme;
+use crate::decoration::DecorationPresets;
+use crate::decoration::DecorationStrategy;
+use crate::identify::Index;
+use crate::rule::Rule;
 
+use winsys::geometry::Corner;
+use winsys::geometry::Edge;
+use winsys::input::Button;
+use winsys::input::KeyInput;
+use winsys::input::Modifier;
+
+use serde::Deserialize;
+
+use toml::value::Table;
+use toml::Value;
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+
+const MAX_INCLUDE_DEPTH: usize = 8;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub learn_affinity: bool,
+    pub audit_interval: u64,
+    pub usage_interval: u64,
+    pub decoration_strategy: DecorationStrategy,
+    pub decoration_presets: DecorationPresets,
+    pub rules: Vec<Rule>,
+    pub rerun_rules_on_class_change: bool,
+    pub max_strut_fraction: f32,
+    pub desktop_layout: DesktopLayout,
+    pub key_repeat_delay: u64,
+    pub key_repeat_interval: u64,
+    pub key_repeat_min_interval: u64,
+    pub key_repeat_acceleration: f32,
+    pub detect_autorepeat: bool,
+    pub repeatable_bindings: Vec<String>,
+    pub binding_throttle: HashMap<String, u64>,
+    pub auto_layouts: Vec<AutoLayout>,
+    pub auto_layout_hysteresis: usize,
+    pub portrait_layout: Option<String>,
+    pub landscape_layout: Option<String>,
+    pub actions: HashMap<String, Vec<String>>,
+    pub autostart: Vec<AutostartEntry>,
+    pub locker: Option<String>,
+    pub frame_corner_radius: u32,
+    pub outline_move_resize: bool,
+    pub colors: ColorScheme,
+    pub picture_in_picture: PictureInPicture,
+    pub flash: Flash,
+    pub kiosk: bool,
+    pub spawn_at_pointer: bool,
+    pub prefer_requested_workspace: bool,
+    pub empty_workspace_action: EmptyWorkspaceAction,
+    pub home_workspace: usize,
+    pub activation: ActivationPolicy,
+    pub pager_activation: ActivationPolicy,
+    pub scroll_zone: ScrollZone,
+    pub scroll_zone_width: u32,
+    pub hint_alphabet: String,
+    pub root_status: bool,
+    pub startup_scripts: bool,
+    pub swallowing: Swallowing,
+    pub workspace_templates: Vec<WorkspaceTemplate>,
+    pub layout_cycle: Vec<String>,
+    // keyed by workspace name, or else by zero-based workspace index
+    pub workspace_layouts: HashMap<String, String>,
+    pub workspace_layout_cycles: HashMap<String, Vec<String>>,
+    pub outputs: HashMap<String, OutputConfig>,
+    pub ipc: IpcConfig,
+    pub status: StatusConfig,
+    pub script: Option<PathBuf>,
+    pub drag: DragBindings,
+    pub startup_retry: u64,
+    pub gap_size: u32,
+    pub margin: Margin,
+    pub gap_step: u32,
+    pub margin_step: i32,
+    pub margin_presets: HashMap<String, Margin>,
+    #[serde(skip)]
+    pub path: PathBuf,
+    // set when the file could not be parsed and the defaults are used instead
+    #[serde(skip)]
+    pub error: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            learn_affinity: false,
+            audit_interval: 60,
+            usage_interval: 0,
+            decoration_strategy: DecorationStrategy::Reparent,
+            decoration_presets: Default::default(),
+            rules: Vec::new(),
+            rerun_rules_on_class_change: true,
+            max_strut_fraction: 0.25,
+            desktop_layout: Default::default(),
+            key_repeat_delay: 400,
+            key_repeat_interval: 80,
+            key_repeat_min_interval: 15,
+            key_repeat_acceleration: 0.9,
+            detect_autorepeat: true,
+            repeatable_bindings: Vec::new(),
+            binding_throttle: HashMap::new(),
+            auto_layouts: Vec::new(),
+            auto_layout_hysteresis: 1,
+            portrait_layout: None,
+            landscape_layout: None,
+            actions: HashMap::new(),
+            autostart: Vec::new(),
+            locker: None,
+            frame_corner_radius: 0,
+            outline_move_resize: false,
+            colors: Default::default(),
+            picture_in_picture: Default::default(),
+            flash: Default::default(),
+            kiosk: false,
+            spawn_at_pointer: false,
+            prefer_requested_workspace: false,
+            empty_workspace_action: EmptyWorkspaceAction::Stay,
+            home_workspace: 0,
+            activation: ActivationPolicy::Smart,
+            pager_activation: ActivationPolicy::Always,
+            scroll_zone: ScrollZone::Anywhere,
+            scroll_zone_width: 20,
+            hint_alphabet: "asdfghjkl".to_owned(),
+            root_status: false,
+            startup_scripts: true,
+            swallowing: Default::default(),
+            workspace_templates: Vec::new(),
+            workspace_layouts: HashMap::new(),
+            layout_cycle: vec![
+                "stack".to_owned(),
+                "bstack".to_owned(),
+                "monocle".to_owned(),
+                "float".to_owned(),
+            ],
+            workspace_layout_cycles: HashMap::new(),
+            outputs: HashMap::new(),
+            ipc: Default::default(),
+            status: Default::default(),
+            script: None,
+            drag: Default::default(),
+            startup_retry: 1000,
+            gap_size: 0,
+            margin: Default::default(),
+            gap_step: 5,
+            margin_step: 5,
+            margin_presets: HashMap::new(),
+            path: Self::default_path(),
+            error: None,
+        }
+    }
+}
+
+// once a token is set, requests must present one: the token permits everything,
+// the read-only token only queries and subscribing, and requests without either
+// are denied
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct IpcConfig {
+    pub socket_mode: u32,
+    pub token: Option<String>,
+    pub read_only_token: Option<String>,
+    pub fifo: Option<PathBuf>,
+}
+
+impl Default for IpcConfig {
+    fn default() -> Self {
+        Self {
+            socket_mode: 0o600,
+            token: None,
+            read_only_token: None,
+            fifo: None,
+        }
+    }
+}
+
+// a line is reported to the output, "-" for stdout or else the path of a FIFO
+// that is created if missing, whenever it changes
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct StatusConfig {
+    pub output: Option<String>,
+    pub format: String,
+}
+
+impl Default for StatusConfig {
+    fn default() -> Self {
+        Self {
+            output: None,
+            format: "{workspaces} {layout} {title}".to_owned(),
+        }
+    }
+}
+
+// the layout is set when the workspaces are created, the commands are spawned onto
+// the workspace the first time it is activated
+#[derive(Debug, Clone, Deserialize)]
+pub struct WorkspaceTemplate {
+    pub workspace: Index,
+    #[serde(default)]
+    pub layout: Option<String>,
+    #[serde(default)]
+    pub spawn: Vec<String>,
+}
+
+// producers are the clients that get swallowed (typically terminals), consumers the
+// clients spawned from them; empty class lists match any class
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Swallowing {
+    pub enabled: bool,
+    pub max_depth: Option<usize>,
+    pub producers: Vec<String>,
+    pub consumers: Vec<String>,
+    pub disabled_workspaces: Vec<Index>,
+}
+
+impl Default for Swallowing {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_depth: None,
+            producers: Vec::new(),
+            consumers: Vec::new(),
+            disabled_workspaces: Vec::new(),
+        }
+    }
+}
+
+impl Swallowing {
+    pub fn allows(
+        &self,
+        workspace: Index,
+        producer_class: &str,
+        consumer_class: &str,
+    ) -> bool {
+        let matches =
+            |classes: &[String], class: &str| classes.is_empty() || classes.iter().any(|c| c == class);
+
+        self.enabled
+            && !self.disabled_workspaces.contains(&workspace)
+            && matches(&self.producers, producer_class)
+            && matches(&self.consumers, consumer_class)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AutoLayoutStep {
+    pub clients: usize,
+    pub layout: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AutoLayout {
+    #[serde(default)]
+    pub workspaces: Vec<Index>,
+    pub steps: Vec<AutoLayoutStep>,
+}
+
+impl AutoLayout {
+    pub fn applies_to(
+        &self,
+        workspace: Index,
+    ) -> bool {
+        self.workspaces.is_empty() || self.workspaces.contains(&workspace)
+    }
+
+    // the step with the highest client threshold that is reached
+    pub fn step_for(
+        &self,
+        count: usize,
+    ) -> Option<usize> {
+        self.steps
+            .iter()
+            .enumerate()
+            .filter(|(_, step)| step.clients <= count)
+            .max_by_key(|(_, step)| step.clients)
+            .map(|(i, _)| i)
+    }
+}
+
+// how requests to activate a client (_NET_ACTIVE_WINDOW) are handled; smart
+// only focuses clients on the active workspace and marks others urgent
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ActivationPolicy {
+    Always,
+    Smart,
+    Urgent,
+    Ignore,
+}
+
+// settings for the partition of the output (RandR name) they are keyed by; the
+// workspace is the one activated at startup when the output is the active one
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct OutputConfig {
+    pub gap: u32,
+    pub workspace: Option<Index>,
+    pub struts: bool,
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        Self {
+            gap: 0,
+            workspace: None,
+            struts: true,
+        }
+    }
+}
+
+// modifiers are named as in key bindings (e.g. "M" for Super); invalid names fall
+// back to dragging with Alt and the left and right buttons
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct DragBindings {
+    pub modifiers: Vec<String>,
+    pub move_button: String,
+    pub resize_button: String,
+}
+
+impl Default for DragBindings {
+    fn default() -> Self {
+        Self {
+            modifiers: vec!["A".to_owned()],
+            move_button: "left".to_owned(),
+            resize_button: "right".to_owned(),
+        }
+    }
+}
+
+impl DragBindings {
+    pub fn modifiers(&self) -> HashSet<Modifier> {
+        self.modifiers
+            .iter()
+            .map(|modifier| Modifier::try_from(modifier.as_str()))
+            .collect::<winsys::Result<HashSet<Modifier>>>()
+            .unwrap_or_else(|err| {
+                warn!("invalid drag modifier: {}", err);
+                vec![Modifier::Alt].into_iter().collect()
+            })
+    }
+
+    pub fn move_button(&self) -> Button {
+        Button::try_from(self.move_button.as_str()).unwrap_or_else(|err| {
+            warn!("invalid drag button: {}", err);
+            Button::Left
+        })
+    }
+
+    pub fn resize_button(&self) -> Button {
+        Button::try_from(self.resize_button.as_str()).unwrap_or_else(|err| {
+            warn!("invalid drag button: {}", err);
+            Button::Right
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default, Deserialize)]
+#[serde(default)]
+// edges that are left unset are kept as they are when a preset is applied
+pub struct Margin {
+    pub left: Option<i32>,
+    pub right: Option<i32>,
+    pub top: Option<i32>,
+    pub bottom: Option<i32>,
+}
+
+// where scrolling switches workspaces; at the edges, scrolling only does so within
+// the zone width of the screen edges, on an empty workspace, or outside its layout
+// zones
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScrollZone {
+    Anywhere,
+    Edges,
+}
+
+// where to go once the last client on the active workspace is closed
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmptyWorkspaceAction {
+    Stay,
+    Recent,
+    Home,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DesktopOrientation {
+    Horizontal,
+    Vertical,
+}
+
+#[derive(Debug, Copy, Clone, Deserialize)]
+#[serde(default)]
+pub struct DesktopLayout {
+    pub orientation: DesktopOrientation,
+    pub rows: usize,
+    pub columns: usize,
+    pub wrap: bool,
+}
+
+impl Default for DesktopLayout {
+    fn default() -> Self {
+        Self {
+            orientation: DesktopOrientation::Horizontal,
+            rows: 1,
+            columns: 0,
+            wrap: false,
+        }
+    }
+}
+
+impl DesktopLayout {
+    // a zero row or column count is derived from the number of desktops
+    pub fn grid(
+        &self,
+        count: usize,
+    ) -> (usize, usize) {
+        let count = count.max(1);
+
+        match (self.columns, self.rows) {
+            (0, 0) => (count, 1),
+            (0, rows) => (count.div_ceil(rows), rows),
+            (columns, 0) => (columns, count.div_ceil(columns)),
+            (columns, rows) => (columns, rows),
+        }
+    }
+
+    pub fn neighbour(
+        &self,
+        count: usize,
+        index: Index,
+        edge: Edge,
+    ) -> Option<Index> {
+        let (columns, rows) = self.grid(count);
+
+        let (mut column, mut row) = match self.orientation {
+            DesktopOrientation::Horizontal => (index % columns, index / columns),
+            DesktopOrientation::Vertical => (index / rows, index % rows),
+        };
+
+        // when wrapping, empty cells at the end of the grid are skipped
+        for _ in 0..columns.max(rows) {
+            match edge {
+                Edge::Left if column > 0 => column -= 1,
+                Edge::Left if self.wrap => column = columns - 1,
+                Edge::Right if column + 1 < columns => column += 1,
+                Edge::Right if self.wrap => column = 0,
+                Edge::Top if row > 0 => row -= 1,
+                Edge::Top if self.wrap => row = rows - 1,
+                Edge::Bottom if row + 1 < rows => row += 1,
+                Edge::Bottom if self.wrap => row = 0,
+                _ => return None,
+            }
+
+            let index = match self.orientation {
+                DesktopOrientation::Horizontal => row * columns + column,
+                DesktopOrientation::Vertical => column * rows + row,
+            };
+
+            if index < count {
+                return Some(index);
+            } else if !self.wrap {
+                return None;
+            }
+        }
+
+        None
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(remote = "Corner", rename_all = "kebab-case")]
+enum CornerDef {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+#[derive(Debug, Copy, Clone, Deserialize)]
+#[serde(default)]
+pub struct PictureInPicture {
+    pub width: i32,
+    pub height: i32,
+    pub margin: i32,
+    #[serde(with = "CornerDef")]
+    pub corner: Corner,
+}
+
+impl Default for PictureInPicture {
+    fn default() -> Self {
+        Self {
+            width: 480,
+            height: 270,
+            margin: 20,
+            corner: Corner::BottomRight,
+        }
+    }
+}
+
+// a flash inverts the colors of a client's decoration the given number of times,
+// switching every interval (in milliseconds)
+#[derive(Debug, Copy, Clone, Deserialize)]
+#[serde(default)]
+pub struct Flash {
+    pub on_urgent: bool,
+    pub count: u32,
+    pub interval: u64,
+}
+
+impl Default for Flash {
+    fn default() -> Self {
+        Self {
+            on_urgent: false,
+            count: 3,
+            interval: 150,
+        }
+    }
+}
+
+impl Config {
+    pub fn repeatable_keys(&self) -> RepeatableKeys {
+        self.repeatable_bindings
+            .iter()
+            .filter_map(|keys| match KeyInput::try_from(keys.as_str()) {
+                Ok(input) => Some(input),
+                Err(err) => {
+                    warn!("ignoring repeatable binding {}: {}", keys, err);
+                    None
+                },
+            })
+            .collect()
+    }
+
+    // the table under profiles that is named after the host is merged over the
+    // rest of the configuration
+    fn parse(path: &Path) -> Result<Self, String> {
+        let mut table = Self::read_table(path, 0)?;
+
+        if let Some(Value::Table(mut profiles)) = table.remove("profiles") {
+            let profile = hostname().and_then(|hostname| profiles.remove(&hostname));
+
+            if let Some(Value::Table(profile)) = profile {
+                let dir = path.parent().unwrap_or_else(|| Path::new(""));
+                merge(&mut table, Self::resolve_includes(profile, dir, 1)?);
+            }
+        }
+
+        Value::Table(table)
+            .try_into()
+            .map_err(|err| format!("{}: {}", path.display(), err))
+    }
+
+    fn read_table(
+        path: &Path,
+        depth: usize,
+    ) -> Result<Table, String> {
+        if depth > MAX_INCLUDE_DEPTH {
+            return Err(format!("{}: includes are nested too deeply", path.display()));
+        }
+
+        let table = fs::read_to_string(path)
+            .map_err(|err| err.to_string())
+            .and_then(|contents| toml::from_str(&contents).map_err(|err| err.to_string()))
+            .map_err(|err| format!("{}: {}", path.display(), err))?;
+
+        Self::resolve_includes(table, path.parent().unwrap_or_else(|| Path::new("")), depth)
+    }
+
+    // included files are relative to the including one, and are overridden by it
+    // (and by files included after them)
+    fn resolve_includes(
+        mut table: Table,
+        dir: &Path,
+        depth: usize,
+    ) -> Result<Table, String> {
+        let includes = match table.remove("include") {
+            Some(Value::Array(includes)) => includes,
+            Some(Value::String(include)) => vec![Value::String(include)],
+            Some(_) => return Err("include must be a path or a list of paths".to_owned()),
+            None => return Ok(table),
+        };
+
+        let mut merged = Table::new();
+
+        for include in includes {
+            match include {
+                Value::String(include) => {
+                    merge(&mut merged, Self::read_table(&dir.join(include), depth + 1)?);
+                },
+                _ => return Err("include must be a path or a list of paths".to_owned()),
+            }
+        }
+
+        merge(&mut merged, table);
+        Ok(merged)
+    }
+
+    pub fn dir() -> PathBuf {
+        env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+            .unwrap_or_default()
+            .join(WM_NAME!())
+    }
+
+    pub fn default_path() -> PathBuf {
+        Self::dir().join("config.toml")
+    }
+
+    // relative script paths are resolved against the configuration directory
+    pub fn script_path(&self) -> Option<PathBuf> {
+        self.script.as_ref().map(|script| Self::dir().join(script))
+    }
+
+    pub fn fifo_path(&self) -> Option<PathBuf> {
+        self.ipc.fifo.as_ref().map(|fifo| Self::dir().join(fifo))
+    }
+
+    pub fn load() -> Self {
+        Self::load_from(&Self::default_path())
+    }
+
+    pub fn load_from(path: &Path) -> Self {
+        let config: Self = if path.exists() {
+            match Self::parse(path) {
+                Ok(config) => config,
+                Err(err) => {
+                    warn!("could not parse {}", err);
+
+                    Self {
+                        error: Some(err),
+                        ..Default::default()
+                    }
+                },
+            }
+        } else {
+            Default::default()
+        };
+
+        Self {
+            path: path.to_owned(),
+            ..config
+        }
+    }
+}
+
+impl Config {
+    // the default configuration with every option commented out, followed by the
+    // compiled-in bindings, given as pairs of input and name, for reference
+    pub fn commented_default(
+        mut key_bindings: Vec<(String, String)>,
+        mut mouse_bindings: Vec<(String, String)>,
+    ) -> String {
+        let config = Self::default();
+        let lower = |value: &dyn std::fmt::Debug| format!("{:?}", value).to_lowercase();
+
+        let corner = match config.picture_in_picture.corner {
+            Corner::TopLeft => "top-left",
+            Corner::TopRight => "top-right",
+            Corner::BottomLeft => "bottom-left",
+            Corner::BottomRight => "bottom-right",
+        };
+
+        let mut out = format!(
+            r#"# {name} configuration, read from {path}
+#
+# options are shown with their defaults, or with an example where there is none;
+# uncomment the ones to change
+
+# further files to merge in before this one, relative to this file
+# include = ["colors.toml"]
+
+# learn the workspaces clients of a class are opened on
+# learn_affinity = {learn_affinity}
+# seconds between checks for windows that disappeared unnoticed
+# audit_interval = {audit_interval}
+# seconds between resource usage samples, 0 disables sampling
+# usage_interval = {usage_interval}
+# either "reparent" (frames) or "direct" (borders only)
+# decoration_strategy = "{decoration_strategy}"
+# re-run the rules when a client changes its class
+# rerun_rules_on_class_change = {rerun_rules_on_class_change}
+# the largest fraction of a screen a strut may reserve
+# max_strut_fraction = {max_strut_fraction}
+# milliseconds before a held binding repeats, and between repeats
+# key_repeat_delay = {key_repeat_delay}
+# key_repeat_interval = {key_repeat_interval}
+# key_repeat_min_interval = {key_repeat_min_interval}
+# key_repeat_acceleration = {key_repeat_acceleration}
+# bindings that repeat while held, meant for those that change a value, unless throttled
+# repeatable_bindings = ["A-C-H", "A-C-L"]
+# drop the presses a held key autorepeats, pairing presses with their releases
+# detect_autorepeat = {detect_autorepeat}
+# auto_layout_hysteresis = {auto_layout_hysteresis}
+# layouts chosen when a screen turns portrait or landscape
+# portrait_layout = "bstack"
+# landscape_layout = "stack"
+# locker = "slock"
+# frame_corner_radius = {frame_corner_radius}
+# draw an outline instead of moving and resizing clients live
+# outline_move_resize = {outline_move_resize}
+# only allow the bindings needed to leave the session
+# kiosk = {kiosk}
+# spawn_at_pointer = {spawn_at_pointer}
+# prefer_requested_workspace = {prefer_requested_workspace}
+# "stay", "recent" or "home"
+# empty_workspace_action = "{empty_workspace_action}"
+# home_workspace = {home_workspace}
+# "always", "smart", "urgent" or "ignore"
+# activation = "{activation}"
+# pager_activation = "{pager_activation}"
+# where scrolling switches workspaces, "anywhere" or only at the "edges", on
+# empty workspaces, and outside the area clients are tiled in
+# scroll_zone = "{scroll_zone}"
+# scroll_zone_width = {scroll_zone_width}
+# the letters the hints for picking windows are made of
+# hint_alphabet = "{hint_alphabet}"
+# name the root window after the workspaces and layout, as read by dwm-style bars
+# root_status = {root_status}
+# run the blocking and non-blocking autostart scripts
+# startup_scripts = {startup_scripts}
+# layouts cycled through, optionally per workspace number or name
+# layout_cycle = {layout_cycle}
+# script = "init.rhai"
+# milliseconds to wait for a previous window manager to exit
+# startup_retry = {startup_retry}
+# gap_size = {gap_size}
+# gap_step = {gap_step}
+# margin_step = {margin_step}
+
+# [colors]
+# focused = {focused:#08x}
+# fdisowned = {fdisowned:#08x}
+# fsticky = {fsticky:#08x}
+# unfocused = {unfocused:#08x}
+# udisowned = {udisowned:#08x}
+# usticky = {usticky:#08x}
+# urgent = {urgent:#08x}
+# paused = {paused:#08x}
+
+# decorations of tiled, floating and tabbed clients; frame extents are given as
+# [left, right, top, bottom]
+# [decoration_presets.floating]
+# border = 1
+# frame = [0, 0, 3, 0]
+
+# layouts switched to as clients are added to a workspace
+# [[auto_layouts]]
+# workspaces = [0]
+# steps = [{{ clients = 1, layout = "monocle" }}, {{ clients = 3, layout = "stack" }}]
+
+# [[autostart]]
+# command = "picom"
+# workspace = 0
+# restart = true
+
+# [[workspace_templates]]
+# workspace = 1
+# layout = "monocle"
+# spawn = ["firefox"]
+
+# keyed by workspace name, or else by zero-based workspace index
+# [workspace_layouts]
+# web = "monocle"
+
+# [workspace_layout_cycles]
+# term = ["stack", "bstack"]
+
+# edges that are left out keep their current margin
+# [margin_presets.presentation]
+# bottom = 200
+
+# [binding_throttle]
+# minimum milliseconds between runs of a binding while it is held
+# "A-J" = 150
+
+# [margin]
+# left = {margin_left}
+# right = {margin_right}
+# top = {margin_top}
+# bottom = {margin_bottom}
+
+# [drag]
+# modifiers = {drag_modifiers}
+# move_button = "{move_button}"
+# resize_button = "{resize_button}"
+
+# [desktop_layout]
+# orientation = "{orientation}"
+# rows = {rows}
+# columns = {columns}
+# wrap = {wrap}
+
+# [picture_in_picture]
+# width = {pip_width}
+# height = {pip_height}
+# margin = {pip_margin}
+# corner = "{corner}"
+
+# [flash]
+# flash the decoration of clients that demand attention
+# on_urgent = {flash_on_urgent}
+# count = {flash_count}
+# interval = {flash_interval}
+
+# [swallowing]
+# enabled = {swallowing}
+# producers = []
+# consumers = []
+
+# [ipc]
+# socket_mode = {socket_mode:#o}
+# token = "secret"
+# read_only_token = "public"
+# once either is set, requests without a token are denied
+# also read commands, one per line and without replies, from a FIFO
+# fifo = "/tmp/wzrd.fifo"
+
+# [status]
+# a line for bars, on stdout ("-", shared with the log) or a FIFO; the active
+# workspace is shown as [name], those with urgent clients as !name, and empty
+# ones are left out
+# output = "/tmp/wzrd-status"
+# format = "{status_format}"
+
+# [outputs.DP-1]
+# gap = 0
+# workspace = 0
+# struts = true
+
+# [actions]
+# terminal = ["spawn alacritty"]
+
+# [[rules]]
+# class = "mpv"
+# float = true
+
+# [profiles.hostname]
+# gap_size = 10
+"#,
+            name = WM_NAME!(),
+            path = Self::default_path().display(),
+            learn_affinity = config.learn_affinity,
+            audit_interval = config.audit_interval,
+            usage_interval = config.usage_interval,
+            decoration_strategy = lower(&config.decoration_strategy),
+            rerun_rules_on_class_change = config.rerun_rules_on_class_change,
+            max_strut_fraction = config.max_strut_fraction,
+            key_repeat_delay = config.key_repeat_delay,
+            key_repeat_interval = config.key_repeat_interval,
+            key_repeat_min_interval = config.key_repeat_min_interval,
+            key_repeat_acceleration = config.key_repeat_acceleration,
+            detect_autorepeat = config.detect_autorepeat,
+            auto_layout_hysteresis = config.auto_layout_hysteresis,
+            frame_corner_radius = config.frame_corner_radius,
+            outline_move_resize = config.outline_move_resize,
+            kiosk = config.kiosk,
+            spawn_at_pointer = config.spawn_at_pointer,
+            prefer_requested_workspace = config.prefer_requested_workspace,
+            empty_workspace_action = lower(&config.empty_workspace_action),
+            home_workspace = config.home_workspace,
+            activation = lower(&config.activation),
+            pager_activation = lower(&config.pager_activation),
+            scroll_zone = lower(&config.scroll_zone),
+            scroll_zone_width = config.scroll_zone_width,
+            hint_alphabet = config.hint_alphabet,
+            root_status = config.root_status,
+            startup_scripts = config.startup_scripts,
+            layout_cycle = quoted(&config.layout_cycle),
+            startup_retry = config.startup_retry,
+            gap_size = config.gap_size,
+            gap_step = config.gap_step,
+            margin_step = config.margin_step,
+            margin_left = config.margin.left.unwrap_or_default(),
+            margin_right = config.margin.right.unwrap_or_default(),
+            margin_top = config.margin.top.unwrap_or_default(),
+            margin_bottom = config.margin.bottom.unwrap_or_default(),
+            drag_modifiers = quoted(&config.drag.modifiers),
+            move_button = config.drag.move_button,
+            resize_button = config.drag.resize_button,
+            orientation = lower(&config.desktop_layout.orientation),
+            rows = config.desktop_layout.rows,
+            columns = config.desktop_layout.columns,
+            wrap = config.desktop_layout.wrap,
+            pip_width = config.picture_in_picture.width,
+            pip_height = config.picture_in_picture.height,
+            pip_margin = config.picture_in_picture.margin,
+            corner = corner,
+            flash_on_urgent = config.flash.on_urgent,
+            flash_count = config.flash.count,
+            flash_interval = config.flash.interval,
+            swallowing = config.swallowing.enabled,
+            socket_mode = config.ipc.socket_mode,
+            status_format = config.status.format,
+            focused = config.colors.focused,
+            fdisowned = config.colors.fdisowned,
+            fsticky = config.colors.fsticky,
+            unfocused = config.colors.unfocused,
+            udisowned = config.colors.udisowned,
+            usticky = config.colors.usticky,
+            urgent = config.colors.urgent,
+            paused = config.colors.paused,
+        );
+
+        key_bindings.sort();
+        mouse_bindings.sort();
+
+        let width = key_bindings
+            .iter()
+            .chain(&mouse_bindings)
+            .map(|(input, _)| input.len())
+            .max()
+            .unwrap_or(0);
+
+        out.push_str("\n# compiled-in key bindings\n");
+        key_bindings.iter().for_each(|(input, name)| {
+            out.push_str(&format!("#   {:<width$}  {}\n", input, name, width = width))
+        });
+
+        out.push_str("\n# compiled-in mouse bindings\n");
+        mouse_bindings.iter().for_each(|(input, name)| {
+            out.push_str(&format!("#   {:<width$}  {}\n", input, name, width = width))
+        });
+
+        out
+    }
+}
+
+fn quoted(values: &[String]) -> String {
+    format!(
+        "[{}]",
+        values
+            .iter()
+            .map(|value| format!("{:?}", value))
+            .collect::<Vec<String>>()
+            .join(", ")
+    )
+}
+
+impl Config {
+    // held bindings are keyed as in scripts (e.g. "A-J"), invalid ones are skipped
+    pub fn binding_throttles(&self) -> HashMap<KeyInput, Duration> {
+        self.binding_throttle
+            .iter()
+            .filter_map(|(keys, &interval)| match KeyInput::try_from(keys.as_str()) {
+                Ok(input) => Some((input, Duration::from_millis(interval))),
+                Err(err) => {
+                    warn!("ignoring throttle for binding {}: {}", keys, err);
+                    None
+                },
+            })
+            .collect()
+    }
+}
+
+// tables are merged recursively, any other value is replaced
+fn merge(
+    base: &mut Table,
+    table: Table,
+) {
+    for (key, value) in table {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(base)), Value::Table(table)) => merge(base, table),
+            (_, value) => {
+                base.insert(key, value);
+            },
+        }
+    }
+}
+
+fn hostname() -> Option<String> {
+    let mut buffer = [0u8; 256];
+
+    nix::unistd::gethostname(&mut buffer)
+        .ok()
+        .and_then(|hostname| hostname.to_str().ok())
+        .map(str::to_owned)
+}
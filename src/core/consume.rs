@@ -7,7 +7,7 @@ use winsys::window::Window;
 use std::collections::HashMap;
 use std::fs;
 
-fn get_parent_pid(pid: Pid) -> Option<Pid> {
+pub fn get_parent_pid(pid: Pid) -> Option<Pid> {
     if let Ok(stat) = fs::read_to_string(format!("/proc/{}/stat", pid)) {
         let stat = stat.split(" ").collect::<Vec<&str>>();
         return stat.get(3).and_then(|ppid| ppid.parse::<Pid>().ok());
@@ -16,13 +16,21 @@ fn get_parent_pid(pid: Pid) -> Option<Pid> {
     None
 }
 
+// the depth of the spawner is the number of process generations between it and the
+// spawned process; spawners beyond max_depth are not considered
 pub fn get_spawner_pid(
     pid: Pid,
     wm_pid: Pid,
+    max_depth: Option<usize>,
     pid_map: &HashMap<Pid, Window>,
     client_map: &HashMap<Window, Client, BuildIdHasher>,
 ) -> Option<Pid> {
+    let within_depth = |depth: usize, pid: Option<Pid>| {
+        pid.filter(|_| max_depth.filter(|&max_depth| depth > max_depth).is_none())
+    };
+
     let mut ppid = get_parent_pid(pid);
+    let mut depth = 1;
 
     while ppid.is_some() {
         let ppid_new = get_parent_pid(ppid.unwrap());
@@ -37,14 +45,19 @@ pub fn get_spawner_pid(
         };
 
         if is_consumer {
-            return ppid_new;
+            return within_depth(depth + 1, ppid_new);
         }
 
         if ppid_new == Some(wm_pid) {
-            return if ppid == Some(pid) { None } else { ppid };
+            return if ppid == Some(pid) {
+                None
+            } else {
+                within_depth(depth, ppid)
+            };
         }
 
         ppid = ppid_new;
+        depth += 1;
     }
 
     None
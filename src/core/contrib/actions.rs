@@ -1 +1,268 @@
+use crate::change::Change;
+use crate::change::Direction;
+use crate::change::Toggle;
 
+use winsys::geometry::Edge;
+use winsys::window::WindowState;
+use winsys::Result;
+
+use std::ops::Add;
+use std::ops::Mul;
+use std::ops::Sub;
+use std::str::FromStr;
+
+use anyhow::anyhow;
+
+// the actions otherwise only reachable through bindings; those that act on a
+// client act on the focused one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    CycleFocus(Direction),
+    DragFocus(Direction),
+    RotateClients(Direction),
+    CycleSameClass(Direction),
+    CycleFullscreen(Direction),
+    CycleZones(Direction),
+    ToggleZone,
+    CreateLayoutZone,
+    CreateTabZone,
+    DeleteZone,
+    MarkZone,
+    ExpandInZone,
+    CycleLayout(Direction),
+    ToggleLayout,
+    NextWorkspace(Direction),
+    WorkspaceTowards(Edge),
+    ToggleWorkspace,
+    WorkspaceBack,
+    MoveToNextWorkspace(Direction),
+    ChangeGapSize(Change<u32>),
+    ResetGapSize,
+    ChangeMainCount(Change<u32>),
+    ChangeMainFactor(Change<f32>),
+    ChangeMargin(Edge, Change<i32>),
+    ResetMargin,
+    CopyPrevLayoutData,
+    ResetLayoutData,
+    SetFloating(Toggle),
+    SetFullscreen(Toggle),
+    SetMaximized(WindowState, Toggle),
+    SetContained(Toggle),
+    SetInvincible(Toggle),
+    SetProducing(Toggle),
+    SetIconifyable(Toggle),
+    SetIconify(Toggle),
+    SetStick(Toggle),
+    PopDeiconify,
+    DeiconifyAll,
+    Center,
+    Snap(Edge),
+    Nudge(Edge, i32),
+    Stretch(Edge, i32),
+    GrowRatio(i32),
+    Pip,
+    Pause,
+    Resume,
+    Teleport,
+    Hint,
+    TogglePassThrough,
+    ConfinePointer,
+    ToggleScreenStruts,
+    ToggleDebugHud,
+    ReloadConfig,
+}
+
+impl Action {
+    pub const NAMES: &'static [&'static str] = &[
+        "cycle-focus",
+        "drag-focus",
+        "rotate-clients",
+        "cycle-same-class",
+        "cycle-fullscreen",
+        "cycle-zones",
+        "toggle-zone",
+        "create-layout-zone",
+        "create-tab-zone",
+        "delete-zone",
+        "mark-zone",
+        "expand-in-zone",
+        "cycle-layout",
+        "toggle-layout",
+        "next-workspace",
+        "workspace-towards",
+        "toggle-workspace",
+        "workspace-back",
+        "move-to-next-workspace",
+        "change-gap-size",
+        "reset-gap-size",
+        "change-main-count",
+        "change-main-factor",
+        "change-margin",
+        "reset-margin",
+        "copy-prev-layout-data",
+        "reset-layout-data",
+        "set-floating",
+        "set-fullscreen",
+        "set-maximized",
+        "set-contained",
+        "set-invincible",
+        "set-producing",
+        "set-iconifyable",
+        "set-iconify",
+        "set-stick",
+        "pop-deiconify",
+        "deiconify-all",
+        "center",
+        "snap",
+        "nudge",
+        "stretch",
+        "grow-ratio",
+        "pip",
+        "pause",
+        "resume",
+        "teleport",
+        "hint",
+        "toggle-pass-through",
+        "confine-pointer",
+        "toggle-screen-struts",
+        "toggle-debug-hud",
+        "reload-config",
+    ];
+
+    // `None` if the name is not that of an action
+    pub fn parse<'a>(
+        name: &str,
+        mut args: impl Iterator<Item = &'a str>,
+    ) -> Option<Result<Self>> {
+        let mut next = || args.next();
+
+        let action = match name {
+            "cycle-focus" => direction(next()).map(Self::CycleFocus),
+            "drag-focus" => direction(next()).map(Self::DragFocus),
+            "rotate-clients" => direction(next()).map(Self::RotateClients),
+            "cycle-same-class" => direction(next()).map(Self::CycleSameClass),
+            "cycle-fullscreen" => direction(next()).map(Self::CycleFullscreen),
+            "cycle-zones" => direction(next()).map(Self::CycleZones),
+            "toggle-zone" => Ok(Self::ToggleZone),
+            "create-layout-zone" => Ok(Self::CreateLayoutZone),
+            "create-tab-zone" => Ok(Self::CreateTabZone),
+            "delete-zone" => Ok(Self::DeleteZone),
+            "mark-zone" => Ok(Self::MarkZone),
+            "expand-in-zone" => Ok(Self::ExpandInZone),
+            "cycle-layout" => direction(next()).map(Self::CycleLayout),
+            "toggle-layout" => Ok(Self::ToggleLayout),
+            "next-workspace" => direction(next()).map(Self::NextWorkspace),
+            "workspace-towards" => edge(next()).map(Self::WorkspaceTowards),
+            "toggle-workspace" => Ok(Self::ToggleWorkspace),
+            "workspace-back" => Ok(Self::WorkspaceBack),
+            "move-to-next-workspace" => direction(next()).map(Self::MoveToNextWorkspace),
+            "change-gap-size" => change(next()).map(Self::ChangeGapSize),
+            "reset-gap-size" => Ok(Self::ResetGapSize),
+            "change-main-count" => change(next()).map(Self::ChangeMainCount),
+            "change-main-factor" => change(next()).map(Self::ChangeMainFactor),
+            "change-margin" => edge(next())
+                .and_then(|edge| change(next()).map(|change| Self::ChangeMargin(edge, change))),
+            "reset-margin" => Ok(Self::ResetMargin),
+            "copy-prev-layout-data" => Ok(Self::CopyPrevLayoutData),
+            "reset-layout-data" => Ok(Self::ResetLayoutData),
+            "set-floating" => toggle(next()).map(Self::SetFloating),
+            "set-fullscreen" => toggle(next()).map(Self::SetFullscreen),
+            "set-maximized" => {
+                let state = match next() {
+                    Some("vert") => Ok(WindowState::MaximizedVert),
+                    Some("horz") => Ok(WindowState::MaximizedHorz),
+                    Some(axis) => Err(anyhow!("unknown axis {}", axis)),
+                    None => Err(anyhow!("missing axis")),
+                };
+
+                state.and_then(|state| {
+                    toggle(next()).map(|toggle| Self::SetMaximized(state, toggle))
+                })
+            },
+            "set-contained" => toggle(next()).map(Self::SetContained),
+            "set-invincible" => toggle(next()).map(Self::SetInvincible),
+            "set-producing" => toggle(next()).map(Self::SetProducing),
+            "set-iconifyable" => toggle(next()).map(Self::SetIconifyable),
+            "set-iconify" => toggle(next()).map(Self::SetIconify),
+            "set-stick" => toggle(next()).map(Self::SetStick),
+            "pop-deiconify" => Ok(Self::PopDeiconify),
+            "deiconify-all" => Ok(Self::DeiconifyAll),
+            "center" => Ok(Self::Center),
+            "snap" => edge(next()).map(Self::Snap),
+            "nudge" => edge(next())
+                .and_then(|edge| number(next(), "step").map(|step| Self::Nudge(edge, step))),
+            "stretch" => edge(next())
+                .and_then(|edge| number(next(), "step").map(|step| Self::Stretch(edge, step))),
+            "grow-ratio" => number(next(), "step").map(Self::GrowRatio),
+            "pip" => Ok(Self::Pip),
+            "pause" => Ok(Self::Pause),
+            "resume" => Ok(Self::Resume),
+            "teleport" => Ok(Self::Teleport),
+            "hint" => Ok(Self::Hint),
+            "toggle-pass-through" => Ok(Self::TogglePassThrough),
+            "confine-pointer" => Ok(Self::ConfinePointer),
+            "toggle-screen-struts" => Ok(Self::ToggleScreenStruts),
+            "toggle-debug-hud" => Ok(Self::ToggleDebugHud),
+            "reload-config" => Ok(Self::ReloadConfig),
+            _ => return None,
+        };
+
+        Some(action)
+    }
+}
+
+pub fn edge(arg: Option<&str>) -> Result<Edge> {
+    match arg {
+        Some("left") => Ok(Edge::Left),
+        Some("right") => Ok(Edge::Right),
+        Some("top") => Ok(Edge::Top),
+        Some("bottom") => Ok(Edge::Bottom),
+        Some(edge) => Err(anyhow!("unknown edge {}", edge)),
+        None => Err(anyhow!("missing edge")),
+    }
+}
+
+fn direction(arg: Option<&str>) -> Result<Direction> {
+    match arg {
+        Some("forward") | Some("next") => Ok(Direction::Forward),
+        Some("backward") | Some("prev") => Ok(Direction::Backward),
+        Some(dir) => Err(anyhow!("unknown direction {}", dir)),
+        None => Err(anyhow!("missing direction")),
+    }
+}
+
+// toggles by default
+fn toggle(arg: Option<&str>) -> Result<Toggle> {
+    match arg {
+        Some("on") => Ok(Toggle::On),
+        Some("off") => Ok(Toggle::Off),
+        Some("toggle") | None => Ok(Toggle::Reverse),
+        Some(toggle) => Err(anyhow!("unknown toggle {}", toggle)),
+    }
+}
+
+fn number<T: FromStr>(
+    arg: Option<&str>,
+    what: &str,
+) -> Result<T> {
+    match arg {
+        Some(arg) => arg.parse().map_err(|_| anyhow!("invalid {} {}", what, arg)),
+        None => Err(anyhow!("missing {}", what)),
+    }
+}
+
+// changes are signed, as in +5 or -0.05
+fn change<T>(arg: Option<&str>) -> Result<Change<T>>
+where
+    T: FromStr + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+{
+    let arg = arg.ok_or_else(|| anyhow!("missing change"))?;
+
+    if let Some(delta) = arg.strip_prefix('+') {
+        number(Some(delta), "change").map(Change::Inc)
+    } else if let Some(delta) = arg.strip_prefix('-') {
+        number(Some(delta), "change").map(Change::Dec)
+    } else {
+        Err(anyhow!("change {} lacks a sign", arg))
+    }
+}
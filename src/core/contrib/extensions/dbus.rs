@@ -0,0 +1,235 @@
+use crate::config::IpcConfig;
+use crate::contrib::extensions::ipc;
+use crate::contrib::extensions::ipc::IpcAccess;
+use crate::contrib::extensions::ipc::IpcCommand;
+
+use winsys::window::Window;
+use winsys::Result;
+
+use std::os::unix::io::RawFd;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use anyhow::anyhow;
+
+use nix::fcntl::OFlag;
+use nix::unistd;
+
+use zbus::blocking::Connection;
+use zbus::blocking::ConnectionBuilder;
+use zbus::dbus_interface;
+use zbus::fdo;
+use zbus::SignalContext;
+
+const NAME: &str = "org.wzrd.WindowManager";
+const PATH: &str = "/org/wzrd/WindowManager";
+const REPLY_TIMEOUT: Duration = Duration::from_secs(1);
+
+type Reply = std::result::Result<String, String>;
+
+#[derive(Debug)]
+pub struct DbusRequest {
+    pub access: IpcAccess,
+    pub command: IpcCommand,
+    reply: mpsc::Sender<Reply>,
+}
+
+impl DbusRequest {
+    pub fn reply(
+        self,
+        reply: Result<String>,
+    ) {
+        drop(self.reply.send(reply.map_err(|err| err.to_string())));
+    }
+}
+
+// method calls arrive on the thread of the connection, they are handed to the
+// event loop, which is woken up through a pipe
+struct Interface {
+    requests: mpsc::Sender<DbusRequest>,
+    wake: RawFd,
+    token: Option<String>,
+    read_only_token: Option<String>,
+}
+
+impl Interface {
+    // any peer on the session bus may call the methods, so the tokens of the socket
+    // apply to each of them, an empty token standing for none
+    fn authorize(
+        &self,
+        token: &str,
+        command: IpcCommand,
+    ) -> fdo::Result<String> {
+        let access = ipc::access(
+            &self.token,
+            &self.read_only_token,
+            Some(token).filter(|token| !token.is_empty()),
+        );
+
+        if !access.permits(&command) {
+            return Err(fdo::Error::AccessDenied("permission denied".to_owned()));
+        }
+
+        self.request(access, command)
+    }
+
+    fn request(
+        &self,
+        access: IpcAccess,
+        command: IpcCommand,
+    ) -> fdo::Result<String> {
+        let (reply, replies) = mpsc::channel();
+
+        self.requests
+            .send(DbusRequest {
+                access,
+                command,
+                reply,
+            })
+            .map_err(|_| fdo::Error::Failed("window manager is gone".to_owned()))?;
+
+        unistd::write(self.wake, &[0]).ok();
+
+        match replies.recv_timeout(REPLY_TIMEOUT) {
+            Ok(reply) => reply.map_err(fdo::Error::Failed),
+            Err(_) => Err(fdo::Error::Failed("no reply from window manager".to_owned())),
+        }
+    }
+}
+
+impl Drop for Interface {
+    fn drop(&mut self) {
+        unistd::close(self.wake).ok();
+    }
+}
+
+#[dbus_interface(name = "org.wzrd.WindowManager")]
+impl Interface {
+    fn activate_workspace(
+        &self,
+        token: &str,
+        workspace: u32,
+    ) -> fdo::Result<()> {
+        self.authorize(token, IpcCommand::Workspace(workspace as usize))
+            .map(drop)
+    }
+
+    // the window, workspace, class and name of every managed client, taken from the
+    // reply to the clients command
+    fn list_clients(
+        &self,
+        token: &str,
+    ) -> fdo::Result<Vec<(u32, u32, String, String)>> {
+        Ok(self
+            .authorize(token, IpcCommand::Clients)?
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.splitn(5, ' ');
+                let window = fields.next()?.strip_prefix("0x")?;
+                let window = Window::from_str_radix(window, 16).ok()?;
+                let workspace = fields.next()?.parse().ok()?;
+                let class = fields.nth(1)?.to_owned();
+                let name = fields.next().unwrap_or_default().to_owned();
+
+                Some((window, workspace, class, name))
+            })
+            .collect())
+    }
+
+    // any command of the socket protocol
+    fn command(
+        &self,
+        token: &str,
+        command: &str,
+    ) -> fdo::Result<String> {
+        let command =
+            IpcCommand::parse(command).map_err(|err| fdo::Error::InvalidArgs(err.to_string()))?;
+
+        self.authorize(token, command)
+    }
+
+    // the window is 0 if no client has focus
+    #[dbus_interface(signal)]
+    async fn focus_changed(
+        ctxt: &SignalContext<'_>,
+        window: u32,
+        title: &str,
+    ) -> zbus::Result<()>;
+}
+
+pub struct DbusService {
+    connection: Connection,
+    requests: mpsc::Receiver<DbusRequest>,
+    wake: RawFd,
+}
+
+impl DbusService {
+    pub fn connect(config: &IpcConfig) -> Result<Self> {
+        let (requests, receiver) = mpsc::channel();
+        let (wake, wake_write) = unistd::pipe2(OFlag::O_NONBLOCK | OFlag::O_CLOEXEC)
+            .map_err(|err| anyhow!("could not create pipe: {}", err))?;
+
+        let interface = Interface {
+            requests,
+            wake: wake_write,
+            token: config.token.clone(),
+            read_only_token: config.read_only_token.clone(),
+        };
+
+        let connection = ConnectionBuilder::session()
+            .and_then(|builder| builder.name(NAME))
+            .and_then(|builder| builder.serve_at(PATH, interface))
+            .and_then(|builder| builder.build())
+            .map_err(|err| {
+                unistd::close(wake).ok();
+                anyhow!("could not register {}: {}", NAME, err)
+            })?;
+
+        info!("offering D-Bus service {}", NAME);
+
+        Ok(Self {
+            connection,
+            requests: receiver,
+            wake,
+        })
+    }
+
+    #[inline]
+    pub fn fd(&self) -> RawFd {
+        self.wake
+    }
+
+    pub fn pending(&self) -> Vec<DbusRequest> {
+        let mut buffer = [0; 64];
+        while let Ok(read) = unistd::read(self.wake, &mut buffer) {
+            if read == 0 {
+                break;
+            }
+        }
+
+        self.requests.try_iter().collect()
+    }
+
+    pub fn focus_changed(
+        &self,
+        window: Option<Window>,
+        title: &str,
+    ) {
+        if let Err(err) = self.connection.emit_signal(
+            None::<&str>,
+            PATH,
+            NAME,
+            "FocusChanged",
+            &(window.unwrap_or(0), title),
+        ) {
+            warn!("could not emit FocusChanged signal: {}", err);
+        }
+    }
+}
+
+// the write end is closed along with the interface
+impl Drop for DbusService {
+    fn drop(&mut self) {
+        unistd::close(self.wake).ok();
+    }
+}
@@ -1 +1,586 @@
+use crate::config::IpcConfig;
+use crate::contrib::actions;
+use crate::contrib::actions::Action;
+use crate::layout::LayoutKind;
+use crate::rule::Rule;
+use crate::selector::WindowSelector;
 
+use winsys::geometry::Edge;
+use winsys::window::Window;
+use winsys::Result;
+
+use std::cell::RefCell;
+use std::env;
+use std::fs;
+use std::io;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Read;
+use std::io::Write;
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::io::AsRawFd;
+use std::os::unix::io::RawFd;
+use std::os::unix::net::UnixListener;
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::anyhow;
+
+use nix::fcntl::OFlag;
+use nix::sys::stat::Mode;
+use nix::unistd;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum IpcCommand {
+    ReloadRules {
+        apply: bool,
+        workspace: bool,
+    },
+    ApplyRules(WindowSelector),
+    AddRule(Rule),
+    ListRules,
+    RemoveRule(usize),
+    IgnoreStruts(Window),
+    Focus(WindowSelector),
+    Close(WindowSelector),
+    ToggleFloat(WindowSelector),
+    ToggleFullscreen(WindowSelector),
+    Flash(WindowSelector),
+    SendToWorkspace(usize, WindowSelector),
+    Workspace(usize),
+    SetLayout(LayoutKind),
+    Spawn(String),
+    Run(String),
+    SaveLayout(String),
+    ApplyLayout(String, Option<usize>),
+    SetMargin(Edge, i32, Option<usize>),
+    MarginPreset(String, Option<usize>),
+    Action(Action),
+    Lock,
+    Clients,
+    Consumers,
+    Consume(WindowSelector),
+    Unconsume,
+    Usage,
+    Subscribe,
+    Timings {
+        reset: bool,
+    },
+    Version,
+    Features,
+    ConfigError,
+}
+
+impl IpcCommand {
+    pub const NAMES: &'static [&'static str] = &[
+        "reload-rules",
+        "apply-rules",
+        "rule",
+        "focus",
+        "close",
+        "toggle-float",
+        "toggle-fullscreen",
+        "flash",
+        "send-to-workspace",
+        "ignore-struts",
+        "save-layout",
+        "apply-layout",
+        "set-margin",
+        "margin-preset",
+        "workspace",
+        "set-layout",
+        "spawn",
+        "run",
+        "lock",
+        "clients",
+        "consumers",
+        "consume",
+        "unconsume",
+        "usage",
+        "subscribe",
+        "timings",
+        "version",
+        "features",
+        "config-error",
+    ];
+
+    pub fn is_read_only(&self) -> bool {
+        matches!(
+            self,
+            Self::ListRules
+                | Self::Clients
+                | Self::Consumers
+                | Self::Usage
+                | Self::Subscribe
+                | Self::Timings {
+                    reset: false
+                }
+                | Self::Version
+                | Self::Features
+                | Self::ConfigError
+        )
+    }
+
+    pub fn parse(line: &str) -> Result<Self> {
+        let mut args = line.split_whitespace();
+        let selector = |arg: Option<&str>| match arg {
+            Some(arg) => WindowSelector::parse(arg),
+            None => Ok(WindowSelector::Focused),
+        };
+
+        match args.next() {
+            Some("reload-rules") => {
+                let mut apply = false;
+                let mut workspace = false;
+
+                for arg in args {
+                    match arg {
+                        "--apply" => apply = true,
+                        "--workspace" => workspace = true,
+                        _ => return Err(anyhow!("unknown argument {}", arg)),
+                    }
+                }
+
+                Ok(Self::ReloadRules {
+                    apply,
+                    workspace,
+                })
+            },
+            Some("apply-rules") => selector(args.next()).map(Self::ApplyRules),
+            Some("rule") => match args.next() {
+                Some("add") => Rule::parse(args).map(Self::AddRule),
+                Some("list") => Ok(Self::ListRules),
+                Some("remove") => match args.next().map(str::parse) {
+                    Some(Ok(index)) => Ok(Self::RemoveRule(index)),
+                    Some(Err(err)) => Err(anyhow!("invalid rule index: {}", err)),
+                    None => Err(anyhow!("missing rule index")),
+                },
+                Some(arg) => Err(anyhow!("unknown rule command {}", arg)),
+                None => Err(anyhow!("missing rule command")),
+            },
+            Some("focus") => selector(args.next()).map(Self::Focus),
+            Some("close") => selector(args.next()).map(Self::Close),
+            Some("toggle-float") => selector(args.next()).map(Self::ToggleFloat),
+            Some("toggle-fullscreen") => selector(args.next()).map(Self::ToggleFullscreen),
+            Some("flash") => selector(args.next()).map(Self::Flash),
+            Some("send-to-workspace") => match args.next().map(str::parse) {
+                Some(Ok(workspace)) => {
+                    selector(args.next()).map(|sel| Self::SendToWorkspace(workspace, sel))
+                },
+                Some(Err(err)) => Err(anyhow!("invalid workspace: {}", err)),
+                None => Err(anyhow!("missing workspace")),
+            },
+            Some("ignore-struts") => match args.next() {
+                Some(window) => {
+                    let window = if let Some(hex) = window.strip_prefix("0x") {
+                        Window::from_str_radix(hex, 16)
+                    } else {
+                        window.parse()
+                    };
+
+                    window
+                        .map(Self::IgnoreStruts)
+                        .map_err(|err| anyhow!("invalid window: {}", err))
+                },
+                None => Err(anyhow!("missing window")),
+            },
+            Some("save-layout") => match args.next() {
+                Some(name) => Ok(Self::SaveLayout(name.to_owned())),
+                None => Err(anyhow!("missing profile name")),
+            },
+            Some("apply-layout") => match args.next() {
+                Some(name) => match args.next().map(str::parse).transpose() {
+                    Ok(workspace) => Ok(Self::ApplyLayout(name.to_owned(), workspace)),
+                    Err(err) => Err(anyhow!("invalid workspace: {}", err)),
+                },
+                None => Err(anyhow!("missing profile name")),
+            },
+            Some("set-margin") => {
+                let edge = actions::edge(args.next())?;
+
+                let value = match args.next().map(str::parse) {
+                    Some(Ok(value)) => value,
+                    Some(Err(err)) => return Err(anyhow!("invalid margin: {}", err)),
+                    None => return Err(anyhow!("missing margin")),
+                };
+
+                match args.next().map(str::parse).transpose() {
+                    Ok(workspace) => Ok(Self::SetMargin(edge, value, workspace)),
+                    Err(err) => Err(anyhow!("invalid workspace: {}", err)),
+                }
+            },
+            Some("margin-preset") => match args.next() {
+                Some(name) => match args.next().map(str::parse).transpose() {
+                    Ok(workspace) => Ok(Self::MarginPreset(name.to_owned(), workspace)),
+                    Err(err) => Err(anyhow!("invalid workspace: {}", err)),
+                },
+                None => Err(anyhow!("missing preset name")),
+            },
+            Some("workspace") => match args.next().map(str::parse) {
+                Some(Ok(workspace)) => Ok(Self::Workspace(workspace)),
+                Some(Err(err)) => Err(anyhow!("invalid workspace: {}", err)),
+                None => Err(anyhow!("missing workspace")),
+            },
+            Some("set-layout") => match args.next() {
+                Some(name) => LayoutKind::from_name(name)
+                    .map(Self::SetLayout)
+                    .ok_or_else(|| anyhow!("unknown layout {}", name)),
+                None => Err(anyhow!("missing layout")),
+            },
+            Some("spawn") => match args.collect::<Vec<&str>>().join(" ") {
+                command if command.is_empty() => Err(anyhow!("missing command")),
+                command => Ok(Self::Spawn(command)),
+            },
+            Some("run") => match args.next() {
+                Some(name) => Ok(Self::Run(name.to_owned())),
+                None => Err(anyhow!("missing action name")),
+            },
+            Some("lock") => Ok(Self::Lock),
+            Some("clients") => Ok(Self::Clients),
+            Some("consumers") => Ok(Self::Consumers),
+            Some("consume") => match args.next() {
+                Some(producer) => WindowSelector::parse(producer).map(Self::Consume),
+                None => Err(anyhow!("missing producer")),
+            },
+            Some("unconsume") => Ok(Self::Unconsume),
+            Some("usage") => Ok(Self::Usage),
+            Some("subscribe") => Ok(Self::Subscribe),
+            Some("timings") => match args.next() {
+                Some("--reset") => Ok(Self::Timings {
+                    reset: true,
+                }),
+                Some(arg) => Err(anyhow!("unknown argument {}", arg)),
+                None => Ok(Self::Timings {
+                    reset: false,
+                }),
+            },
+            Some("version") => Ok(Self::Version),
+            Some("features") => Ok(Self::Features),
+            Some("config-error") => Ok(Self::ConfigError),
+            Some(command) => Action::parse(command, args)
+                .unwrap_or_else(|| Err(anyhow!("unknown command {}", command)))
+                .map(Self::Action),
+            None => Err(anyhow!("empty command")),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum IpcAccess {
+    Full,
+    ReadOnly,
+    Denied,
+}
+
+impl IpcAccess {
+    pub fn permits(
+        &self,
+        command: &IpcCommand,
+    ) -> bool {
+        match self {
+            Self::Full => true,
+            Self::ReadOnly => command.is_read_only(),
+            Self::Denied => false,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct IpcRequest {
+    stream: UnixStream,
+    pub access: IpcAccess,
+    pub command: Result<IpcCommand>,
+}
+
+impl IpcRequest {
+    pub fn reply(
+        mut self,
+        reply: Result<String>,
+    ) {
+        let reply = match reply {
+            Ok(reply) if reply.is_empty() => "ok\n".to_owned(),
+            Ok(reply) => format!("ok\n{}\n", reply.trim_end()),
+            Err(err) => format!("error: {}\n", err),
+        };
+
+        if let Err(err) = self.stream.write_all(reply.as_bytes()) {
+            warn!("could not reply to IPC request: {}", err);
+        }
+    }
+}
+
+// a subscriber is sent a line whenever the status changes, until it hangs up;
+// subscribing is read-only, so clients holding only the read-only token can
+// follow the window manager without being able to drive it
+#[derive(Debug)]
+struct IpcSubscriber {
+    stream: UnixStream,
+}
+
+#[derive(Debug)]
+pub struct IpcServer {
+    listener: UnixListener,
+    path: PathBuf,
+    token: Option<String>,
+    read_only_token: Option<String>,
+    subscribers: RefCell<Vec<IpcSubscriber>>,
+    published: RefCell<String>,
+}
+
+impl IpcServer {
+    const READ_TIMEOUT: Duration = Duration::from_millis(200);
+
+    pub fn socket_path() -> PathBuf {
+        if let Some(path) = env::var_os("WZRD_SOCKET") {
+            return PathBuf::from(path);
+        }
+
+        let display = env::var("DISPLAY")
+            .unwrap_or_default()
+            .replace(|c: char| !c.is_ascii_alphanumeric() && c != '.', "");
+
+        env::var_os("XDG_RUNTIME_DIR")
+            .map_or_else(env::temp_dir, PathBuf::from)
+            .join(format!("{}-{}.sock", WM_NAME!(), display))
+    }
+
+    pub fn bind(config: &IpcConfig) -> io::Result<Self> {
+        let path = Self::socket_path();
+
+        if UnixStream::connect(&path).is_ok() {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                format!("{} is in use", path.display()),
+            ));
+        }
+
+        drop(fs::remove_file(&path));
+
+        let listener = UnixListener::bind(&path)?;
+        listener.set_nonblocking(true)?;
+        fs::set_permissions(&path, fs::Permissions::from_mode(config.socket_mode))?;
+
+        info!("listening for IPC requests on {}", path.display());
+
+        Ok(Self {
+            listener,
+            path,
+            token: config.token.clone(),
+            read_only_token: config.read_only_token.clone(),
+            subscribers: RefCell::new(Vec::new()),
+            published: RefCell::new(String::new()),
+        })
+    }
+
+    #[inline]
+    pub fn fd(&self) -> RawFd {
+        self.listener.as_raw_fd()
+    }
+
+    pub fn accept(&self) -> Option<IpcRequest> {
+        let stream = match self.listener.accept() {
+            Ok((stream, _)) => stream,
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => return None,
+            Err(err) => {
+                warn!("could not accept IPC connection: {}", err);
+                return None;
+            },
+        };
+
+        let mut token = None;
+        let mut line = String::new();
+        let command = stream
+            .set_nonblocking(false)
+            .and_then(|_| stream.set_read_timeout(Some(Self::READ_TIMEOUT)))
+            .and_then(|_| {
+                let mut reader = BufReader::new(&stream);
+                reader.read_line(&mut line)?;
+
+                // an optional token line precedes the command
+                if let Some(value) = line.strip_prefix("token ") {
+                    token = Some(value.trim().to_owned());
+                    line.clear();
+                    reader.read_line(&mut line)?;
+                }
+
+                Ok(())
+            })
+            .map_err(|err| anyhow!("could not read request: {}", err))
+            .and_then(|_| IpcCommand::parse(&line));
+
+        debug!("IPC request {:?}", line.trim_end());
+
+        Some(IpcRequest {
+            stream,
+            access: self.access_for(token.as_deref()),
+            command,
+        })
+    }
+
+    pub fn subscribe(
+        &self,
+        request: IpcRequest,
+    ) {
+        // the current status is sent right away, changes follow
+        let mut stream = request.stream;
+        let subscribed = stream
+            .write_all(format!("ok\n{}", self.published.borrow()).as_bytes())
+            .and_then(|_| stream.set_nonblocking(true));
+
+        match subscribed {
+            Ok(()) => self.subscribers.borrow_mut().push(IpcSubscriber {
+                stream,
+            }),
+            Err(err) => warn!("could not add IPC subscriber: {}", err),
+        }
+    }
+
+    // subscribers that cannot keep up or have gone away are dropped
+    pub fn publish(
+        &self,
+        line: &str,
+    ) {
+        let line = format!("{}\n", line.replace('\n', " "));
+
+        if *self.published.borrow() == line {
+            return;
+        }
+
+        self.published.replace(line.clone());
+        self.subscribers
+            .borrow_mut()
+            .retain(|subscriber| (&subscriber.stream).write_all(line.as_bytes()).is_ok());
+    }
+
+    fn access_for(
+        &self,
+        token: Option<&str>,
+    ) -> IpcAccess {
+        access(&self.token, &self.read_only_token, token)
+    }
+}
+
+pub fn access(
+    full: &Option<String>,
+    read_only: &Option<String>,
+    token: Option<&str>,
+) -> IpcAccess {
+    match (full, read_only) {
+        (None, None) => IpcAccess::Full,
+        (Some(full), _) if token == Some(full.as_str()) => IpcAccess::Full,
+        (_, Some(read_only)) if token == Some(read_only.as_str()) => IpcAccess::ReadOnly,
+        _ => IpcAccess::Denied,
+    }
+}
+
+impl Drop for IpcServer {
+    fn drop(&mut self) {
+        drop(fs::remove_file(&self.path));
+    }
+}
+
+// commands written to the FIFO, one per line, are run without a reply; as on the
+// socket, a token line may precede a command
+#[derive(Debug)]
+pub struct IpcFifo {
+    fifo: fs::File,
+    path: PathBuf,
+    buffer: Vec<u8>,
+    pending_token: Option<String>,
+    token: Option<String>,
+    read_only_token: Option<String>,
+}
+
+impl IpcFifo {
+    const MAX_LINE: usize = 4096;
+
+    pub fn open(
+        path: PathBuf,
+        config: &IpcConfig,
+    ) -> Result<Self> {
+        match fs::metadata(&path) {
+            Ok(metadata) if metadata.file_type().is_fifo() => {},
+            Ok(_) => return Err(anyhow!("{} exists and is not a FIFO", path.display())),
+            Err(_) => unistd::mkfifo(&path, Mode::from_bits_truncate(config.socket_mode))?,
+        }
+
+        fs::set_permissions(&path, fs::Permissions::from_mode(config.socket_mode))?;
+
+        // holding the write end as well keeps the FIFO from reporting end of file
+        // whenever the last writer goes away
+        let fifo = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(OFlag::O_NONBLOCK.bits())
+            .open(&path)?;
+
+        info!("reading IPC commands from {}", path.display());
+
+        Ok(Self {
+            fifo,
+            path,
+            buffer: Vec::new(),
+            pending_token: None,
+            token: config.token.clone(),
+            read_only_token: config.read_only_token.clone(),
+        })
+    }
+
+    #[inline]
+    pub fn fd(&self) -> RawFd {
+        self.fifo.as_raw_fd()
+    }
+
+    pub fn pending(&mut self) -> Vec<(IpcAccess, Result<IpcCommand>)> {
+        let mut chunk = [0; 1024];
+
+        loop {
+            match self.fifo.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(read) => self.buffer.extend_from_slice(&chunk[..read]),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {},
+                Err(_) => break,
+            }
+        }
+
+        let mut commands = Vec::new();
+
+        while let Some(end) = self.buffer.iter().position(|&byte| byte == b'\n') {
+            let line = self.buffer.drain(..=end).collect::<Vec<u8>>();
+            let line = String::from_utf8_lossy(&line);
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            if let Some(value) = line.strip_prefix("token ") {
+                self.pending_token = Some(value.trim().to_owned());
+                continue;
+            }
+
+            debug!("IPC command {:?} from FIFO", line.trim_end());
+
+            let token = self.pending_token.take();
+            commands.push((
+                access(&self.token, &self.read_only_token, token.as_deref()),
+                IpcCommand::parse(&line),
+            ));
+        }
+
+        if self.buffer.len() > Self::MAX_LINE {
+            warn!("discarding overlong line from IPC FIFO");
+            self.buffer.clear();
+        }
+
+        commands
+    }
+}
+
+impl Drop for IpcFifo {
+    fn drop(&mut self) {
+        drop(fs::remove_file(&self.path));
+    }
+}
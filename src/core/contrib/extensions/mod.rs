@@ -1 +1,5 @@
-
+#[cfg(feature = "dbus")]
+pub mod dbus;
+pub mod ipc;
+#[cfg(feature = "scripting")]
+pub mod script;
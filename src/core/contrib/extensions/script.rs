@@ -0,0 +1,240 @@
+use crate::contrib::extensions::ipc::IpcCommand;
+use crate::layout::LayoutKind;
+use crate::rule::Rule;
+use crate::selector::WindowSelector;
+
+use winsys::input::KeyInput;
+use winsys::Result;
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::path::Path;
+use std::rc::Rc;
+
+use anyhow::anyhow;
+
+use rhai::Dynamic;
+use rhai::Engine;
+use rhai::EvalAltResult;
+use rhai::FnPtr;
+use rhai::Map;
+use rhai::AST;
+
+type ScriptResult<T> = std::result::Result<T, Box<EvalAltResult>>;
+
+// the handle passed to binding callbacks; actions are queued and executed as
+// commands once the callback returns, so scripts never touch the model directly
+#[derive(Clone)]
+pub struct ScriptHandle {
+    workspace: usize,
+    class: String,
+    commands: Rc<RefCell<Vec<IpcCommand>>>,
+}
+
+impl ScriptHandle {
+    fn push(
+        &mut self,
+        command: Result<IpcCommand>,
+    ) -> ScriptResult<()> {
+        command
+            .map(|command| self.commands.borrow_mut().push(command))
+            .map_err(|err| err.to_string().into())
+    }
+
+    fn jump(
+        &mut self,
+        selector: &str,
+    ) -> ScriptResult<()> {
+        self.push(WindowSelector::parse(selector).map(IpcCommand::Focus))
+    }
+
+    fn spawn(
+        &mut self,
+        command: &str,
+    ) -> ScriptResult<()> {
+        self.push(Ok(IpcCommand::Spawn(command.to_owned())))
+    }
+
+    fn set_layout(
+        &mut self,
+        name: &str,
+    ) -> ScriptResult<()> {
+        self.push(
+            LayoutKind::from_name(name)
+                .map(IpcCommand::SetLayout)
+                .ok_or_else(|| anyhow!("unknown layout {}", name)),
+        )
+    }
+
+    fn activate_workspace(
+        &mut self,
+        workspace: i64,
+    ) -> ScriptResult<()> {
+        self.push(
+            usize::try_from(workspace)
+                .map(IpcCommand::Workspace)
+                .map_err(|_| anyhow!("invalid workspace {}", workspace)),
+        )
+    }
+
+    fn run(
+        &mut self,
+        action: &str,
+    ) -> ScriptResult<()> {
+        self.push(Ok(IpcCommand::Run(action.to_owned())))
+    }
+}
+
+pub struct Script {
+    engine: Engine,
+    ast: AST,
+    bindings: HashMap<KeyInput, FnPtr>,
+    rules: Vec<Rule>,
+}
+
+impl Script {
+    // bindings and rules are collected while the script's top level runs
+    pub fn load(path: &Path) -> Result<Self> {
+        let bindings = Rc::new(RefCell::new(Vec::<(String, FnPtr)>::new()));
+        let rules = Rc::new(RefCell::new(Vec::<Map>::new()));
+        let mut engine = Engine::new();
+
+        engine
+            .register_type_with_name::<ScriptHandle>("Wm")
+            .register_get("active_workspace", |wm: &mut ScriptHandle| wm.workspace as i64)
+            .register_get("class", |wm: &mut ScriptHandle| wm.class.clone())
+            .register_fn("jump", ScriptHandle::jump)
+            // spawn is a reserved keyword in rhai
+            .register_fn("exec", ScriptHandle::spawn)
+            .register_fn("set_layout", ScriptHandle::set_layout)
+            .register_fn("workspace", ScriptHandle::activate_workspace)
+            .register_fn("run", ScriptHandle::run);
+
+        {
+            let bindings = bindings.clone();
+            engine.register_fn("bind", move |keys: &str, callback: FnPtr| {
+                bindings.borrow_mut().push((keys.to_owned(), callback));
+            });
+        }
+
+        {
+            let rules = rules.clone();
+            engine.register_fn("rule", move |rule: Map| {
+                rules.borrow_mut().push(rule);
+            });
+        }
+
+        let ast = engine
+            .compile_file(path.to_owned())
+            .map_err(|err| anyhow!("could not compile {}: {}", path.display(), err))?;
+
+        engine
+            .run_ast(&ast)
+            .map_err(|err| anyhow!("could not run {}: {}", path.display(), err))?;
+
+        let bindings = bindings
+            .take()
+            .into_iter()
+            .map(|(keys, callback)| Ok((KeyInput::try_from(keys.as_str())?, callback)))
+            .collect::<Result<HashMap<KeyInput, FnPtr>>>()?;
+
+        let rules = rules
+            .take()
+            .into_iter()
+            .map(Self::rule)
+            .collect::<Result<Vec<Rule>>>()?;
+
+        Ok(Self {
+            engine,
+            ast,
+            bindings,
+            rules,
+        })
+    }
+
+    fn rule(map: Map) -> Result<Rule> {
+        let mut rule: Rule = Default::default();
+
+        for (key, value) in map {
+            let invalid = |value: Dynamic| anyhow!("invalid value {} for rule field {}", value, key);
+
+            match key.as_str() {
+                "name" | "class" | "instance" => {
+                    let value = Some(value.clone().into_string().map_err(|_| invalid(value))?);
+
+                    match key.as_str() {
+                        "name" => rule.name = value,
+                        "class" => rule.class = value,
+                        _ => rule.instance = value,
+                    }
+                },
+                "float" | "center" | "fullscreen" | "dedicated" => {
+                    let value = Some(value.as_bool().map_err(|_| invalid(value))?);
+
+                    match key.as_str() {
+                        "float" => rule.float = value,
+                        "center" => rule.center = value,
+                        "fullscreen" => rule.fullscreen = value,
+                        _ => rule.dedicated = value,
+                    }
+                },
+                "workspace" => {
+                    rule.workspace = Some(
+                        value
+                            .as_int()
+                            .ok()
+                            .and_then(|workspace| usize::try_from(workspace).ok())
+                            .ok_or_else(|| invalid(value))?,
+                    );
+                },
+                _ => return Err(anyhow!("unknown rule field {}", key)),
+            }
+        }
+
+        Ok(rule)
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &KeyInput> {
+        self.bindings.keys()
+    }
+
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    pub fn is_bound(
+        &self,
+        input: &KeyInput,
+    ) -> bool {
+        self.bindings.contains_key(input)
+    }
+
+    // the commands the callback queued, in order
+    pub fn call(
+        &self,
+        input: &KeyInput,
+        workspace: usize,
+        class: String,
+    ) -> Result<Vec<IpcCommand>> {
+        let callback = self
+            .bindings
+            .get(input)
+            .ok_or_else(|| anyhow!("no script binding for {:?}", input))?;
+
+        let handle = ScriptHandle {
+            workspace,
+            class,
+            commands: Rc::new(RefCell::new(Vec::new())),
+        };
+
+        // whatever the callback evaluates to is ignored
+        callback
+            .call::<Dynamic>(&self.engine, &self.ast, (handle.clone(),))
+            .map(drop)
+            .map_err(|err| anyhow!("script binding failed: {}", err))?;
+
+        Ok(handle.commands.take())
+    }
+}
+
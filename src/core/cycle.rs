@@ -709,6 +709,17 @@ where
         self.stack.borrow().as_vecdeque()
     }
 
+    #[inline]
+    pub fn clear_stack(&self) {
+        self.stack.borrow_mut().clear();
+    }
+
+    // takes the most recently active element off the history stack
+    #[inline]
+    pub fn unwind(&self) -> Option<Ident> {
+        self.pop_from_stack()
+    }
+
     pub fn stack_after_focus(&self) -> Vec<Ident> {
         let mut stack: Vec<Ident> = self.stack.borrow().as_vec();
 
@@ -1,11 +1,14 @@
 use winsys::geometry::Extents;
 use winsys::geometry::Padding;
 
+use serde::Deserialize;
+
 use std::ops::Add;
 
 pub type Color = u32;
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
 pub struct ColorScheme {
     pub focused: Color,
     pub fdisowned: Color,
@@ -14,6 +17,7 @@ pub struct ColorScheme {
     pub udisowned: Color,
     pub usticky: Color,
     pub urgent: Color,
+    pub paused: Color,
 }
 
 impl ColorScheme {
@@ -25,6 +29,7 @@ impl ColorScheme {
         udisowned: 0x999999,
         usticky: 0x444444,
         urgent: 0xfbcb97,
+        paused: 0x1c1c1c,
     };
 }
 
@@ -34,6 +39,49 @@ impl Default for ColorScheme {
     }
 }
 
+impl ColorScheme {
+    // overrides the colors for which a (resource) value is found, keyed by the
+    // resource names under which they are looked up
+    pub fn with_overrides<F>(
+        mut self,
+        lookup: F,
+    ) -> Self
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        for (name, color) in [
+            ("focused", &mut self.focused),
+            ("focusedDisowned", &mut self.fdisowned),
+            ("focusedSticky", &mut self.fsticky),
+            ("unfocused", &mut self.unfocused),
+            ("unfocusedDisowned", &mut self.udisowned),
+            ("unfocusedSticky", &mut self.usticky),
+            ("urgent", &mut self.urgent),
+            ("paused", &mut self.paused),
+        ] {
+            if let Some(value) = lookup(name).as_deref().and_then(parse_color) {
+                *color = value;
+            }
+        }
+
+        self
+    }
+}
+
+// accepts #rrggbb, 0xrrggbb and rrggbb
+pub fn parse_color(value: &str) -> Option<Color> {
+    let hex = value
+        .strip_prefix('#')
+        .or_else(|| value.strip_prefix("0x"))
+        .unwrap_or(value);
+
+    if hex.len() != 6 {
+        return None;
+    }
+
+    Color::from_str_radix(hex, 16).ok()
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct Border {
     pub width: u32,
@@ -94,6 +142,22 @@ impl Default for Decoration {
 }
 
 impl Decoration {
+    pub fn with_colors(
+        self,
+        colors: ColorScheme,
+    ) -> Self {
+        Self {
+            border: self.border.map(|border| Border {
+                colors,
+                ..border
+            }),
+            frame: self.frame.map(|frame| Frame {
+                colors,
+                ..frame
+            }),
+        }
+    }
+
     pub fn extents(&self) -> Extents {
         Extents {
             left: 0,
@@ -102,6 +166,81 @@ impl Decoration {
             bottom: 0,
         } + *self
     }
+
+    pub fn uniform(&self) -> Self {
+        let extents = self.extents();
+        let width = extents
+            .left
+            .max(extents.right)
+            .max(extents.top)
+            .max(extents.bottom);
+
+        match (self.border, self.frame) {
+            (None, None) => *self,
+            (border, frame) => Self {
+                border: None,
+                frame: Some(Frame {
+                    extents: Extents {
+                        left: width,
+                        right: width,
+                        top: width,
+                        bottom: width,
+                    },
+                    colors: frame.map_or_else(
+                        || border.map_or(ColorScheme::DEFAULT, |border| border.colors),
+                        |frame| frame.colors,
+                    ),
+                }),
+            },
+        }
+    }
+}
+
+// a frame is given as its [left, right, top, bottom] extents
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct DecorationPreset {
+    pub border: u32,
+    pub frame: Option<[i32; 4]>,
+}
+
+impl DecorationPreset {
+    pub fn decoration(&self) -> Decoration {
+        Decoration {
+            border: if self.border > 0 {
+                Some(Border {
+                    width: self.border,
+                    colors: Default::default(),
+                })
+            } else {
+                None
+            },
+            frame: self.frame.map(|[left, right, top, bottom]| Frame {
+                extents: Extents {
+                    left,
+                    right,
+                    top,
+                    bottom,
+                },
+                colors: Default::default(),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct DecorationPresets {
+    pub tiled: Option<DecorationPreset>,
+    pub floating: Option<DecorationPreset>,
+    pub tabbed: Option<DecorationPreset>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DecorationStrategy {
+    Reparent,
+    Direct,
 }
 
 impl Add<Decoration> for Padding {
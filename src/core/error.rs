@@ -1,3 +1,6 @@
+use std::error::Error;
+use std::fmt;
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum StateChangeError {
     EarlyStop,
@@ -5,3 +8,19 @@ pub enum StateChangeError {
     StateUnchanged,
     InvalidCaller,
 }
+
+impl fmt::Display for StateChangeError {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        f.write_str(match self {
+            Self::EarlyStop => "stopped early",
+            Self::LimitReached => "limit reached",
+            Self::StateUnchanged => "state unchanged",
+            Self::InvalidCaller => "invalid caller",
+        })
+    }
+}
+
+impl Error for StateChangeError {}
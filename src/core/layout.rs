@@ -112,6 +112,20 @@ impl LayoutKind {
         self.to_string()
     }
 
+    // only the kinds that can be arranged are known by name
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::iter()
+            .filter(Self::is_implemented)
+            .find(|kind| kind.name().eq_ignore_ascii_case(name))
+    }
+
+    pub fn is_implemented(&self) -> bool {
+        !matches!(
+            self,
+            LayoutKind::Horz | LayoutKind::SHorz | LayoutKind::Vert | LayoutKind::SVert
+        )
+    }
+
     pub fn config(&self) -> LayoutConfig {
         match *self {
             LayoutKind::Float => LayoutConfig {
@@ -710,6 +724,26 @@ impl LayoutKind {
     }
 }
 
+// the gap size and margin every layout starts out with (and is reset to)
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct LayoutDefaults {
+    pub gap_size: u32,
+    pub margin: Padding,
+}
+
+impl LayoutDefaults {
+    pub fn apply(
+        &self,
+        data: LayoutData,
+    ) -> LayoutData {
+        LayoutData {
+            gap_size: self.gap_size,
+            margin: self.margin,
+            ..data
+        }
+    }
+}
+
 pub struct Layout {
     kind: LayoutKind,
     prev_kind: LayoutKind,
@@ -783,6 +817,21 @@ impl Layout {
         self.kind.default_data()
     }
 
+    // the data of every kind, whether it is the active one or not
+    #[inline]
+    pub fn all_data_mut(&mut self) -> Vec<&mut LayoutData> {
+        self.data.values_mut().collect()
+    }
+
+    pub fn apply_defaults(
+        &mut self,
+        defaults: &LayoutDefaults,
+    ) {
+        self.data
+            .values_mut()
+            .for_each(|data| *data = defaults.apply(*data));
+    }
+
     #[inline]
     pub fn set_kind(
         &mut self,
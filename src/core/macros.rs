@@ -7,6 +7,15 @@ macro_rules! hashset {
     }}
 }
 
+#[macro_export]
+macro_rules! bind(
+    ($bindings:expr, $names:expr, $input:expr, $name:expr, $action:expr $(,)?) => {{
+        let input = $input;
+        $names.insert(input.clone(), $name);
+        $bindings.insert(input, $action);
+    }};
+);
+
 #[macro_export]
 macro_rules! call(
     ($($method:tt)+) => {
@@ -15,9 +15,7 @@
 #[macro_use]
 extern crate log;
 
-#[allow(unused_imports)]
 use simplelog::LevelFilter;
-#[allow(unused_imports)]
 use simplelog::SimpleLogger;
 
 use winsys::geometry::Edge;
@@ -26,6 +24,12 @@ pub use winsys::Result;
 
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::env;
+use std::os::unix::io::AsRawFd;
+use std::time::Duration;
+
+use x11rb::connection::Connection;
+use x11rb::rust_connection::RustConnection;
 
 #[macro_use]
 mod macros;
@@ -33,11 +37,14 @@ mod macros;
 #[macro_use]
 mod defaults;
 
+mod autostart;
 mod binding;
 mod change;
 mod client;
 mod compare;
+mod config;
 mod consume;
+mod contrib;
 mod cycle;
 mod decoration;
 mod error;
@@ -45,23 +52,38 @@ mod identify;
 mod jump;
 mod layout;
 mod model;
+mod options;
 mod partition;
 mod placement;
+#[cfg(feature = "profiling")]
+mod profile;
+mod retired;
 mod rule;
+mod selector;
+mod signal;
 mod stack;
+mod state;
+mod status;
+mod timer;
+mod usage;
 mod util;
 mod workspace;
 mod zone;
 
+use binding::BindingNames;
 use binding::KeyBindings;
 use binding::MouseBindings;
+use binding::RepeatableKeys;
 use change::Change;
 use change::Direction;
 use change::Toggle;
 use compare::MatchMethod;
+use config::Config;
+use config::DragBindings;
 use jump::JumpCriterium;
 use layout::LayoutKind;
 use model::Model;
+use options::Options;
 use winsys::input::Button;
 use winsys::input::Key;
 use winsys::input::KeyInput;
@@ -73,32 +95,147 @@ use winsys::window::Window;
 use workspace::ClientSelector;
 
 pub fn main() -> Result<()> {
-    #[cfg(debug_assertions)]
-    SimpleLogger::init(LevelFilter::Debug, simplelog::Config::default())?;
+    let options = Options::parse();
+
+    if options.print_default_config {
+        let (.., names) = init_bindings(&Default::default());
+
+        print!(
+            "{}",
+            Config::commented_default(
+                names
+                    .keys
+                    .iter()
+                    .map(|(input, name)| (input.to_string(), name.to_string()))
+                    .collect(),
+                names
+                    .buttons
+                    .iter()
+                    .map(|(input, name)| {
+                        (
+                            format!("{} ({})", input, format!("{:?}", input.target).to_lowercase()),
+                            name.to_string(),
+                        )
+                    })
+                    .collect(),
+            )
+        );
+
+        return Ok(());
+    }
+
+    let log_level = options.log_level.unwrap_or(if cfg!(debug_assertions) {
+        LevelFilter::Debug
+    } else {
+        LevelFilter::Off
+    });
+
+    SimpleLogger::init(log_level, simplelog::Config::default())?;
+
+    let (conn, default_screen) = RustConnection::connect(None)?;
+    let screen_count = conn.setup().roots.len();
+    let screen_num = options.screen.unwrap_or(default_screen);
+
+    if screen_num >= screen_count {
+        return Err(anyhow::anyhow!("the display has no screen {}", screen_num));
+    }
+
+    // every classic X screen is managed by an instance of its own; clients spawned
+    // from an instance, as well as its IPC socket, follow the screen in DISPLAY
+    if screen_num != default_screen {
+        if let Some(display) = env::var("DISPLAY")
+            .ok()
+            .and_then(|display| display_for_screen(&display, screen_num))
+        {
+            env::set_var("DISPLAY", display);
+        }
+    }
+
+    if screen_count > 1 {
+        warn!(
+            "managing screen {} of {}, the others need an instance each, started with --screen",
+            screen_num, screen_count
+        );
+    }
+
+    let mut config = match &options.config {
+        Some(path) => Config::load_from(path),
+        None => Config::load(),
+    };
 
-    let (conn, screen_num) = x11rb::connect(None)?;
-    let (mouse_bindings, key_bindings) = init_bindings();
+    options.apply(&mut config);
+
+    let (mut mouse_bindings, mut key_bindings, repeatable_keys, pass_through_key, _) =
+        init_bindings(&config.drag);
+
+    if config.kiosk {
+        restrict_to_kiosk_bindings(&mut mouse_bindings, &mut key_bindings);
+    }
 
     Model::new(
-        &mut XConnection::new(&conn, screen_num)?,
+        &mut XConnection::new(
+            &conn,
+            conn.stream().as_raw_fd(),
+            screen_num,
+            options.replace,
+            Duration::from_millis(config.startup_retry),
+        )?,
+        config,
+        options,
         &key_bindings,
         &mouse_bindings,
+        pass_through_key,
     )
-    .run(key_bindings, mouse_bindings);
+    .run(key_bindings, repeatable_keys, mouse_bindings);
 
     Ok(())
 }
 
-fn init_bindings() -> (MouseBindings, KeyBindings) {
+// replaces the screen number of a display name such as host:0.1
+fn display_for_screen(
+    display: &str,
+    screen: usize,
+) -> Option<String> {
+    let colon = display.rfind(':')?;
+    let number = match display[colon..].find('.') {
+        Some(dot) => &display[..colon + dot],
+        None => display,
+    };
+
+    Some(format!("{}.{}", number, screen))
+}
+
+// only the bindings needed to leave the session remain in kiosk mode
+fn restrict_to_kiosk_bindings(
+    mouse_bindings: &mut MouseBindings,
+    key_bindings: &mut KeyBindings,
+) {
+    let whitelist = [KeyInput {
+        key: Key::Escape,
+        modifiers: hashset!(Modifier::Alt, Modifier::Ctrl, Modifier::Shift),
+    }];
+
+    mouse_bindings.clear();
+    key_bindings.retain(|input, _| whitelist.contains(input));
+}
+
+fn init_bindings(
+    drag: &DragBindings,
+) -> (MouseBindings, KeyBindings, RepeatableKeys, KeyInput, BindingNames) {
     let mut mouse_bindings = MouseBindings::new();
     let mut key_bindings = KeyBindings::new();
+    let repeatable_keys = RepeatableKeys::new();
+    let mut names = BindingNames::default();
 
-    mouse_bindings.insert(
+    bind!(
+        mouse_bindings,
+        names.buttons,
         MouseInput {
             target: MouseInputTarget::Client,
             button: Button::Right,
             modifiers: hashset!(Modifier::Alt, Modifier::Ctrl),
         },
+        "toggle float",
         |model: &mut Model<'_>, window: Option<Window>| -> bool {
             if let Some(window) = window {
                 model.set_floating_window(window, Toggle::Reverse);
@@ -108,12 +245,15 @@ fn init_bindings() -> (MouseBindings, KeyBindings) {
         },
     );
 
-    mouse_bindings.insert(
+    bind!(
+        mouse_bindings,
+        names.buttons,
         MouseInput {
             target: MouseInputTarget::Client,
             button: Button::Middle,
             modifiers: hashset!(Modifier::Alt, Modifier::Ctrl, Modifier::Shift),
         },
+        "toggle fullscreen",
         |model: &mut Model<'_>, window: Option<Window>| -> bool {
             if let Some(window) = window {
                 model.set_fullscreen_window(window, Toggle::Reverse);
@@ -123,12 +263,15 @@ fn init_bindings() -> (MouseBindings, KeyBindings) {
         },
     );
 
-    mouse_bindings.insert(
+    bind!(
+        mouse_bindings,
+        names.buttons,
         MouseInput {
             target: MouseInputTarget::Client,
             button: Button::Middle,
             modifiers: hashset!(Modifier::Alt),
         },
+        "center",
         |model: &mut Model<'_>, window: Option<Window>| -> bool {
             if let Some(window) = window {
                 model.center_window(window);
@@ -138,12 +281,15 @@ fn init_bindings() -> (MouseBindings, KeyBindings) {
         },
     );
 
-    mouse_bindings.insert(
+    bind!(
+        mouse_bindings,
+        names.buttons,
         MouseInput {
             target: MouseInputTarget::Client,
-            button: Button::Left,
-            modifiers: hashset!(Modifier::Alt),
+            button: drag.move_button(),
+            modifiers: drag.modifiers(),
         },
+        "move",
         |model: &mut Model<'_>, window: Option<Window>| -> bool {
             if let Some(window) = window {
                 model.start_moving(window);
@@ -153,12 +299,15 @@ fn init_bindings() -> (MouseBindings, KeyBindings) {
         },
     );
 
-    mouse_bindings.insert(
+    bind!(
+        mouse_bindings,
+        names.buttons,
         MouseInput {
             target: MouseInputTarget::Client,
-            button: Button::Right,
-            modifiers: hashset!(Modifier::Alt),
+            button: drag.resize_button(),
+            modifiers: drag.modifiers(),
         },
+        "resize",
         |model: &mut Model<'_>, window: Option<Window>| -> bool {
             if let Some(window) = window {
                 model.start_resizing(window);
@@ -168,12 +317,15 @@ fn init_bindings() -> (MouseBindings, KeyBindings) {
         },
     );
 
-    mouse_bindings.insert(
+    bind!(
+        mouse_bindings,
+        names.buttons,
         MouseInput {
             target: MouseInputTarget::Client,
             button: Button::ScrollDown,
             modifiers: hashset!(Modifier::Alt, Modifier::Ctrl, Modifier::Shift),
         },
+        "shrink main area",
         |model: &mut Model<'_>, window: Option<Window>| -> bool {
             if let Some(window) = window {
                 model.grow_ratio_window(window, -15);
@@ -183,12 +335,15 @@ fn init_bindings() -> (MouseBindings, KeyBindings) {
         },
     );
 
-    mouse_bindings.insert(
+    bind!(
+        mouse_bindings,
+        names.buttons,
         MouseInput {
             target: MouseInputTarget::Client,
             button: Button::ScrollUp,
             modifiers: hashset!(Modifier::Alt, Modifier::Ctrl, Modifier::Shift),
         },
+        "grow main area",
         |model: &mut Model<'_>, window: Option<Window>| -> bool {
             if let Some(window) = window {
                 model.grow_ratio_window(window, 15);
@@ -198,60 +353,75 @@ fn init_bindings() -> (MouseBindings, KeyBindings) {
         },
     );
 
-    mouse_bindings.insert(
+    bind!(
+        mouse_bindings,
+        names.buttons,
         MouseInput {
             target: MouseInputTarget::Client,
             button: Button::ScrollUp,
             modifiers: hashset!(Modifier::Alt),
         },
+        "focus previous",
         |model: &mut Model<'_>, _: Option<Window>| -> bool {
             model.cycle_focus(Direction::Backward);
             false
         }
     );
 
-    mouse_bindings.insert(
+    bind!(
+        mouse_bindings,
+        names.buttons,
         MouseInput {
             target: MouseInputTarget::Client,
             button: Button::ScrollDown,
             modifiers: hashset!(Modifier::Alt),
         },
+        "focus next",
         |model: &mut Model<'_>, _: Option<Window>| -> bool {
             model.cycle_focus(Direction::Forward);
             false
         }
     );
 
-    mouse_bindings.insert(
+    bind!(
+        mouse_bindings,
+        names.buttons,
         MouseInput {
             target: MouseInputTarget::Global,
             button: Button::ScrollUp,
             modifiers: hashset!(Modifier::Alt, Modifier::Shift),
         },
+        "previous workspace",
         |model: &mut Model<'_>, _: Option<Window>| -> bool {
-            model.activate_next_workspace(Direction::Backward);
+            model.scroll_workspace(Direction::Backward);
             false
         }
     );
 
-    mouse_bindings.insert(
+    bind!(
+        mouse_bindings,
+        names.buttons,
         MouseInput {
             target: MouseInputTarget::Global,
             button: Button::ScrollDown,
             modifiers: hashset!(Modifier::Alt, Modifier::Shift),
         },
+        "next workspace",
         |model: &mut Model<'_>, _: Option<Window>| -> bool {
-            model.activate_next_workspace(Direction::Forward);
+            model.scroll_workspace(Direction::Forward);
             false
         }
     );
 
-    mouse_bindings.insert(
+    bind!(
+        mouse_bindings,
+        names.buttons,
         MouseInput {
             target: MouseInputTarget::Client,
             button: Button::Backward,
             modifiers: hashset!(Modifier::Alt),
         },
+        "send to previous workspace",
         |model: &mut Model<'_>, window: Option<Window>| -> bool {
             if let Some(window) = window {
                 model.move_window_to_next_workspace(window, Direction::Backward);
@@ -261,12 +431,15 @@ fn init_bindings() -> (MouseBindings, KeyBindings) {
         }
     );
 
-    mouse_bindings.insert(
+    bind!(
+        mouse_bindings,
+        names.buttons,
         MouseInput {
             target: MouseInputTarget::Client,
             button: Button::Forward,
             modifiers: hashset!(Modifier::Alt),
         },
+        "send to next workspace",
         |model: &mut Model<'_>, window: Option<Window>| -> bool {
             if let Some(window) = window {
                 model.move_window_to_next_workspace(window, Direction::Forward);
@@ -276,36 +449,120 @@ fn init_bindings() -> (MouseBindings, KeyBindings) {
         }
     );
 
-    key_bindings.insert(
+    bind!(
+        key_bindings,
+        names.keys,
         KeyInput {
             key: Key::Escape,
             modifiers: hashset!(Modifier::Alt, Modifier::Ctrl, Modifier::Shift),
         },
+        "exit",
         |model: &mut Model<'_>| {
             model.exit();
         }
     );
 
-    key_bindings.insert(
+    // the only binding that remains grabbed while keys are passed through
+    let pass_through_key = KeyInput {
+        key: Key::Pause,
+        modifiers: hashset!(Modifier::Alt),
+    };
+
+    bind!(key_bindings, names.keys, pass_through_key.clone(), "toggle pass-through", |model: &mut Model<'_>| {
+        model.toggle_pass_through();
+    });
+
+    bind!(
+        key_bindings,
+        names.keys,
+        KeyInput {
+            key: Key::Pause,
+            modifiers: hashset!(Modifier::Alt, Modifier::Shift),
+        },
+        "confine pointer",
+        |model: &mut Model<'_>| {
+            model.confine_pointer_focus();
+        }
+    );
+
+    bind!(
+        key_bindings,
+        names.keys,
+        KeyInput {
+            key: Key::F12,
+            modifiers: hashset!(Modifier::Alt, Modifier::Shift),
+        },
+        "toggle debug hud",
+        |model: &mut Model<'_>| {
+            model.toggle_debug_hud();
+        }
+    );
+
+    bind!(
+        key_bindings,
+        names.keys,
+        KeyInput {
+            key: Key::SemiColon,
+            modifiers: hashset!(Modifier::Alt),
+        },
+        "hint focus",
+        |model: &mut Model<'_>| {
+            model.hint_focus();
+        }
+    );
+
+    bind!(
+        key_bindings,
+        names.keys,
         KeyInput {
             key: Key::J,
             modifiers: hashset!(Modifier::Alt),
         },
+        "focus next",
         |model: &mut Model<'_>| {
             model.cycle_focus(Direction::Forward);
         }
     );
 
-    key_bindings.insert(
+    bind!(
+        key_bindings,
+        names.keys,
         KeyInput {
             key: Key::K,
             modifiers: hashset!(Modifier::Alt),
         },
+        "focus previous",
         |model: &mut Model<'_>| {
             model.cycle_focus(Direction::Backward);
         }
     );
 
+    bind!(
+        key_bindings,
+        names.keys,
+        KeyInput {
+            key: Key::Grave,
+            modifiers: hashset!(Modifier::Alt),
+        },
+        "focus next of same class",
+        |model: &mut Model<'_>| {
+            model.cycle_same_class(Direction::Forward);
+        }
+    );
+
+    bind!(
+        key_bindings,
+        names.keys,
+        KeyInput {
+            key: Key::Grave,
+            modifiers: hashset!(Modifier::Alt, Modifier::Shift),
+        },
+        "focus previous of same class",
+        |model: &mut Model<'_>| {
+            model.cycle_same_class(Direction::Backward);
+        }
+    );
+
     // // (kind, target, focus): "[modifiers]-button" => action
     // let mouse_bindings = build_mouse_bindings!(
     //     }),
@@ -313,16 +570,26 @@ fn init_bindings() -> (MouseBindings, KeyBindings) {
     // // "[modifiers]-key" => action
     // let key_bindings = build_key_bindings!(
     //     "1-C-S-q" => do_internal!(exit),
+    //     "1-C-S-x" => do_internal!(lock),
 
     //     // client state modifiers
     //     "1-c" => do_internal!(kill_focus),
     //     "1-S-space" => do_internal!(set_floating_focus, Toggle::Reverse),
     //     "1-f" => do_internal!(set_fullscreen_focus, Toggle::Reverse),
+    //     "1-C-f" => do_internal!(cycle_fullscreen, Direction::Forward),
+    //     "1-C-S-F" => do_internal!(cycle_fullscreen, Direction::Backward),
     //     "1-x" => do_internal!(set_stick_focus, Toggle::Reverse),
+    //     "1-2-C-x" => do_internal!(pip_focus),
+    //     "1-C-z" => do_internal!(expand_in_zone),
+    //     "1-2-C-S-H" => do_internal!(set_maximized_focus, WindowState::MaximizedHorz, Toggle::Reverse),
+    //     "1-2-C-S-V" => do_internal!(set_maximized_focus, WindowState::MaximizedVert, Toggle::Reverse),
+    //     "1-2-C-z" => do_internal!(pause_focus),
+    //     "1-2-C-S-Z" => do_internal!(resume_focus),
     //     "1-2-C-f" => do_internal!(set_contained_focus, Toggle::Reverse),
     //     "1-2-C-i" => do_internal!(set_invincible_focus, Toggle::Reverse),
     //     "1-2-C-p" => do_internal!(set_producing_focus, Toggle::Reverse),
     //     "1-2-C-y" => do_internal!(set_iconifyable_focus, Toggle::Reverse),
+    //     "1-2-C-r" => do_internal!(apply_rules_focus),
     //     "1-y" => do_internal!(set_iconify_focus, Toggle::On),
     //     "1-u" => do_internal!(pop_deiconify),
     //     "1-2-u" => do_internal_block!(model, {
@@ -364,6 +631,7 @@ fn init_bindings() -> (MouseBindings, KeyBindings) {
     //     // zone order modifiers
     //     // "1-C-j" => do_internal!(cycle_zones, Direction::Forward),
     //     // "1-C-k" => do_internal!(cycle_zones, Direction::Backward),
+    //     "1-C-grave" => do_internal!(toggle_zone),
 
     //     // active workspace layout modifiers
     //     "1-S-f" => do_internal!(set_layout, LayoutKind::Float),
@@ -384,31 +652,46 @@ fn init_bindings() -> (MouseBindings, KeyBindings) {
     //     "1-C-v" => do_internal!(set_layout, LayoutKind::SVert),
     //     "1-C-S-f" => do_internal!(apply_float_retain_region),
     //     "1-space" => do_internal!(toggle_layout),
+    //     "1-2-bracketright" => do_internal!(cycle_layout, Direction::Forward),
+    //     "1-2-bracketleft" => do_internal!(cycle_layout, Direction::Backward),
 
     //     // active workspace layout data modifiers
-    //     "1-plus" => do_internal!(change_gap_size, Change::Inc(5u32)),
-    //     "1-minus" => do_internal!(change_gap_size, Change::Dec(5u32)),
+    //     "1-plus" => do_internal!(step_gap_size, Direction::Forward),
+    //     "1-minus" => do_internal!(step_gap_size, Direction::Backward),
     //     "1-S-equal" => do_internal!(reset_gap_size),
     //     "1-i" => do_internal!(change_main_count, Change::Inc(1u32)),
     //     "1-d" => do_internal!(change_main_count, Change::Dec(1u32)),
     //     "1-l" => do_internal!(change_main_factor, Change::Inc(0.05f32)),
     //     "1-h" => do_internal!(change_main_factor, Change::Dec(0.05f32)),
-    //     "1-S-Left" => do_internal!(change_margin, Edge::Left, Change::Inc(5i32)),
-    //     "1-C-S-Left" => do_internal!(change_margin, Edge::Left, Change::Dec(5i32)),
-    //     "1-S-Up" => do_internal!(change_margin, Edge::Top, Change::Inc(5i32)),
-    //     "1-C-S-Up" => do_internal!(change_margin, Edge::Top, Change::Dec(5i32)),
-    //     "1-S-Down" => do_internal!(change_margin, Edge::Bottom, Change::Inc(5i32)),
-    //     "1-C-S-Down" => do_internal!(change_margin, Edge::Bottom, Change::Dec(5i32)),
-    //     "1-S-Right" => do_internal!(change_margin, Edge::Right, Change::Inc(5i32)),
-    //     "1-C-S-Right" => do_internal!(change_margin, Edge::Right, Change::Dec(5i32)),
+    //     "1-2-l" => do_internal!(change_main_factor_for, ZoneSelector::Parent, Change::Inc(0.05f32)),
+    //     "1-2-h" => do_internal!(change_main_factor_for, ZoneSelector::Parent, Change::Dec(0.05f32)),
+    //     "1-2-m" => do_internal!(mark_focus_zone),
+    //     "1-2-S-s" => do_internal!(save_layout_profile, "default"),
+    //     "1-2-s" => do_internal_block!(model, {
+    //         model.apply_layout_profile("default", model.active_workspace());
+    //     }),
+    //     "1-2-S-p" => do_internal!(run_action, "project-setup"),
+    //     "1-S-Left" => do_internal!(step_margin, Edge::Left, Direction::Forward),
+    //     "1-C-S-Left" => do_internal!(step_margin, Edge::Left, Direction::Backward),
+    //     "1-S-Up" => do_internal!(step_margin, Edge::Top, Direction::Forward),
+    //     "1-C-S-Up" => do_internal!(step_margin, Edge::Top, Direction::Backward),
+    //     "1-S-Down" => do_internal!(step_margin, Edge::Bottom, Direction::Forward),
+    //     "1-C-S-Down" => do_internal!(step_margin, Edge::Bottom, Direction::Backward),
+    //     "1-S-Right" => do_internal!(step_margin, Edge::Right, Direction::Forward),
+    //     "1-C-S-Right" => do_internal!(step_margin, Edge::Right, Direction::Backward),
     //     "1-C-S-equal" => do_internal!(reset_margin),
     //     "1-2-C-S-l" => do_internal!(copy_prev_layout_data),
     //     "1-2-C-S-equal" => do_internal!(reset_layout_data),
 
     //     // workspace activators
     //     "1-Escape" => do_internal!(toggle_workspace),
+    //     "1-S-Escape" => do_internal!(workspace_back),
     //     "1-bracketleft" => do_internal!(activate_next_workspace, Direction::Backward),
     //     "1-bracketright" => do_internal!(activate_next_workspace, Direction::Forward),
+    //     "1-2-Left" => do_internal!(activate_workspace_towards, Edge::Left),
+    //     "1-2-Down" => do_internal!(activate_workspace_towards, Edge::Bottom),
+    //     "1-2-Up" => do_internal!(activate_workspace_towards, Edge::Top),
+    //     "1-2-Right" => do_internal!(activate_workspace_towards, Edge::Right),
     //     "1-1" => do_internal!(activate_workspace, 0),
     //     "1-2" => do_internal!(activate_workspace, 1),
     //     "1-3" => do_internal!(activate_workspace, 2),
@@ -423,6 +706,7 @@ fn init_bindings() -> (MouseBindings, KeyBindings) {
     //     // workspace client movers
     //     "1-S-bracketleft" => do_internal!(move_focus_to_next_workspace, Direction::Backward),
     //     "1-S-bracketright" => do_internal!(move_focus_to_next_workspace, Direction::Forward),
+    //     "1-C-S-t" => do_internal!(teleport_focus),
     //     "1-S-1" => do_internal!(move_focus_to_workspace, 0),
     //     "1-S-2" => do_internal!(move_focus_to_workspace, 1),
     //     "1-S-3" => do_internal!(move_focus_to_workspace, 2),
@@ -438,6 +722,8 @@ fn init_bindings() -> (MouseBindings, KeyBindings) {
     //     "1-v" => do_internal!(toggle_screen_struts),
 
     //     // client jump criteria
+    //     "1-grave" => do_internal!(cycle_same_class, Direction::Forward),
+    //     "1-S-grave" => do_internal!(cycle_same_class, Direction::Backward),
     //     "1-b" => do_internal!(jump_client,
     //         JumpCriterium::ByClass(MatchMethod::Equals("qutebrowser"))
     //     ),
@@ -512,5 +798,5 @@ fn init_bindings() -> (MouseBindings, KeyBindings) {
     //     ),
     // );
 
-    (mouse_bindings, key_bindings)
+    (mouse_bindings, key_bindings, repeatable_keys, pass_through_key, names)
 }
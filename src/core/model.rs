@@ -1,54 +1,100 @@
 #[allow(unused_imports)]
 use crate::util::Util;
 
+use crate::autostart::Autostart;
+use crate::autostart::AutostartEntry;
+use crate::binding::KeyAction;
 use crate::binding::KeyBindings;
 use crate::binding::MouseBindings;
+use crate::binding::RepeatableKeys;
 use crate::change::Change;
 use crate::change::Direction;
 use crate::change::Toggle;
 use crate::client::Client;
+use crate::config::ActivationPolicy;
+use crate::config::Config;
+use crate::config::DesktopOrientation;
+use crate::config::EmptyWorkspaceAction;
+use crate::config::ScrollZone;
 use crate::consume::get_spawner_pid;
+use crate::contrib::actions::Action;
+#[cfg(feature = "dbus")]
+use crate::contrib::extensions::dbus::DbusService;
+use crate::contrib::extensions::ipc::IpcAccess;
+use crate::contrib::extensions::ipc::IpcCommand;
+use crate::contrib::extensions::ipc::IpcFifo;
+use crate::contrib::extensions::ipc::IpcServer;
+#[cfg(feature = "scripting")]
+use crate::contrib::extensions::script::Script;
 use crate::cycle::Cycle;
 use crate::cycle::InsertPos;
 use crate::cycle::Selector;
+use crate::decoration::ColorScheme;
 use crate::decoration::Decoration;
+use crate::decoration::DecorationStrategy;
 use crate::defaults;
 use crate::error::StateChangeError;
 use crate::identify::Ident;
 use crate::identify::Index;
 use crate::jump::JumpCriterium;
 use crate::layout::Layout;
+use crate::layout::LayoutDefaults;
 use crate::layout::LayoutKind;
+use crate::options::Options;
 use crate::partition::Partition;
 use crate::placement::Placement;
 use crate::placement::PlacementClass;
 use crate::placement::PlacementMethod;
 use crate::placement::PlacementRegion;
 use crate::placement::PlacementTarget;
+#[cfg(feature = "profiling")]
+use crate::profile::Profiler;
+use crate::retired::RetiredWindows;
+use crate::retired::Unmap;
+use crate::rule::Rule;
 use crate::rule::Rules;
+use crate::selector::WindowSelector;
+use crate::signal::reap_children;
+use crate::signal::SignalWatcher;
 use crate::stack::StackLayer;
 use crate::stack::StackManager;
+use crate::state::LayoutProfile;
+use crate::state::State;
+use crate::status::StatusReporter;
+use crate::timer::TimerKind;
+use crate::timer::Timers;
+use crate::usage::Usage;
+use crate::usage::UsageMonitor;
 use crate::util::BuildIdHasher;
 use crate::workspace::Buffer;
 use crate::workspace::BufferKind;
 use crate::workspace::Workspace;
+use crate::workspace::ZoneSelector;
 use crate::zone::ZoneContent;
+use crate::zone::ZoneId;
 use crate::zone::ZoneManager;
 
+use winsys::connection::ALL_DESKTOPS;
 use winsys::connection::Connection;
 use winsys::connection::Pid;
 use winsys::event::Event;
 use winsys::event::PropertyKind;
+use winsys::event::RequestSource;
 use winsys::event::StackMode;
 use winsys::event::ToggleAction;
 use winsys::geometry::Corner;
 use winsys::geometry::Dim;
 use winsys::geometry::Edge;
+use winsys::geometry::Padding;
 use winsys::geometry::Pos;
 use winsys::geometry::Region;
+use winsys::geometry::Strut;
 use winsys::hints::Hints;
+use winsys::input::CursorShape;
 use winsys::input::Grip;
+use winsys::input::Key;
 use winsys::input::KeyEvent;
+use winsys::input::KeyEventKind;
 use winsys::input::KeyInput;
 use winsys::input::MouseEvent;
 use winsys::input::MouseEventKind;
@@ -62,21 +108,67 @@ use winsys::window::WindowType;
 
 use std::cell::Cell;
 use std::cell::RefCell;
+use std::cmp::Reverse;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::process;
+use std::time::Duration;
+use std::time::Instant;
+
+use anyhow::anyhow;
+use nix::poll::poll;
+use nix::poll::PollFd;
+use nix::poll::PollFlags;
+use nix::sys::signal;
+use nix::sys::signal::Signal;
+use nix::unistd;
+
+// the horizontal and vertical position and length a maximized client had before
+type MaximizedSpans = (Option<(i32, i32)>, Option<(i32, i32)>);
+
+// where the client selected for teleporting ends up
+enum TeleportTarget {
+    Workspace(Index),
+    Screen(Region),
+}
 
 pub struct Model<'model> {
     conn: &'model mut dyn Connection,
+    config: Config,
+    options: Options,
+    state: RefCell<State>,
+    timers: RefCell<Timers>,
+    autostart: RefCell<Autostart>,
+    ipc: Option<IpcServer>,
+    fifo: Option<IpcFifo>,
+    #[cfg(feature = "dbus")]
+    dbus: Option<DbusService>,
+    #[cfg(feature = "profiling")]
+    profiler: Option<Profiler>,
+    #[cfg(feature = "scripting")]
+    script: Option<Script>,
+    signals: Option<SignalWatcher>,
+    status: RefCell<Option<StatusReporter>>,
+    runtime_rules: Vec<Rule>,
+    bound_keys: Vec<KeyInput>,
+    bound_buttons: Vec<MouseInput>,
+    pass_through_key: KeyInput,
     zone_manager: ZoneManager,
     stack_manager: StackManager,
     stacking_order: RefCell<Vec<Window>>,
+    client_stacking: RefCell<Vec<Window>>,
     pid_map: HashMap<Pid, Window>,
     client_map: HashMap<Window, Client, BuildIdHasher>,
     window_map: HashMap<Window, Window, BuildIdHasher>,
     frame_map: HashMap<Window, Window, BuildIdHasher>,
-    sticky_clients: RefCell<HashSet<Window, BuildIdHasher>>,
+    retired: RefCell<RetiredWindows>,
+    sticky_clients: RefCell<HashMap<Window, Region, BuildIdHasher>>,
     unmanaged_windows: RefCell<HashSet<Window, BuildIdHasher>>,
-    fullscreen_regions: RefCell<HashMap<Window, Region, BuildIdHasher>>,
+    ignored_struts: RefCell<HashSet<Window, BuildIdHasher>>,
+    fullscreen_regions: RefCell<HashMap<Window, (Region, Region), BuildIdHasher>>,
+    pip_clients: RefCell<HashMap<Window, (bool, bool, Region), BuildIdHasher>>,
+    dedicated_clients: RefCell<HashMap<Window, Index, BuildIdHasher>>,
     partitions: Cycle<Partition>,
     workspaces: Cycle<Workspace>,
     move_buffer: Buffer,
@@ -86,50 +178,179 @@ pub struct Model<'model> {
     running: bool,
     focus: Cell<Option<Window>>,
     jumped_from: Cell<Option<Window>>,
+    class_cycle: RefCell<Option<(Window, Vec<Window>)>>,
+    teleporting: Cell<Option<Window>>,
+    hints: RefCell<Vec<(String, Window)>>,
+    debug_hud: Cell<bool>,
+    recent_events: RefCell<VecDeque<String>>,
+    hint_prefix: RefCell<String>,
+    key_repeat: Cell<Option<(Key, KeyAction, u32)>>,
+    flashing: RefCell<HashMap<Window, u32, BuildIdHasher>>,
+    held_keys: RefCell<HashSet<Key>>,
+    repeatable_bindings: RefCell<RepeatableKeys>,
+    binding_throttles: RefCell<HashMap<KeyInput, Duration>>,
+    binding_invocations: RefCell<HashMap<KeyInput, Instant>>,
+    auto_layout_steps: RefCell<HashMap<Index, (usize, Option<usize>)>>,
+    portrait: Cell<bool>,
+    landscape_kinds: RefCell<HashMap<Index, LayoutKind>>,
+    locker: Cell<Option<Pid>>,
+    pass_through: Cell<Option<Window>>,
+    confined: Cell<Option<Window>>,
+    colors: Cell<ColorScheme>,
+    outline: Cell<Option<Region>>,
+    occupancy: RefCell<Vec<(usize, usize)>>,
+    root_status: RefCell<String>,
+    deferred_arrangement: Cell<Option<(bool, bool)>>,
+    producer_slots: RefCell<HashMap<Window, (Index, Option<Window>), BuildIdHasher>>,
+    paused: RefCell<Vec<Window>>,
+    float_slots: RefCell<HashMap<Window, (Index, Option<Window>), BuildIdHasher>>,
+    maximized_spans: RefCell<HashMap<Window, MaximizedSpans, BuildIdHasher>>,
+    usage: RefCell<UsageMonitor>,
+    used_workspaces: RefCell<HashSet<Index>>,
 }
 
 impl<'model> Model<'model> {
+    const RECENT_EVENTS: usize = 8;
+    const EVENT_WIDTH: usize = 100;
+
     pub fn new(
         conn: &'model mut dyn Connection,
+        config: Config,
+        options: Options,
         key_bindings: &KeyBindings,
         mouse_bindings: &MouseBindings,
+        pass_through_key: KeyInput,
     ) -> Self {
+        #[cfg(feature = "scripting")]
+        let script = config.script_path().and_then(|path| {
+            Script::load(&path)
+                .map_err(|err| warn!("could not load script: {}", err))
+                .ok()
+        });
+
+        #[allow(unused_mut)]
+        let mut bound_keys: Vec<KeyInput> = key_bindings.keys().cloned().collect();
+
+        // script bindings take precedence over compiled-in ones, but are not
+        // available in kiosk mode
+        #[cfg(feature = "scripting")]
+        if !config.kiosk {
+            bound_keys.extend(script.iter().flat_map(Script::keys).cloned());
+        }
+
+        let repeatable_bindings = config.repeatable_keys();
+        let binding_throttles = config.binding_throttles();
+
         Self::init(
             Self {
+                state: RefCell::new(State::load()),
+                autostart: RefCell::new(Autostart::new(&config.autostart)),
+                ipc: IpcServer::bind(&config.ipc)
+                    .map_err(|err| warn!("could not set up IPC socket: {}", err))
+                    .ok(),
+                fifo: config.fifo_path().and_then(|path| {
+                    IpcFifo::open(path, &config.ipc)
+                        .map_err(|err| warn!("could not set up IPC FIFO: {}", err))
+                        .ok()
+                }),
+                #[cfg(feature = "dbus")]
+                dbus: DbusService::connect(&config.ipc)
+                    .map_err(|err| warn!("could not offer D-Bus service: {}", err))
+                    .ok(),
+                #[cfg(feature = "profiling")]
+                profiler: Profiler::install(),
+                #[cfg(feature = "scripting")]
+                script,
+                status: RefCell::new(StatusReporter::open(&config.status)),
+                config,
+                options,
+                timers: RefCell::new(Timers::new()),
+                signals: SignalWatcher::new(&[
+                    Signal::SIGCHLD,
+                    Signal::SIGTERM,
+                    Signal::SIGINT,
+                    Signal::SIGHUP,
+                    Signal::SIGUSR1,
+                ]),
+                runtime_rules: Vec::new(),
+                bound_keys,
+                bound_buttons: mouse_bindings.keys().cloned().collect(),
+                pass_through_key,
                 zone_manager: ZoneManager::new(),
                 stack_manager: StackManager::new(),
                 stacking_order: RefCell::new(Vec::with_capacity(200)),
+                client_stacking: RefCell::new(Vec::with_capacity(200)),
                 pid_map: HashMap::new(),
                 client_map: HashMap::with_hasher(BuildIdHasher),
                 window_map: HashMap::with_hasher(BuildIdHasher),
                 frame_map: HashMap::with_hasher(BuildIdHasher),
-                sticky_clients: RefCell::new(HashSet::with_hasher(BuildIdHasher)),
+                retired: RefCell::new(RetiredWindows::new()),
+                sticky_clients: RefCell::new(HashMap::with_hasher(BuildIdHasher)),
                 unmanaged_windows: RefCell::new(HashSet::with_hasher(BuildIdHasher)),
+                ignored_struts: RefCell::new(HashSet::with_hasher(BuildIdHasher)),
                 fullscreen_regions: RefCell::new(HashMap::with_hasher(BuildIdHasher)),
+                pip_clients: RefCell::new(HashMap::with_hasher(BuildIdHasher)),
+                dedicated_clients: RefCell::new(HashMap::with_hasher(BuildIdHasher)),
                 partitions: Cycle::new(Vec::new(), false),
-                workspaces: Cycle::new(Vec::with_capacity(defaults::WORKSPACE_NAMES.len()), false),
-                move_buffer: Buffer::new(BufferKind::Move, conn.create_handle()),
-                resize_buffer: Buffer::new(BufferKind::Resize, conn.create_handle()),
+                workspaces: Cycle::new(Vec::with_capacity(defaults::WORKSPACE_NAMES.len()), true),
+                // the handles are only held as the targets of pointer grabs
+                move_buffer: Buffer::new(
+                    BufferKind::Move,
+                    conn.create_handle()
+                        .map_err(|err| warn!("could not create move handle: {}", err))
+                        .unwrap_or_default(),
+                ),
+                resize_buffer: Buffer::new(
+                    BufferKind::Resize,
+                    conn.create_handle()
+                        .map_err(|err| warn!("could not create resize handle: {}", err))
+                        .unwrap_or_default(),
+                ),
                 prev_partition: Cell::new(0),
                 prev_workspace: Cell::new(0),
                 running: true,
                 focus: Cell::new(None),
                 jumped_from: Cell::new(None),
+                class_cycle: RefCell::new(None),
+                teleporting: Cell::new(None),
+                hints: RefCell::new(Vec::new()),
+                debug_hud: Cell::new(false),
+                recent_events: RefCell::new(VecDeque::with_capacity(Self::RECENT_EVENTS)),
+                hint_prefix: RefCell::new(String::new()),
+                key_repeat: Cell::new(None),
+                flashing: RefCell::new(HashMap::with_hasher(BuildIdHasher)),
+                held_keys: RefCell::new(HashSet::new()),
+                repeatable_bindings: RefCell::new(repeatable_bindings),
+                binding_throttles: RefCell::new(binding_throttles),
+                binding_invocations: RefCell::new(HashMap::new()),
+                auto_layout_steps: RefCell::new(HashMap::new()),
+                portrait: Cell::new(false),
+                landscape_kinds: RefCell::new(HashMap::new()),
+                locker: Cell::new(None),
+                pass_through: Cell::new(None),
+                confined: Cell::new(None),
+                colors: Cell::new(ColorScheme::DEFAULT),
+                outline: Cell::new(None),
+                occupancy: RefCell::new(Vec::new()),
+                root_status: RefCell::new(String::new()),
+                deferred_arrangement: Cell::new(None),
+                producer_slots: RefCell::new(HashMap::with_hasher(BuildIdHasher)),
+                paused: RefCell::new(Vec::new()),
+                float_slots: RefCell::new(HashMap::with_hasher(BuildIdHasher)),
+                maximized_spans: RefCell::new(HashMap::with_hasher(BuildIdHasher)),
+                usage: RefCell::new(UsageMonitor::new()),
+                used_workspaces: RefCell::new(HashSet::new()),
                 conn,
             },
-            key_bindings,
-            mouse_bindings,
         )
     }
 
-    fn init(
-        mut model: Self,
-        key_bindings: &KeyBindings,
-        mouse_bindings: &MouseBindings,
-    ) -> Self {
+    fn init(mut model: Self) -> Self {
         info!("initializing window manager");
 
         model.acquire_partitions();
+        model.set_layout_defaults();
+
         let screen_region = model
             .partitions
             .active_element()
@@ -155,20 +376,59 @@ impl<'model> Model<'model> {
                     .set_region(screen_region);
             });
 
+        // templates take precedence
+        let layouts = model
+            .config
+            .workspace_layouts
+            .iter()
+            .filter_map(|(key, layout)| {
+                model
+                    .workspace_by_key(key)
+                    .map(|index| (index, layout.to_owned()))
+            })
+            .chain(model.config.workspace_templates.iter().filter_map(|template| {
+                template
+                    .layout
+                    .to_owned()
+                    .map(|layout| (template.workspace, layout))
+            }))
+            .collect::<Vec<(Index, String)>>();
+
+        for (index, name) in layouts {
+            let kind = match LayoutKind::from_name(&name) {
+                Some(kind) => kind,
+                None => {
+                    warn!("unknown layout {} for workspace {}", name, index);
+                    continue;
+                },
+            };
+
+            if let Some(workspace) = model.workspaces.get(index) {
+                model.zone_manager.set_kind(workspace.root_zone(), kind).ok();
+            }
+        }
+
+        model.restore_workspace_margins();
+
         model.workspaces.activate_for(&Selector::AtIndex(0));
+        model.workspaces.clear_stack();
         model.conn.set_current_desktop(0);
+        model.update_workarea();
+        model.update_orientation_layouts();
+
+        if model.config.kiosk {
+            info!("running in kiosk mode");
+            model.set_layout(LayoutKind::Monocle).ok();
+        }
 
         model
             .conn
             .init_wm_properties(WM_NAME!(), &defaults::WORKSPACE_NAMES);
 
-        model.conn.grab_bindings(
-            &key_bindings.keys().into_iter().collect::<Vec<&KeyInput>>(),
-            &mouse_bindings
-                .keys()
-                .into_iter()
-                .collect::<Vec<&MouseInput>>(),
-        );
+        model.publish_desktop_layout();
+        model.load_theme();
+        model.grab_bindings();
+        model.report_config_error();
 
         model
             .conn
@@ -178,7 +438,10 @@ impl<'model> Model<'model> {
                 model.manage(window, !model.conn.must_manage_window(window));
             });
 
-        if cfg!(not(debug_assertions)) {
+        model.schedule_audit();
+        model.schedule_usage();
+
+        if cfg!(not(debug_assertions)) && model.config.startup_scripts {
             let nonblocking = concat!("$HOME/.config/", WM_NAME!(), "/nonblocking_autostart &");
             let blocking = concat!("$HOME/.config/", WM_NAME!(), "/blocking_autostart");
 
@@ -187,6 +450,25 @@ impl<'model> Model<'model> {
             Util::spawn_shell(blocking);
         }
 
+        model.use_workspace(0);
+
+        if let Some(workspace) = model
+            .config
+            .outputs
+            .get(&model.partitions.active_element().unwrap().name())
+            .and_then(|output| output.workspace)
+        {
+            model.activate_workspace(workspace);
+        }
+
+        if !model.autostart.borrow().is_empty() {
+            model.autostart.borrow_mut().supervise();
+            model
+                .timers
+                .borrow_mut()
+                .schedule_repeating(TimerKind::Autostart, Duration::from_secs(1));
+        }
+
         model
     }
 
@@ -282,6 +564,22 @@ impl<'model> Model<'model> {
         self.partitions.active_element().unwrap().screen()
     }
 
+    // configuration keys name a workspace, or else give its zero-based index, so
+    // that names that are numbers refer to the workspace they name
+    fn workspace_by_key(
+        &self,
+        key: &str,
+    ) -> Option<Index> {
+        self.workspaces
+            .iter()
+            .position(|workspace| workspace.name() == key)
+            .or_else(|| {
+                key.parse::<Index>()
+                    .ok()
+                    .filter(|&index| index < self.workspaces.len())
+            })
+    }
+
     #[inline(always)]
     pub fn active_workspace(&self) -> usize {
         self.workspaces.active_index()
@@ -310,7 +608,7 @@ impl<'model> Model<'model> {
             .into_iter()
             .enumerate()
             .map(|(i, screen)| {
-                screen.compute_placeable_region();
+                self.configure_screen(&screen);
                 Partition::new(screen, i)
             })
             .collect();
@@ -323,6 +621,34 @@ impl<'model> Model<'model> {
         }
     }
 
+    fn configure_screen(
+        &self,
+        screen: &Screen,
+    ) {
+        let output = self
+            .config
+            .outputs
+            .get(&screen.name())
+            .cloned()
+            .unwrap_or_default();
+
+        screen.set_gap(output.gap);
+        let show = output.struts;
+
+        if show != screen.showing_struts() {
+            screen
+                .show_and_yield_struts(show)
+                .iter()
+                .for_each(|&strut| {
+                    if show {
+                        self.conn.map_window(strut);
+                    } else {
+                        self.conn.unmap_window(strut);
+                    }
+                });
+        }
+    }
+
     #[inline]
     pub fn toggle_screen_struts(&self) {
         let screen = self.active_screen();
@@ -339,19 +665,197 @@ impl<'model> Model<'model> {
                 }
             });
 
+        self.update_workarea();
         self.apply_layout(self.active_workspace());
     }
 
+    fn update_placeable_region(&self) {
+        self.active_screen().compute_placeable_region();
+        self.update_workarea();
+
+        let workspace = self.active_workspace();
+        self.apply_layout(workspace);
+        self.apply_stack(workspace);
+    }
+
+    fn update_screen_placeable_region(
+        &self,
+        screen: &Screen,
+    ) {
+        if screen.number() == self.active_screen().number() {
+            self.update_placeable_region();
+        } else {
+            screen.compute_placeable_region();
+        }
+    }
+
+    fn add_strut(
+        &self,
+        screen: &Screen,
+        edge: Edge,
+        window: Window,
+        width: u32,
+    ) {
+        if self.ignored_struts.borrow().contains(&window) {
+            return;
+        }
+
+        let full_region = screen.full_region();
+        let extent = match edge {
+            Edge::Left | Edge::Right => full_region.dim.w,
+            Edge::Top | Edge::Bottom => full_region.dim.h,
+        };
+
+        let max_width = (extent as f32 * self.config.max_strut_fraction) as u32;
+
+        if width > max_width {
+            warn!(
+                "capping {:?} strut of window {:#0x} from {} to {}",
+                edge, window, width, max_width
+            );
+        }
+
+        screen.add_strut(edge, window, width.min(max_width));
+    }
+
+    // struts are reserved at the edges of the root window, each screen only loses
+    // the part of them that it overlaps
+    fn add_struts(
+        &self,
+        struts: Vec<Option<Strut>>,
+    ) {
+        let root = self.root_region();
+
+        [Edge::Left, Edge::Right, Edge::Top, Edge::Bottom]
+            .iter()
+            .zip(struts)
+            .filter_map(|(&edge, strut)| strut.map(|strut| (edge, strut)))
+            .for_each(|(edge, strut)| {
+                let geometry = self.conn.get_window_geometry(strut.window).ok();
+
+                for screen in self.partitions.iter().map(|partition| partition.screen()) {
+                    if let Some(width) = screen.strut_width(edge, &strut, root) {
+                        let width = geometry.map_or(width, |geometry| {
+                            screen.stacked_strut_width(edge, width, geometry)
+                        });
+
+                        self.add_strut(screen, edge, strut.window, width);
+                    }
+                }
+            });
+    }
+
+    fn root_region(&self) -> Region {
+        let regions = self
+            .partitions
+            .iter()
+            .map(|partition| partition.screen().full_region());
+
+        let (left, top, right, bottom) = regions.fold(
+            (i32::MAX, i32::MAX, i32::MIN, i32::MIN),
+            |(left, top, right, bottom), region| {
+                (
+                    left.min(region.pos.x),
+                    top.min(region.pos.y),
+                    right.max(region.pos.x + region.dim.w),
+                    bottom.max(region.pos.y + region.dim.h),
+                )
+            },
+        );
+
+        Region::new(left, top, right - left, bottom - top)
+    }
+
+    fn update_placeable_regions(&self) {
+        let active = self.active_screen().number();
+
+        self.partitions
+            .iter()
+            .map(|partition| partition.screen())
+            .filter(|screen| screen.number() != active)
+            .for_each(|screen| screen.compute_placeable_region());
+
+        self.update_placeable_region();
+    }
+
+    fn remove_struts(
+        &self,
+        window: Window,
+    ) -> bool {
+        let mut removed = false;
+
+        for screen in self.partitions.iter().map(|partition| partition.screen()) {
+            if screen.has_strut_window(window) {
+                screen.remove_window_strut(window);
+                removed = true;
+            }
+        }
+
+        removed
+    }
+
+    pub fn ignore_struts(
+        &self,
+        window: Window,
+    ) {
+        info!("ignoring struts of window {:#0x}", window);
+        self.ignored_struts.borrow_mut().insert(window);
+
+        if self.remove_struts(window) {
+            self.update_placeable_regions();
+        }
+    }
+
+    fn screen_containing(
+        &self,
+        region: Region,
+    ) -> &Screen {
+        let center = Pos::from_center_of_region(region);
+
+        self.partitions
+            .iter()
+            .map(|partition| partition.screen())
+            .find(|screen| screen.full_encompasses(center))
+            .unwrap_or_else(|| self.active_screen())
+    }
+
+    fn update_workarea(&self) {
+        let workarea = self.active_screen().placeable_region();
+        let desktop_count = self.workspaces.len();
+
+        let geometry = self
+            .partitions
+            .iter()
+            .map(|partition| partition.screen().full_region())
+            .fold(Dim::default(), |dim, region| Dim {
+                w: dim.w.max(region.pos.x + region.dim.w),
+                h: dim.h.max(region.pos.y + region.dim.h),
+            });
+
+        self.conn.set_desktop_geometry(geometry);
+        self.conn
+            .set_desktop_viewport(&vec![Pos::default(); desktop_count]);
+        self.conn.set_workarea(&vec![&workarea; desktop_count]);
+    }
+
+    #[cfg_attr(feature = "profiling", tracing::instrument(skip_all))]
     fn apply_layout(
         &self,
         index: Index,
     ) {
+        if let Some((_, stack)) = self.deferred_arrangement.get() {
+            self.deferred_arrangement.set(Some((true, stack)));
+            return;
+        }
+
         let workspace = match self.workspaces.get(index) {
             Some(workspace) if index == self.active_workspace() => workspace,
             _ => return,
         };
 
         info!("applying layout on workspace {}", index);
+        self.publish_layout_symbol(workspace);
+        self.publish_root_status();
 
         let (show, hide): (Vec<Placement>, Vec<Placement>) = workspace
             .arrange(
@@ -368,6 +872,10 @@ impl<'model> Model<'model> {
                 PlacementTarget::Client(window) => {
                     let client = &self.client_map[&window];
 
+                    if client.is_fullscreen() {
+                        self.anchor_fullscreen_region(window);
+                    }
+
                     self.update_client_placement(client, &placement);
                     self.place_client(client, placement.method);
                     self.map_client(client);
@@ -388,10 +896,16 @@ impl<'model> Model<'model> {
         });
     }
 
+    #[cfg_attr(feature = "profiling", tracing::instrument(skip_all))]
     fn apply_stack(
         &self,
         index: Index,
     ) {
+        if let Some((layout, _)) = self.deferred_arrangement.get() {
+            self.deferred_arrangement.set(Some((layout, true)));
+            return;
+        }
+
         let workspace = match self.workspaces.get(index) {
             Some(workspace) if index == self.active_workspace() => workspace,
             _ => return,
@@ -409,6 +923,7 @@ impl<'model> Model<'model> {
             .stack_after_focus()
             .into_iter()
             .map(|window| self.frame_unchecked(window))
+            .filter(|frame| !above.contains(frame))
             .collect::<Vec<Window>>();
 
         let (regular, fullscreen): (Vec<_>, Vec<_>) = stack.iter().partition(|&&window| {
@@ -420,6 +935,12 @@ impl<'model> Model<'model> {
             .into_iter()
             .partition(|&window| self.is_free(self.client_unchecked(window)));
 
+        // a focused fullscreen client is kept above free clients, other fullscreen clients are not
+        let focus = self.focus.get().and_then(|window| self.frame(window));
+        let (focused_fullscreen, fullscreen): (Vec<_>, Vec<_>) = fullscreen
+            .into_iter()
+            .partition(|&window| Some(window) == focus);
+
         let mut windows = desktop
             .into_iter()
             .chain(below.into_iter())
@@ -427,6 +948,7 @@ impl<'model> Model<'model> {
             .chain(regular.into_iter())
             .chain(fullscreen.into_iter())
             .chain(free.into_iter())
+            .chain(focused_fullscreen)
             .chain(above.into_iter())
             .chain(notification)
             .into_iter()
@@ -483,8 +1005,149 @@ impl<'model> Model<'model> {
         }
 
         drop(stacking_order);
+
+        let stack_windows = windows
+            .iter()
+            .filter_map(|window| self.frame_map.get(window))
+            .copied()
+            .collect::<Vec<Window>>();
+
         self.stacking_order.replace(windows);
+        self.update_client_lists(&stack_windows);
+    }
+
+    // bars read the title of the focused client from the root window
+    fn publish_title(&self) {
+        let title = self
+            .focus
+            .get()
+            .and_then(|focus| self.client_any(focus))
+            .map(Client::name);
+
+        self.conn.set_focused_title(title.as_deref());
+    }
+
+    // bars read the symbol of the layout in the focused zone from the root window
+    fn publish_layout_symbol(
+        &self,
+        workspace: &Workspace,
+    ) {
+        self.conn.set_layout_symbol(self.layout_symbol(workspace));
+    }
+
+    fn layout_symbol(
+        &self,
+        workspace: &Workspace,
+    ) -> Option<char> {
+        workspace
+            .active_focus_zone()
+            .and_then(|id| self.zone_manager.zone(id).kind().ok())
+            .map(|kind| kind.symbol())
+    }
+
+    // as in dwm, bars without EWMH support can show the name of the root window
+    fn publish_root_status(&self) {
+        if !self.config.root_status {
+            return;
+        }
+
+        let workspace = &self.workspaces[self.active_workspace()];
+        let status = match self.layout_symbol(workspace) {
+            Some(symbol) => format!("{} {}", self.workspace_summary(), symbol),
+            None => self.workspace_summary(),
+        };
+
+        if *self.root_status.borrow() != status {
+            self.conn.set_root_window_name(&status);
+            self.root_status.replace(status);
+        }
+    }
+
+    // the active workspace is shown as [name], those with urgent clients as !name,
+    // and empty ones are left out
+    fn workspace_summary(&self) -> String {
+        let active = self.active_workspace();
+        let occupancy = self.occupancy.borrow();
+
+        self.workspaces
+            .iter()
+            .enumerate()
+            .filter_map(|(index, workspace)| {
+                let (clients, urgent) = occupancy.get(index).copied().unwrap_or((0, 0));
+
+                if index == active {
+                    Some(format!("[{}]", workspace.name()))
+                } else if urgent > 0 {
+                    Some(format!("!{}", workspace.name()))
+                } else if clients > 0 {
+                    Some(workspace.name().to_owned())
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    fn publish_occupancy(&self) {
+        let workspace_count = self.workspaces.len();
+        let mut occupancy = vec![(0, 0); workspace_count];
+
+        self.client_map
+            .values()
+            .filter(|client| client.is_managed() && client.workspace() < workspace_count)
+            .for_each(|client| {
+                let (clients, urgent) = &mut occupancy[client.workspace()];
+
+                *clients += 1;
+
+                if client.is_urgent() {
+                    *urgent += 1;
+                }
+            });
 
+        if *self.occupancy.borrow() != occupancy {
+            self.conn.set_workspace_occupancy(&occupancy);
+            self.occupancy.replace(occupancy);
+            self.publish_root_status();
+        }
+    }
+
+    // the status line is reported once the queued events have been handled
+    fn report_status(&self) {
+        let mut status = self.status.borrow_mut();
+
+        if status.is_none() && self.ipc.is_none() {
+            return;
+        }
+
+        let workspaces = self.workspace_summary();
+        let layout = self
+            .layout_symbol(&self.workspaces[self.active_workspace()])
+            .map(String::from)
+            .unwrap_or_default();
+
+        let title = self
+            .focus
+            .get()
+            .and_then(|focus| self.client_any(focus))
+            .map(Client::name)
+            .unwrap_or_default();
+
+        if let Some(status) = status.as_mut() {
+            status.report(&workspaces, &layout, &title);
+        }
+
+        // subscribers receive the fields separated by tabs
+        if let Some(ipc) = &self.ipc {
+            ipc.publish(&format!("{}\t{}\t{}", workspaces, layout, title.replace('\t', " ")));
+        }
+    }
+
+    fn update_client_lists(
+        &self,
+        stack_windows: &[Window],
+    ) {
         let mut client_list = self.client_map.values().collect::<Vec<&Client>>();
         client_list.sort_by_key(|&a| a.managed_since());
 
@@ -494,26 +1157,35 @@ impl<'model> Model<'model> {
             .collect::<Vec<Window>>();
 
         self.conn.update_client_list(&client_list);
+        self.publish_occupancy();
 
-        let mut client_list_stacking = client_list;
-        let stack_windows = stack
-            .into_iter()
-            .map(|window| self.window_unchecked(window))
-            .collect::<Vec<_>>();
+        // clients that are not part of the current stack keep their relative
+        // order below it, so that the list remains global across workspaces
+        let mut client_stacking = self.client_stacking.borrow_mut();
+
+        client_stacking.retain(|window| {
+            self.client_map.contains_key(window) && !stack_windows.contains(window)
+        });
 
-        client_list_stacking.retain(|&window| !stack_windows.contains(&window));
-        client_list_stacking = client_list_stacking
+        let unstacked = client_list
             .iter()
-            .chain(stack_windows.iter())
+            .filter(|&window| {
+                !client_stacking.contains(window) && !stack_windows.contains(window)
+            })
             .copied()
-            .collect();
+            .collect::<Vec<Window>>();
+
+        client_stacking.splice(0..0, unstacked);
+        client_stacking.extend(stack_windows);
 
-        self.conn.update_client_list_stacking(&client_list_stacking);
+        self.conn.update_client_list_stacking(&client_stacking);
     }
 
     #[inline]
     fn detect_rules(
         &self,
+        name: &str,
+        class: &str,
         instance: &str,
     ) -> Rules {
         const PREFIX: &str = &concat!(WM_NAME!(), ":");
@@ -521,6 +1193,29 @@ impl<'model> Model<'model> {
 
         let mut rules: Rules = Default::default();
 
+        self.config
+            .rules
+            .iter()
+            .filter(|rule| rule.matches(name, class, instance))
+            .for_each(|rule| rule.apply(&mut rules));
+
+        #[cfg(feature = "scripting")]
+        self.script
+            .iter()
+            .flat_map(Script::rules)
+            .filter(|rule| rule.matches(name, class, instance))
+            .for_each(|rule| rule.apply(&mut rules));
+
+        // rules added over IPC take precedence over the configured ones
+        self.runtime_rules
+            .iter()
+            .filter(|rule| rule.matches(name, class, instance))
+            .for_each(|rule| rule.apply(&mut rules));
+
+        rules.workspace = rules
+            .workspace
+            .filter(|&workspace| workspace < self.workspaces.len());
+
         match (instance.get(..PREFIX_LEN), instance.get(PREFIX_LEN..)) {
             (Some(PREFIX), Some(flags)) if !flags.is_empty() => {
                 let mut invert = false;
@@ -548,6 +1243,7 @@ impl<'model> Model<'model> {
                         "f" => rules.float = Some(!invert),
                         "F" => rules.fullscreen = Some(!invert),
                         "c" => rules.center = Some(!invert),
+                        "d" => rules.dedicated = Some(!invert),
                         _ => {},
                     }
 
@@ -561,6 +1257,7 @@ impl<'model> Model<'model> {
         rules
     }
 
+    #[cfg_attr(feature = "profiling", tracing::instrument(skip_all))]
     fn manage(
         &mut self,
         window: Window,
@@ -579,7 +1276,13 @@ impl<'model> Model<'model> {
 
         let pid = self.conn.get_window_pid(window);
         let ppid = pid.and_then(|pid| {
-            get_spawner_pid(pid, std::process::id(), &self.pid_map, &self.client_map)
+            get_spawner_pid(
+                pid,
+                process::id(),
+                self.config.swallowing.max_depth,
+                &self.pid_map,
+                &self.client_map,
+            )
         });
 
         let name = self.conn.get_icccm_window_name(window);
@@ -598,8 +1301,14 @@ impl<'model> Model<'model> {
         self.stop_resizing();
 
         let at_origin = geometry.pos.is_origin();
-        let frame = self.conn.create_frame(geometry);
-        let rules = self.detect_rules(&instance);
+        // a window whose frame could not be created is decorated directly instead
+        let frame = self.create_frame(window, geometry).unwrap_or_else(|err| {
+            warn!("managing window {:#0x} without a frame: {}", window, err);
+            window
+        });
+
+        let free_decoration = self.adapt_decoration(Decoration::FREE_DECORATION, frame != window);
+        let rules = self.detect_rules(&name, &class, &instance);
         let hints = self.conn.get_icccm_window_hints(window);
         let size_hints = self
             .conn
@@ -609,21 +1318,16 @@ impl<'model> Model<'model> {
         geometry = match size_hints {
             Some(size_hints) => geometry
                 .with_size_hints(&Some(size_hints))
-                .with_extents(Decoration::FREE_DECORATION.extents()),
+                .with_extents(free_decoration.extents()),
             None => geometry
                 .with_minimum_dim(&Client::MIN_CLIENT_DIM)
-                .with_extents(Decoration::FREE_DECORATION.extents()),
+                .with_extents(free_decoration.extents()),
         };
 
         let parent = self.conn.get_icccm_window_transient_for(window);
         let screen = self.active_screen();
         let context = 0;
-        let workspace = rules.workspace.unwrap_or_else(|| {
-            self.conn
-                .get_window_desktop(window)
-                .filter(|&workspace| workspace < self.workspaces.len())
-                .unwrap_or_else(|| self.active_workspace())
-        });
+        let workspace = self.initial_workspace(window, &rules, &class, &instance);
 
         if rules.center() || size_hints.map_or(true, |size_hints| !size_hints.by_user) && at_origin
         {
@@ -632,8 +1336,9 @@ impl<'model> Model<'model> {
                 .from_absolute_inner_center(geometry.dim);
         }
 
-        let parent_zone = self.workspaces[workspace]
-            .active_spawn_zone()
+        let parent_zone = self
+            .pointer_spawn_zone(workspace)
+            .or_else(|| self.workspaces[workspace].active_spawn_zone())
             .map(|id| self.zone_manager.nearest_cycle(id));
 
         let zone = self
@@ -653,8 +1358,10 @@ impl<'model> Model<'model> {
         );
 
         let mut floating = self.conn.must_free_window(window) | rules.float();
-        let fullscreen = self.conn.window_is_fullscreen(window) | rules.fullscreen();
-        let sticky = self.conn.window_is_sticky(window);
+        let fullscreen =
+            self.conn.window_is_fullscreen(window) | rules.fullscreen() | rules.dedicated();
+        let sticky = self.conn.window_is_sticky(window)
+            || self.conn.get_window_desktop(window) == Some(ALL_DESKTOPS);
 
         if let Some(parent) = parent {
             floating = true;
@@ -682,17 +1389,20 @@ impl<'model> Model<'model> {
         client.set_floating(Toggle::from(floating));
         client.set_region(PlacementClass::Free(geometry));
         client.set_size_hints(size_hints);
+        client.set_opaque_region(self.conn.get_window_opaque_region(window));
         client.set_context(context);
         client.set_workspace(workspace);
 
-        self.conn.reparent_window(window, frame, {
-            let extents = Decoration::FREE_DECORATION.extents();
+        if frame != window {
+            self.conn.reparent_window(window, frame, {
+                let extents = free_decoration.extents();
 
-            Pos {
-                x: extents.left,
-                y: extents.top,
-            }
-        });
+                Pos {
+                    x: extents.left,
+                    y: extents.top,
+                }
+            });
+        }
 
         if let Some(parent) = parent.and_then(|parent| self.client_any(parent)) {
             let parent_frame = parent.frame();
@@ -705,6 +1415,12 @@ impl<'model> Model<'model> {
             self.pid_map.insert(pid, window);
         }
 
+        if rules.dedicated() {
+            self.dedicated_clients
+                .borrow_mut()
+                .insert(window, self.active_workspace());
+        }
+
         self.workspaces[workspace].add_client(window, &InsertPos::AfterActive);
         self.client_map.insert(window, client);
         self.frame_map.insert(frame, window);
@@ -712,8 +1428,11 @@ impl<'model> Model<'model> {
 
         self.conn.insert_window_in_save_set(window);
         self.conn.init_window(window, false);
-        self.conn.init_frame(frame, false);
-        self.conn.set_window_border_width(window, 0);
+
+        if frame != window {
+            self.conn.init_frame(frame, false);
+            self.conn.set_window_border_width(window, 0);
+        }
         self.conn.set_window_desktop(window, workspace);
         self.conn
             .set_icccm_window_state(window, IcccmWindowState::Normal);
@@ -731,6 +1450,7 @@ impl<'model> Model<'model> {
         }
 
         let client = &self.client_map[&window];
+        self.learn_workspace(client);
 
         if sticky {
             self.stick(client);
@@ -742,7 +1462,13 @@ impl<'model> Model<'model> {
 
         if let Some(&ppid_window) = ppid.and_then(|ppid| self.pid_map.get(&ppid)) {
             if let Some(ppid_client) = self.client(ppid_window) {
-                if ppid_client.is_producing() {
+                if ppid_client.is_producing()
+                    && self.config.swallowing.allows(
+                        client.workspace(),
+                        &ppid_client.class(),
+                        &client.class(),
+                    )
+                {
                     self.consume_client(client, ppid_client);
                 }
             }
@@ -758,40 +1484,168 @@ impl<'model> Model<'model> {
         info!("managing client {:#?}", client);
     }
 
-    fn remanage(
+    fn initial_workspace(
         &self,
-        client: &Client,
-        must_alter_workspace: bool,
-    ) {
-        if client.is_managed() {
-            return;
+        window: Window,
+        rules: &Rules,
+        class: &str,
+        instance: &str,
+    ) -> Index {
+        if self.config.kiosk {
+            return self.active_workspace();
         }
 
-        let window = client.window();
-        info!("remanaging client with window {:#0x}", window);
-
-        client.set_managed(Toggle::On);
-
-        if must_alter_workspace {
-            let workspace = client
-                .leader()
-                .and_then(|leader| self.client(leader))
-                .map(|leader| {
-                    let workspace = leader.workspace();
+        // dedicated clients get a workspace of their own, preferably an empty one
+        if rules.dedicated() {
+            return rules
+                .workspace
+                .or_else(|| self.workspaces.iter().position(Workspace::is_empty))
+                .unwrap_or_else(|| self.active_workspace());
+        }
 
-                    client.set_workspace(workspace);
-                    self.workspace(leader.workspace())
-                });
+        let requested = self
+            .conn
+            .get_window_desktop(window)
+            .filter(|&workspace| workspace < self.workspaces.len());
 
-            if let Some(workspace) = workspace {
-                if !workspace.contains(window) {
-                    workspace.add_client(window, &InsertPos::Back);
-                }
+        // restored applications may ask for the workspace they were on before
+        if self.config.prefer_requested_workspace {
+            if let Some(workspace) = rules
+                .workspace
+                .filter(|&workspace| workspace < self.workspaces.len())
+                .or(requested)
+            {
+                return workspace;
             }
         }
 
-        if client.is_sticky() {
-            client.set_sticky(Toggle::Off);
+        rules
+            .workspace
+            .or_else(|| {
+                self.conn
+                    .get_window_pid(window)
+                    .and_then(|pid| self.autostart.borrow().workspace_for(pid))
+            })
+            .filter(|&workspace| workspace < self.workspaces.len())
+            .or_else(|| self.learned_workspace(class, instance))
+            .or(requested)
+            .unwrap_or_else(|| self.active_workspace())
+    }
+
+    fn estimate_decoration(
+        &self,
+        window: Window,
+    ) -> Decoration {
+        if !self.conn.must_manage_window(window) {
+            return Decoration::NO_DECORATION;
+        }
+
+        let class = self.conn.get_icccm_window_class(window);
+        let instance = self.conn.get_icccm_window_instance(window);
+        let rules =
+            self.detect_rules(&self.conn.get_icccm_window_name(window), &class, &instance);
+
+        if self.conn.window_is_fullscreen(window) || rules.fullscreen() {
+            return Decoration::NO_DECORATION;
+        }
+
+        let floating = self.conn.must_free_window(window)
+            || rules.float()
+            || self.conn.get_icccm_window_transient_for(window).is_some()
+            || self
+                .conn
+                .get_icccm_window_client_leader(window)
+                .and_then(|leader| self.client_any(leader))
+                .filter(|leader| leader.window() != window)
+                .is_some();
+
+        let workspace = self.initial_workspace(window, &rules, &class, &instance);
+
+        self.adapt_decoration(
+            if floating {
+                self.preset_decoration(Decoration::FREE_DECORATION, PlacementMethod::Free, false)
+            } else {
+                self.pointer_spawn_zone(workspace)
+                    .or_else(|| self.workspaces[workspace].active_spawn_zone())
+                    .and_then(|id| self.zone_manager.active_layoutconfig(id))
+                    .map_or(Decoration::FREE_DECORATION, |config| {
+                        self.preset_decoration(config.decoration, PlacementMethod::Tile, false)
+                    })
+            },
+            self.config.decoration_strategy == DecorationStrategy::Reparent,
+        )
+    }
+
+    // new clients are tiled into the zone under the pointer, if enabled
+    fn pointer_spawn_zone(
+        &self,
+        workspace: Index,
+    ) -> Option<ZoneId> {
+        if !self.config.spawn_at_pointer || workspace != self.active_workspace() {
+            return None;
+        }
+
+        self.zone_manager.zone_at(
+            &self.workspaces[workspace].spawn_zones(),
+            self.conn.get_pointer_position(),
+        )
+    }
+
+    // presets only replace decorations, undecorated placements stay undecorated
+    fn preset_decoration(
+        &self,
+        decoration: Decoration,
+        method: PlacementMethod,
+        tabbed: bool,
+    ) -> Decoration {
+        if decoration == Decoration::NO_DECORATION {
+            return decoration;
+        }
+
+        let presets = &self.config.decoration_presets;
+
+        match method {
+            PlacementMethod::Free => presets.floating,
+            PlacementMethod::Tile if tabbed => presets.tabbed.or(presets.tiled),
+            PlacementMethod::Tile => presets.tiled,
+        }
+        .map_or(decoration, |preset| preset.decoration())
+    }
+
+    fn remanage(
+        &self,
+        client: &Client,
+        must_alter_workspace: bool,
+    ) {
+        if client.is_managed() {
+            return;
+        }
+
+        let window = client.window();
+        info!("remanaging client with window {:#0x}", window);
+
+        client.set_managed(Toggle::On);
+
+        if must_alter_workspace {
+            let workspace = client
+                .leader()
+                .and_then(|leader| self.client(leader))
+                .map(|leader| {
+                    let workspace = leader.workspace();
+
+                    client.set_workspace(workspace);
+                    self.workspace(leader.workspace())
+                });
+
+            if let Some(workspace) = workspace {
+                if !workspace.contains(window) {
+                    workspace.add_client(window, &InsertPos::Back);
+                }
+            }
+        }
+
+        if client.is_sticky() {
+            client.set_sticky(Toggle::Off);
             self.stick(client);
             self.map_client(client);
         }
@@ -895,7 +1749,7 @@ impl<'model> Model<'model> {
         &self,
         client: &Client,
     ) -> bool {
-        !client.is_disowned() && !client.is_iconified()
+        !client.is_disowned() && !client.is_iconified() && !client.is_paused()
     }
 
     fn remove_window(
@@ -903,8 +1757,8 @@ impl<'model> Model<'model> {
         window: Window,
     ) {
         let client = match self.client_any(window) {
-            Some(client) if !client.consume_unmap_if_expecting() => client,
-            _ => return,
+            Some(client) => client,
+            None => return,
         };
 
         let (window, frame) = client.windows();
@@ -916,12 +1770,19 @@ impl<'model> Model<'model> {
             self.remanage(client, true);
         }
 
-        if let Ok(geometry) = self.conn.get_window_geometry(frame) {
-            self.conn.unparent_window(window, geometry.pos);
+        if self.move_buffer.window() == Some(window) {
+            self.stop_moving();
         }
 
-        self.conn.cleanup_window(window);
-        self.conn.destroy_window(frame);
+        if self.resize_buffer.window() == Some(window) {
+            self.stop_resizing();
+        }
+
+        if self.teleporting.get() == Some(window) {
+            self.finish_teleport(None);
+        }
+
+        self.destroy_frame(window, frame);
 
         if client.is_sticky() {
             self.unstick(client);
@@ -949,14 +1810,129 @@ impl<'model> Model<'model> {
         }
 
         self.stack_manager.remove_window(window);
+        self.stack_manager.remove_window(frame);
+        self.pip_clients.borrow_mut().remove(&window);
+        self.producer_slots.borrow_mut().remove(&window);
+        self.paused.borrow_mut().retain(|&paused| paused != window);
+        self.float_slots.borrow_mut().remove(&window);
+        self.maximized_spans.borrow_mut().remove(&window);
+        self.flashing.borrow_mut().remove(&window);
+        self.hints.borrow_mut().retain(|&(_, hinted)| hinted != window);
         self.frame_map.remove(&frame);
         self.window_map.remove(&window);
         self.client_map.remove(&window);
-        self.pid_map.remove(&window);
+
+        if self.pass_through.get() == Some(window) {
+            self.end_pass_through();
+        }
+
+        if self.confined.get() == Some(window) {
+            self.release_confined_pointer();
+        }
+
+        self.pid_map.retain(|_, &mut pid_window| pid_window != window);
         self.fullscreen_regions.borrow_mut().remove(&window);
+        self.update_client_lists(&[]);
 
         self.sync_focus();
         self.apply_layout(workspace);
+
+        // focus returns to where it was before the dedicated client appeared
+        let return_to = self.dedicated_clients.borrow_mut().remove(&window);
+
+        if let Some(return_to) = return_to {
+            if workspace == self.active_workspace() {
+                self.activate_workspace(return_to);
+            }
+        } else if workspace == self.active_workspace() && self.workspaces[workspace].is_empty() {
+            self.leave_empty_workspace();
+        }
+    }
+
+    fn leave_empty_workspace(&self) {
+        let to = match self.config.empty_workspace_action {
+            EmptyWorkspaceAction::Stay => None,
+            EmptyWorkspaceAction::Recent => self
+                .workspaces
+                .stack()
+                .into_iter()
+                .rev()
+                .map(|id| id as Index)
+                .find(|&index| index < self.workspaces.len() && !self.workspaces[index].is_empty()),
+            EmptyWorkspaceAction::Home => Some(self.config.home_workspace),
+        };
+
+        if let Some(to) = to {
+            self.activate_workspace(to);
+        }
+    }
+
+    #[inline]
+    fn create_frame(
+        &self,
+        window: Window,
+        region: Region,
+    ) -> winsys::Result<Window> {
+        match self.config.decoration_strategy {
+            DecorationStrategy::Reparent => self.conn.create_frame(region),
+            DecorationStrategy::Direct => Ok(window),
+        }
+    }
+
+    #[inline]
+    fn destroy_frame(
+        &self,
+        window: Window,
+        frame: Window,
+    ) {
+        if frame != window {
+            if let Ok(geometry) = self.conn.get_window_geometry(frame) {
+                self.conn.unparent_window(window, geometry.pos);
+            }
+        }
+
+        self.conn.cleanup_window(window);
+
+        if frame != window {
+            self.conn.destroy_window(frame);
+            self.retired.borrow_mut().retire(frame);
+        }
+    }
+
+    #[inline(always)]
+    fn adapt_decoration(
+        &self,
+        decoration: Decoration,
+        framed: bool,
+    ) -> Decoration {
+        let decoration = decoration.with_colors(self.colors.get());
+
+        if framed {
+            decoration
+        } else {
+            decoration.uniform()
+        }
+    }
+
+    // colors set through X resources take precedence over the configured ones
+    fn load_theme(&self) {
+        let colors = self.config.colors.with_overrides(|name| {
+            self.conn.get_resource(
+                &format!("{}.border.{}", WM_NAME!(), name),
+                concat!(WM_NAME!(), ".Border.Color"),
+            )
+        });
+
+        if colors == self.colors.get() {
+            return;
+        }
+
+        self.colors.set(colors);
+
+        self.client_map.values().for_each(|client| {
+            client.set_decoration(client.decoration().with_colors(colors));
+            self.render_decoration(client);
+        });
     }
 
     #[inline(always)]
@@ -966,14 +1942,96 @@ impl<'model> Model<'model> {
     ) {
         let (border, frame_color) = client.decoration_colors();
 
+        if client.frame() == client.window() {
+            let width = client.frame_extents().left as u32;
+
+            self.conn.set_window_border_width(client.window(), width);
+
+            if let Some(color) = frame_color.or_else(|| border.map(|(_, color)| color)) {
+                self.conn.set_window_border_color(client.window(), color);
+            }
+
+            return;
+        }
+
         if let Some((width, color)) = border {
             self.conn.set_window_border_width(client.frame(), width);
             self.conn.set_window_border_color(client.frame(), color);
         }
 
         if let Some(color) = frame_color {
-            self.conn.set_window_background_color(client.frame(), color);
+            let opaque_region = client.opaque_region();
+
+            if opaque_region.is_empty() {
+                self.conn.set_window_background_color(client.frame(), color);
+            } else {
+                let extents = client.frame_extents();
+                let frame_region = Region {
+                    pos: Pos::default(),
+                    dim: client.active_region().dim,
+                };
+
+                // only paint the parts of the frame not covered by opaque client content
+                let regions = opaque_region.iter().fold(vec![frame_region], |regions, opaque| {
+                    let opaque = Region::new(
+                        opaque.pos.x + extents.left,
+                        opaque.pos.y + extents.top,
+                        opaque.dim.w,
+                        opaque.dim.h,
+                    );
+
+                    regions
+                        .iter()
+                        .flat_map(|region| region.subtract(opaque))
+                        .collect()
+                });
+
+                self.conn.fill_window_regions(client.frame(), color, &regions);
+            }
+        }
+    }
+
+    // the frame only takes input on its decoration and on the part of its interior that the
+    // client actually covers, as clients can end up smaller than their frame due to size hints
+    fn shape_frame(
+        &self,
+        client: &Client,
+        content: Region,
+    ) {
+        let border_width = client
+            .decoration()
+            .border
+            .map_or(0, |border| border.width as i32);
+
+        let dim = client.active_region().dim;
+        let inner_region = client.inner_region();
+
+        let outer_region = Region::new(
+            -border_width,
+            -border_width,
+            dim.w + 2 * border_width,
+            dim.h + 2 * border_width,
+        );
+
+        let mut regions = outer_region.subtract(inner_region);
+        regions.extend(inner_region.intersect(content));
+
+        let radius = self.config.frame_corner_radius as i32;
+
+        if radius > 0 && !client.is_fullscreen() {
+            let rounded = outer_region.rounded(radius);
+
+            regions = regions
+                .iter()
+                .flat_map(|region| rounded.iter().filter_map(move |&strip| region.intersect(strip)))
+                .collect();
+
+            self.conn.set_window_bounding_shape(client.frame(), &rounded);
+        } else {
+            self.conn.reset_window_bounding_shape(client.frame());
         }
+
+        self.conn.set_window_input_shape(client.frame(), &regions);
     }
 
     #[inline(always)]
@@ -991,7 +2049,17 @@ impl<'model> Model<'model> {
         let zone = self.zone_manager.zone(client.zone());
         zone.set_method(placement.method);
 
-        client.set_decoration(placement.decoration);
+        client.set_decoration(self.adapt_decoration(
+            self.preset_decoration(
+                placement.decoration,
+                placement.method,
+                self.zone_manager
+                    .parent_id(client.zone())
+                    .filter(|&parent| self.zone_manager.is_tab(parent))
+                    .is_some(),
+            ),
+            client.frame() != client.window(),
+        ));
         client.set_region(match placement.method {
             PlacementMethod::Free => {
                 zone.set_region(region);
@@ -1008,15 +2076,67 @@ impl<'model> Model<'model> {
         method: PlacementMethod,
     ) {
         let (window, frame) = client.windows();
-
-        self.conn.place_window(window, &client.inner_region());
-        self.conn.place_window(frame, &match method {
+        let region = match method {
             PlacementMethod::Free => client.free_region(),
             PlacementMethod::Tile => client.tile_region(),
-        });
+        };
+
+        if frame == window {
+            let width = client.frame_extents().left;
+
+            self.conn.place_window(window, &Region {
+                pos: region.pos,
+                dim: Dim {
+                    w: std::cmp::max(1, region.dim.w - 2 * width),
+                    h: std::cmp::max(1, region.dim.h - 2 * width),
+                },
+            });
+
+            self.render_decoration(client);
+            self.update_client_monitor(client);
+
+            if self.confined.get() == Some(window) {
+                self.conn.confine_pointer_to(window);
+            }
+
+            return;
+        }
+
+        self.conn.place_window(window, &client.inner_region());
+        self.conn.place_window(frame, &region);
+        self.shape_frame(client, client.inner_region());
 
         self.render_decoration(client);
         self.conn.update_window_offset(window, frame);
+        self.update_client_monitor(client);
+
+        // the barriers that confine the pointer do not follow the frame
+        if self.confined.get() == Some(window) {
+            self.conn.confine_pointer_to(frame);
+        }
+    }
+
+    // the monitor of a client is the one that holds the center of its region
+    fn update_client_monitor(
+        &self,
+        client: &Client,
+    ) {
+        let region = client.active_region();
+        let center = Pos {
+            x: region.pos.x + region.dim.w / 2,
+            y: region.pos.y + region.dim.h / 2,
+        };
+
+        let monitor = self
+            .partitions
+            .iter()
+            .find(|partition| partition.full_region().encompasses(center))
+            .map(|partition| partition.screen().number());
+
+        if monitor.is_some() && monitor != client.monitor() {
+            client.set_monitor(monitor);
+            self.conn.set_window_monitor(client.window(), monitor);
+        }
     }
 
     #[inline(always)]
@@ -1029,7 +2149,10 @@ impl<'model> Model<'model> {
             info!("mapping client with window {:#0x}", window);
 
             self.conn.map_window(window);
-            self.conn.map_window(frame);
+
+            if frame != window {
+                self.conn.map_window(frame);
+            }
             self.render_decoration(client);
             client.set_mapped(Toggle::On);
         }
@@ -1072,6 +2195,17 @@ impl<'model> Model<'model> {
         if producer.consumer_len() == 0 {
             let workspace = self.workspace(pworkspace);
 
+            // remember where the producer was, so that it can be put back there
+            let successor = workspace
+                .clients()
+                .into_iter()
+                .skip_while(|&window| window != pwindow)
+                .nth(1);
+
+            self.producer_slots
+                .borrow_mut()
+                .insert(pwindow, (pworkspace, successor));
+
             if pworkspace == cworkspace {
                 workspace.replace_client(pwindow, cwindow);
             } else {
@@ -1107,17 +2241,31 @@ impl<'model> Model<'model> {
         producer.remove_consumer(consumer.window());
 
         if producer.consumer_len() == 0 {
+            let slot = self.producer_slots.borrow_mut().remove(&producer.window());
             let workspace = consumer.workspace();
 
-            {
-                let workspace = self.workspace(workspace);
+            let workspace = if self.workspace(workspace).contains(consumer.window()) {
+                self.workspace(workspace)
+                    .replace_client(consumer.window(), producer.window());
 
-                if workspace.contains(consumer.window()) {
-                    workspace.replace_client(consumer.window(), producer.window());
-                } else {
-                    workspace.add_client(producer.window(), &InsertPos::Back);
-                }
-            }
+                workspace
+            } else {
+                let (workspace, successor) = slot
+                    .filter(|&(workspace, _)| workspace < self.workspaces.len())
+                    .unwrap_or((workspace, None));
+
+                let workspace_ref = self.workspace(workspace);
+
+                workspace_ref.add_client(
+                    producer.window(),
+                    &match successor.filter(|&successor| workspace_ref.contains(successor)) {
+                        Some(successor) => InsertPos::BeforeIdent(successor),
+                        None => InsertPos::Back,
+                    },
+                );
+
+                workspace
+            };
 
             producer.set_workspace(workspace);
             self.remanage(producer, false);
@@ -1128,6 +2276,106 @@ impl<'model> Model<'model> {
         consumer.unset_producer();
     }
 
+    pub fn consume_focus(
+        &self,
+        producer: Window,
+    ) -> winsys::Result<()> {
+        let consumer = self
+            .focused_client()
+            .ok_or_else(|| anyhow!("no focused client"))?;
+
+        let producer = self
+            .client(producer)
+            .ok_or_else(|| anyhow!("no producer with window {:#0x}", producer))?;
+
+        if consumer.window() == producer.window() {
+            return Err(anyhow!("a client cannot consume itself"));
+        }
+
+        if consumer.producer().is_some() || consumer.consumer_len() > 0 {
+            return Err(anyhow!("client is already part of a consumer chain"));
+        }
+
+        self.consume_client(consumer, producer);
+        Ok(())
+    }
+
+    pub fn unconsume_focus(&self) -> winsys::Result<()> {
+        let consumer = self
+            .focused_client()
+            .filter(|client| client.producer().is_some())
+            .ok_or_else(|| anyhow!("focused client has no producer"))?;
+
+        self.unconsume_client(consumer);
+        Ok(())
+    }
+
+    pub fn pause_focus(&self) {
+        let client = match self.focused_client() {
+            Some(client) if !client.is_paused() => client,
+            _ => return,
+        };
+
+        if !self.signal_client(client, Signal::SIGSTOP) {
+            return;
+        }
+
+        info!("pausing client with window {:#0x}", client.window());
+
+        client.set_paused(Toggle::On);
+        self.paused.borrow_mut().push(client.window());
+        self.render_decoration(client);
+
+        // a stopped client cannot handle input, so focus moves on
+        self.cycle_focus(Direction::Forward);
+    }
+
+    // paused clients cannot be focused, so the most recently paused one is resumed
+    pub fn resume_focus(&self) {
+        let window = match self.paused.borrow().last().copied() {
+            Some(window) => window,
+            None => return,
+        };
+
+        if let Some(client) = self.client_any(window) {
+            self.resume_client(client);
+            self.focus(client);
+        }
+    }
+
+    fn resume_client(
+        &self,
+        client: &Client,
+    ) {
+        if !client.is_paused() || !self.signal_client(client, Signal::SIGCONT) {
+            return;
+        }
+
+        info!("resuming client with window {:#0x}", client.window());
+
+        client.set_paused(Toggle::Off);
+        self.paused.borrow_mut().retain(|&paused| paused != client.window());
+        self.render_decoration(client);
+    }
+
+    fn signal_client(
+        &self,
+        client: &Client,
+        signal: Signal,
+    ) -> bool {
+        let pid = match client.pid() {
+            Some(pid) if pid != process::id() => pid,
+            _ => {
+                warn!("no process known for client with window {:#0x}", client.window());
+                return false;
+            },
+        };
+
+        signal::kill(unistd::Pid::from_raw(pid as i32), signal)
+            .map_err(|err| warn!("could not send {} to process {}: {}", signal, pid, err))
+            .is_ok()
+    }
+
     #[inline(always)]
     pub fn kill_focus(&self) {
         if let Some(focus) = self.focus.get() {
@@ -1160,12 +2408,32 @@ impl<'model> Model<'model> {
             .cycle_zones(dir, &self.zone_manager);
     }
 
-    #[inline(always)]
-    pub fn cycle_focus(
-        &self,
-        dir: Direction,
-    ) {
-        if let Some((_, window)) = self.workspace(self.active_workspace()).cycle_focus(
+    pub fn toggle_zone(&self) {
+        let workspace = self.workspace(self.active_workspace());
+        let zone = match workspace.previous_focus_zone() {
+            Some(zone) => zone,
+            None => return,
+        };
+
+        if let Some(client) = workspace
+            .clients()
+            .into_iter()
+            .filter_map(|window| self.client_map.get(&window))
+            .filter(|&client| {
+                self.is_focusable(client) && self.zone_manager.nearest_cycle(client.zone()) == zone
+            })
+            .max_by_key(|client| client.last_focused())
+        {
+            self.focus(client);
+        }
+    }
+
+    #[inline(always)]
+    pub fn cycle_focus(
+        &self,
+        dir: Direction,
+    ) {
+        if let Some((_, window)) = self.workspace(self.active_workspace()).cycle_focus(
             dir,
             &self.client_map,
             &self.zone_manager,
@@ -1236,6 +2504,196 @@ impl<'model> Model<'model> {
         );
     }
 
+    pub fn teleport_focus(&self) {
+        if let Some(focus) = self.focus.get() {
+            if self.conn.grab_input() {
+                info!("selecting teleport target for window {:#0x}", focus);
+                self.teleporting.set(Some(focus));
+            }
+        }
+    }
+
+    fn finish_teleport(
+        &self,
+        target: Option<TeleportTarget>,
+    ) {
+        if let Some(window) = self.teleporting.take() {
+            self.conn.release_input();
+
+            match target {
+                Some(TeleportTarget::Workspace(workspace)) => {
+                    self.move_window_to_workspace(window, workspace);
+                },
+                Some(TeleportTarget::Screen(region)) => {
+                    if let Some(client) = self.client(window) {
+                        self.move_client_to_screen(client, region);
+                    }
+                },
+                None => {},
+            }
+        }
+    }
+
+    // the layout only spans the active screen, so a tiled client is floated to
+    // be placed elsewhere; it ends up centered on the screen
+    fn move_client_to_screen(
+        &self,
+        client: &Client,
+        screen: Region,
+    ) {
+        if !self.is_free(client) {
+            self.set_floating_client(client, Toggle::On);
+        }
+
+        info!("moving client with window {:#0x} to another screen", client.window());
+
+        let mut region = client.free_region();
+        region.pos = screen.from_absolute_inner_center(region.dim).pos;
+
+        self.conn.move_window(client.frame(), region.pos);
+        client.set_region(PlacementClass::Free(region));
+        self.update_client_monitor(client);
+    }
+
+    // labels every visible client, typing a label focuses its client
+    pub fn hint_focus(&self) {
+        if !self.hints.borrow().is_empty() {
+            self.finish_hinting(None);
+            return;
+        }
+
+        let alphabet = self
+            .config
+            .hint_alphabet
+            .chars()
+            .filter(char::is_ascii_alphabetic)
+            .map(|c| c.to_ascii_lowercase())
+            .fold(Vec::new(), |mut alphabet, c| {
+                if !alphabet.contains(&c) {
+                    alphabet.push(c);
+                }
+
+                alphabet
+            });
+
+        let mut clients: Vec<&Client> = self
+            .client_map
+            .values()
+            .filter(|client| client.is_mapped())
+            .collect();
+
+        // labels are handed out in reading order
+        clients.sort_by_key(|client| {
+            let pos = client.active_region().pos;
+            (pos.y, pos.x)
+        });
+
+        let labels = Util::hint_labels(&alphabet, clients.len());
+
+        if labels.is_empty() {
+            if alphabet.len() < 2 {
+                warn!("the hint alphabet needs at least two distinct letters");
+            }
+
+            return;
+        }
+
+        if self.conn.grab_input() {
+            info!("picking one of {} clients by hint", clients.len());
+
+            *self.hints.borrow_mut() = labels
+                .into_iter()
+                .zip(clients.iter().map(|client| client.window()))
+                .collect();
+
+            self.hint_prefix.borrow_mut().clear();
+            self.draw_hints();
+        }
+    }
+
+    fn draw_hints(&self) {
+        let prefix = self.hint_prefix.borrow();
+
+        self.conn.draw_hints(
+            &self
+                .hints
+                .borrow()
+                .iter()
+                .filter(|(label, _)| label.starts_with(prefix.as_str()))
+                .filter_map(|(label, window)| {
+                    self.client(*window)
+                        .map(|client| (client.active_region(), label.clone()))
+                })
+                .collect::<Vec<(Region, String)>>(),
+        );
+    }
+
+    fn handle_hint_key(
+        &self,
+        key: Key,
+    ) {
+        let letter = match key {
+            Key::Shift
+            | Key::Control
+            | Key::Alt
+            | Key::Super
+            | Key::LeftShift
+            | Key::RightShift
+            | Key::LeftControl
+            | Key::RightContol
+            | Key::LeftAlt
+            | Key::RightAlt
+            | Key::LeftSuper
+            | Key::RightSuper => return,
+            Key::Backspace => {
+                self.hint_prefix.borrow_mut().pop();
+                self.draw_hints();
+                return;
+            },
+            // only the letter keys have single character names
+            key => match key.to_string().chars().collect::<Vec<char>>().as_slice() {
+                &[c] => c.to_ascii_lowercase(),
+                _ => {
+                    self.finish_hinting(None);
+                    return;
+                },
+            },
+        };
+
+        self.hint_prefix.borrow_mut().push(letter);
+
+        let matching: Vec<Window> = {
+            let prefix = self.hint_prefix.borrow();
+
+            self.hints
+                .borrow()
+                .iter()
+                .filter(|(label, _)| label.starts_with(prefix.as_str()))
+                .map(|&(_, window)| window)
+                .collect()
+        };
+
+        match matching.as_slice() {
+            [] => self.finish_hinting(None),
+            &[window] => self.finish_hinting(Some(window)),
+            _ => self.draw_hints(),
+        }
+    }
+
+    fn finish_hinting(
+        &self,
+        window: Option<Window>,
+    ) {
+        self.hints.borrow_mut().clear();
+        self.hint_prefix.borrow_mut().clear();
+        self.conn.draw_hints(&[]);
+        self.conn.release_input();
+
+        if let Some(window) = window {
+            self.focus_window(window);
+        }
+    }
+
     #[inline]
     pub fn move_focus_to_workspace(
         &self,
@@ -1262,8 +2720,11 @@ impl<'model> Model<'model> {
         client: &Client,
         to: Index,
     ) {
-        let (window, from) =
-            if to != self.active_workspace() && to < self.workspaces.len() && !client.is_sticky() {
+        let (window, from) = if to != self.active_workspace()
+            && to < self.workspaces.len()
+            && !client.is_sticky()
+            && !self.config.kiosk
+        {
                 (client.window(), client.workspace())
             } else {
                 return;
@@ -1285,14 +2746,64 @@ impl<'model> Model<'model> {
         self.apply_layout(from);
         self.apply_stack(from);
 
+        self.learn_workspace(client);
         self.sync_focus();
     }
 
+    fn learned_workspace(
+        &self,
+        class: &str,
+        instance: &str,
+    ) -> Option<Index> {
+        if !self.config.learn_affinity {
+            return None;
+        }
+
+        self.state
+            .borrow()
+            .learned_workspace(class, instance)
+            .filter(|&workspace| workspace < self.workspaces.len())
+    }
+
+    fn learn_workspace(
+        &self,
+        client: &Client,
+    ) {
+        if !self.config.learn_affinity || client.parent().is_some() || client.is_sticky() {
+            return;
+        }
+
+        let mut state = self.state.borrow_mut();
+
+        if state.learn_workspace(&client.class(), &client.instance(), client.workspace()) {
+            state.save();
+        }
+    }
+
     #[inline(always)]
     pub fn toggle_workspace(&self) {
         self.activate_workspace(self.prev_workspace.get());
     }
 
+    pub fn workspace_back(&self) {
+        let active = self.active_workspace();
+        let to = loop {
+            match self.workspaces.unwind() {
+                Some(id) if id as Index == active => continue,
+                Some(id) => break id as Index,
+                None => return,
+            }
+        };
+
+        self.activate_workspace(to);
+
+        // the workspace just left is not recorded, so that going back repeatedly
+        // returns through ever older workspaces
+        if self.active_workspace() == to {
+            self.workspaces.unwind();
+        }
+    }
+
     #[inline(always)]
     pub fn activate_next_workspace(
         &self,
@@ -1305,11 +2816,88 @@ impl<'model> Model<'model> {
         ));
     }
 
+    pub fn scroll_workspace(
+        &self,
+        dir: Direction,
+    ) {
+        if self.in_scroll_zone(self.conn.get_pointer_position()) {
+            self.activate_next_workspace(dir);
+        }
+    }
+
+    // keeps scrolling over the gaps between clients from switching workspaces
+    fn in_scroll_zone(
+        &self,
+        pos: Pos,
+    ) -> bool {
+        if self.config.scroll_zone == ScrollZone::Anywhere {
+            return true;
+        }
+
+        let width = self.config.scroll_zone_width as i32;
+        let near_edge = self
+            .partitions
+            .iter()
+            .map(|partition| partition.screen().full_region())
+            .find(|region| region.encompasses(pos))
+            .map_or(false, |region| {
+                pos.x - region.pos.x < width
+                    || pos.y - region.pos.y < width
+                    || region.pos.x + region.dim.w - pos.x <= width
+                    || region.pos.y + region.dim.h - pos.y <= width
+            });
+
+        // the gaps between tiled clients lie within a layout zone, so they are not
+        // taken for empty parts of the desktop
+        let workspace = self.active_workspace();
+        let workspace = &self.workspaces[workspace];
+
+        near_edge
+            || workspace.is_empty()
+            || self.zone_manager.zone_at(&workspace.spawn_zones(), pos).is_none()
+    }
+
+    pub fn activate_workspace_towards(
+        &self,
+        edge: Edge,
+    ) {
+        if let Some(index) = self.config.desktop_layout.neighbour(
+            self.workspaces.len(),
+            self.active_workspace(),
+            edge,
+        ) {
+            self.activate_workspace(index);
+        }
+    }
+
+    // builds the workspace from its template the first time it is activated
+    fn use_workspace(
+        &self,
+        index: Index,
+    ) {
+        if !self.used_workspaces.borrow_mut().insert(index) {
+            return;
+        }
+
+        self.config
+            .workspace_templates
+            .iter()
+            .filter(|template| template.workspace == index)
+            .flat_map(|template| template.spawn.iter())
+            .for_each(|command| {
+                self.autostart.borrow_mut().launch(AutostartEntry {
+                    command: command.to_owned(),
+                    workspace: Some(index),
+                    restart: false,
+                });
+            });
+    }
+
     pub fn activate_workspace(
         &self,
         to: Index,
     ) {
-        if to == self.active_workspace() || to >= self.workspaces.len() {
+        if to == self.active_workspace() || to >= self.workspaces.len() || self.config.kiosk {
             return;
         }
 
@@ -1335,15 +2923,19 @@ impl<'model> Model<'model> {
                 }
             });
 
-        self.sticky_clients.borrow().iter().for_each(|&window| {
+        self.sticky_clients.borrow().keys().for_each(|&window| {
             self.client_unchecked(window).set_workspace(to);
         });
 
+        self.anchor_sticky_clients();
+
         self.conn.set_current_desktop(to);
 
         self.workspaces.activate_for(&Selector::AtIndex(to));
         self.apply_layout(to);
         self.apply_stack(to);
+        self.use_workspace(to);
+        self.publish_root_status();
 
         self.sync_focus();
     }
@@ -1352,10 +2944,18 @@ impl<'model> Model<'model> {
     pub fn change_gap_size(
         &mut self,
         change: Change<u32>,
+    ) -> Result<(), StateChangeError> {
+        self.change_gap_size_for(ZoneSelector::Focus, change)
+    }
+
+    pub fn change_gap_size_for(
+        &mut self,
+        sel: ZoneSelector,
+        change: Change<u32>,
     ) -> Result<(), StateChangeError> {
         let workspace = self.active_workspace();
 
-        self.workspaces[workspace].change_gap_size(change, &mut self.zone_manager)?;
+        self.workspaces[workspace].change_gap_size(sel, change, &mut self.zone_manager)?;
         self.apply_layout(workspace);
         self.apply_stack(workspace);
 
@@ -1366,7 +2966,8 @@ impl<'model> Model<'model> {
     pub fn copy_prev_layout_data(&mut self) -> Result<(), StateChangeError> {
         let workspace = self.active_workspace();
 
-        self.workspaces[workspace].copy_prev_layout_data(&mut self.zone_manager)?;
+        self.workspaces[workspace]
+            .copy_prev_layout_data(ZoneSelector::Focus, &mut self.zone_manager)?;
         self.apply_layout(workspace);
         self.apply_stack(workspace);
 
@@ -1377,18 +2978,59 @@ impl<'model> Model<'model> {
     pub fn reset_layout_data(&mut self) -> Result<(), StateChangeError> {
         let workspace = self.active_workspace();
 
-        self.workspaces[workspace].reset_layout_data(&mut self.zone_manager)?;
+        self.workspaces[workspace].reset_layout_data(ZoneSelector::Focus, &mut self.zone_manager)?;
         self.apply_layout(workspace);
         self.apply_stack(workspace);
 
         Ok(())
     }
 
+    fn set_layout_defaults(&mut self) {
+        let margin = self.config.margin;
+
+        self.zone_manager.set_layout_defaults(LayoutDefaults {
+            gap_size: self.config.gap_size,
+            margin: Padding {
+                left: margin.left.unwrap_or_default(),
+                right: margin.right.unwrap_or_default(),
+                top: margin.top.unwrap_or_default(),
+                bottom: margin.bottom.unwrap_or_default(),
+            },
+        });
+    }
+
+    // changes the gap size by the configured step
+    pub fn step_gap_size(
+        &mut self,
+        dir: Direction,
+    ) -> Result<(), StateChangeError> {
+        let step = self.config.gap_step;
+
+        self.change_gap_size(match dir {
+            Direction::Forward => Change::Inc(step),
+            Direction::Backward => Change::Dec(step),
+        })
+    }
+
+    // changes the margin at the given edge by the configured step
+    pub fn step_margin(
+        &mut self,
+        edge: Edge,
+        dir: Direction,
+    ) -> Result<(), StateChangeError> {
+        let step = self.config.margin_step;
+
+        self.change_margin(edge, match dir {
+            Direction::Forward => Change::Inc(step),
+            Direction::Backward => Change::Dec(step),
+        })
+    }
+
     #[inline]
     pub fn reset_gap_size(&mut self) -> Result<(), StateChangeError> {
         let workspace = self.active_workspace();
 
-        self.workspaces[workspace].reset_gap_size(&mut self.zone_manager)?;
+        self.workspaces[workspace].reset_gap_size(ZoneSelector::Focus, &mut self.zone_manager)?;
         self.apply_layout(workspace);
         self.apply_stack(workspace);
 
@@ -1399,10 +3041,18 @@ impl<'model> Model<'model> {
     pub fn change_main_count(
         &mut self,
         change: Change<u32>,
+    ) -> Result<(), StateChangeError> {
+        self.change_main_count_for(ZoneSelector::Focus, change)
+    }
+
+    pub fn change_main_count_for(
+        &mut self,
+        sel: ZoneSelector,
+        change: Change<u32>,
     ) -> Result<(), StateChangeError> {
         let workspace = self.active_workspace();
 
-        self.workspaces[workspace].change_main_count(change, &mut self.zone_manager)?;
+        self.workspaces[workspace].change_main_count(sel, change, &mut self.zone_manager)?;
         self.apply_layout(workspace);
         self.apply_stack(workspace);
 
@@ -1413,10 +3063,18 @@ impl<'model> Model<'model> {
     pub fn change_main_factor(
         &mut self,
         change: Change<f32>,
+    ) -> Result<(), StateChangeError> {
+        self.change_main_factor_for(ZoneSelector::Focus, change)
+    }
+
+    pub fn change_main_factor_for(
+        &mut self,
+        sel: ZoneSelector,
+        change: Change<f32>,
     ) -> Result<(), StateChangeError> {
         let workspace = self.active_workspace();
 
-        self.workspaces[workspace].change_main_factor(change, &mut self.zone_manager)?;
+        self.workspaces[workspace].change_main_factor(sel, change, &mut self.zone_manager)?;
         self.apply_layout(workspace);
         self.apply_stack(workspace);
 
@@ -1428,77 +3086,556 @@ impl<'model> Model<'model> {
         &mut self,
         edge: Edge,
         change: Change<i32>,
+    ) -> Result<(), StateChangeError> {
+        self.change_margin_for(ZoneSelector::Focus, edge, change)
+    }
+
+    pub fn change_margin_for(
+        &mut self,
+        sel: ZoneSelector,
+        edge: Edge,
+        change: Change<i32>,
     ) -> Result<(), StateChangeError> {
         let workspace = self.active_workspace();
 
-        self.workspaces[workspace].change_margin(edge, change, &mut self.zone_manager)?;
+        self.workspaces[workspace].change_margin(sel, edge, change, &mut self.zone_manager)?;
         self.apply_layout(workspace);
         self.apply_stack(workspace);
 
         Ok(())
     }
 
-    #[inline]
-    pub fn reset_margin(&mut self) -> Result<(), StateChangeError> {
-        let workspace = self.active_workspace();
-
-        self.workspaces[workspace].reset_margin(&mut self.zone_manager)?;
+    // sets a margin of the workspace outright, the margins are kept across restarts
+    pub fn set_workspace_margin(
+        &mut self,
+        workspace: Index,
+        edge: Edge,
+        value: i32,
+    ) -> Result<(), StateChangeError> {
+        self.workspaces[workspace].set_persistent_margin(
+            ZoneSelector::Root,
+            edge,
+            value,
+            &mut self.zone_manager,
+        )?;
+
+        self.store_workspace_margin(workspace);
         self.apply_layout(workspace);
         self.apply_stack(workspace);
 
         Ok(())
     }
 
-    #[inline]
-    pub fn set_layout(
+    pub fn apply_margin_preset(
         &mut self,
-        kind: LayoutKind,
-    ) -> Result<(), StateChangeError> {
-        let workspace = self.active_workspace();
+        name: &str,
+        workspace: Index,
+    ) -> bool {
+        let margin = match self.config.margin_presets.get(name) {
+            Some(&margin) => margin,
+            None => return false,
+        };
 
-        if let Some(id) = self.workspaces[workspace].active_focus_zone() {
-            info!("activating layout {:?} on workspace {}", kind, workspace);
+        info!("applying margin preset {} to workspace {}", name, workspace);
 
-            self.zone_manager.set_kind(id, kind)?;
-            self.apply_layout(workspace);
-            self.apply_stack(workspace);
+        for (edge, value) in [
+            (Edge::Left, margin.left),
+            (Edge::Right, margin.right),
+            (Edge::Top, margin.top),
+            (Edge::Bottom, margin.bottom),
+        ]
+        .iter()
+        .filter_map(|&(edge, value)| value.map(|value| (edge, value)))
+        {
+            if self.workspaces[workspace]
+                .set_persistent_margin(ZoneSelector::Root, edge, value, &mut self.zone_manager)
+                .is_err()
+            {
+                return false;
+            }
         }
 
-        Ok(())
+        self.store_workspace_margin(workspace);
+        self.apply_layout(workspace);
+        self.apply_stack(workspace);
+
+        true
     }
 
-    #[inline]
-    pub fn toggle_layout(&mut self) {
-        let workspace = self.active_workspace();
+    fn store_workspace_margin(
+        &self,
+        workspace: Index,
+    ) {
+        let root_zone = self.workspaces[workspace].root_zone();
+        let cycle = self.zone_manager.zone(self.zone_manager.nearest_cycle(root_zone));
 
-        if let Some(id) = self.workspaces[workspace].active_focus_zone() {
-            let prev_kind = self.zone_manager.set_prev_kind(id);
+        if let Some(data) = cycle.data() {
+            let mut state = self.state.borrow_mut();
 
-            info!(
-                "activating layout {:?} on workspace {}",
-                prev_kind, workspace
-            );
+            state.store_workspace_margin(workspace, [
+                data.margin.left,
+                data.margin.right,
+                data.margin.top,
+                data.margin.bottom,
+            ]);
 
-            self.apply_layout(workspace);
-            self.apply_stack(workspace);
+            state.save();
         }
     }
 
-    #[inline(always)]
-    pub fn set_floating_focus(
-        &self,
-        toggle: Toggle,
-    ) {
-        if let Some(focus) = self.focus.get() {
-            self.set_floating_window(focus, toggle);
+    fn restore_workspace_margins(&mut self) {
+        let margins = self.state.borrow().workspace_margins().to_vec();
+
+        for stored in margins {
+            let workspace = match self.workspaces.get(stored.workspace) {
+                Some(workspace) => workspace,
+                None => continue,
+            };
+
+            for (&edge, &value) in [Edge::Left, Edge::Right, Edge::Top, Edge::Bottom]
+                .iter()
+                .zip(stored.margin.iter())
+            {
+                workspace
+                    .set_persistent_margin(ZoneSelector::Root, edge, value, &mut self.zone_manager)
+                    .ok();
+            }
         }
     }
 
-    #[inline(always)]
-    pub fn set_floating_window(
-        &self,
-        window: Window,
-        toggle: Toggle,
+    #[inline]
+    pub fn reset_margin(&mut self) -> Result<(), StateChangeError> {
+        let workspace = self.active_workspace();
+
+        self.workspaces[workspace].reset_margin(ZoneSelector::Focus, &mut self.zone_manager)?;
+        self.apply_layout(workspace);
+        self.apply_stack(workspace);
+
+        Ok(())
+    }
+
+    // profiles are saved from and applied to the layout the focused zone is part of
+    fn layout_profile_zone(
+        &self,
+        workspace: Index,
+    ) -> Option<ZoneId> {
+        self.workspaces[workspace]
+            .active_focus_zone()
+            .map(|id| self.zone_manager.nearest_cycle(id))
+    }
+
+    pub fn save_layout_profile(
+        &self,
+        name: &str,
+    ) -> bool {
+        let workspace = self.active_workspace();
+        let cycle = match self.layout_profile_zone(workspace) {
+            Some(id) => self.zone_manager.zone(id),
+            None => return false,
+        };
+
+        let (kind, data) = match (cycle.kind(), cycle.data()) {
+            (Ok(kind), Some(data)) => (kind, data),
+            _ => return false,
+        };
+
+        info!("saving layout profile {} from workspace {}", name, workspace);
+
+        let mut state = self.state.borrow_mut();
+        state.store_layout_profile(LayoutProfile {
+            name: name.to_owned(),
+            kind: kind.name(),
+            main_count: data.main_count,
+            main_factor: data.main_factor,
+            gap_size: data.gap_size,
+            margin: [
+                data.margin.left,
+                data.margin.right,
+                data.margin.top,
+                data.margin.bottom,
+            ],
+        });
+
+        state.save();
+        true
+    }
+
+    pub fn apply_layout_profile(
+        &mut self,
+        name: &str,
+        workspace: Index,
+    ) -> bool {
+        let profile = match self.state.borrow().layout_profile(name) {
+            Some(profile) => profile.clone(),
+            None => return false,
+        };
+
+        let kind = match LayoutKind::from_name(&profile.kind) {
+            Some(kind) => kind,
+            None => return false,
+        };
+
+        let id = match self.layout_profile_zone(workspace) {
+            Some(id) => id,
+            None => return false,
+        };
+
+        info!("applying layout profile {} to workspace {}", name, workspace);
+
+        if self.zone_manager.set_kind(id, kind).is_err() {
+            return false;
+        }
+
+        if let Some(data) = self.zone_manager.active_data_mut(id) {
+            data.main_count = profile.main_count;
+            data.main_factor = profile.main_factor;
+            data.gap_size = profile.gap_size;
+            data.margin.left = profile.margin[0];
+            data.margin.right = profile.margin[1];
+            data.margin.top = profile.margin[2];
+            data.margin.bottom = profile.margin[3];
+        }
+
+        self.apply_layout(workspace);
+        self.apply_stack(workspace);
+
+        true
+    }
+
+    fn update_auto_layouts(&mut self) {
+        if self.config.kiosk {
+            return;
+        }
+
+        for workspace in 0..self.workspaces.len() {
+            let policy = match self
+                .config
+                .auto_layouts
+                .iter()
+                .find(|policy| policy.applies_to(workspace))
+            {
+                Some(policy) => policy,
+                None => continue,
+            };
+
+            let count = self.workspaces[workspace].len();
+            let prev = self.auto_layout_steps.borrow().get(&workspace).copied();
+
+            let step = match prev {
+                Some((prev_count, _)) if prev_count == count => continue,
+                // only step down once the count is clearly below the threshold;
+                // steps need not be ordered, so their thresholds are compared
+                Some((_, Some(prev_step)))
+                    if policy.step_for(count).map(|step| policy.steps[step].clients)
+                        < Some(policy.steps[prev_step].clients)
+                        && count + self.config.auto_layout_hysteresis
+                            >= policy.steps[prev_step].clients =>
+                {
+                    Some(prev_step)
+                },
+                _ => policy.step_for(count),
+            };
+
+            self.auto_layout_steps
+                .borrow_mut()
+                .insert(workspace, (count, step));
+
+            if step == prev.and_then(|(_, step)| step) {
+                continue;
+            }
+
+            let kind = match step.and_then(|step| LayoutKind::from_name(&policy.steps[step].layout)) {
+                Some(kind) => kind,
+                None => continue,
+            };
+
+            let root_zone = self.workspaces[workspace].root_zone();
+
+            if self.zone_manager.set_kind(root_zone, kind).is_ok() {
+                info!(
+                    "activating layout {:?} on workspace {} with {} clients",
+                    kind, workspace, count
+                );
+
+                self.apply_layout(workspace);
+                self.apply_stack(workspace);
+            }
+        }
+    }
+
+    fn update_orientation_layouts(&mut self) {
+        if self.config.kiosk {
+            return;
+        }
+
+        let portrait = self.active_screen().is_portrait();
+
+        if portrait == self.portrait.get() {
+            return;
+        }
+
+        info!(
+            "active screen changed to {} orientation",
+            if portrait { "portrait" } else { "landscape" }
+        );
+
+        self.portrait.set(portrait);
+
+        let kinds: Vec<(Index, LayoutKind)> = if portrait {
+            let kind = match self
+                .config
+                .portrait_layout
+                .as_deref()
+                .and_then(LayoutKind::from_name)
+            {
+                Some(kind) => kind,
+                None => return,
+            };
+
+            let mut landscape_kinds = self.landscape_kinds.borrow_mut();
+
+            self.workspaces
+                .iter()
+                .enumerate()
+                .map(|(index, workspace)| {
+                    if let Ok(prev_kind) = self.zone_manager.zone(workspace.root_zone()).kind() {
+                        landscape_kinds.insert(index, prev_kind);
+                    }
+
+                    (index, kind)
+                })
+                .collect()
+        } else {
+            let landscape_kinds = self.landscape_kinds.replace(HashMap::new());
+
+            match self
+                .config
+                .landscape_layout
+                .as_deref()
+                .and_then(LayoutKind::from_name)
+            {
+                Some(kind) => (0..self.workspaces.len()).map(|index| (index, kind)).collect(),
+                None => landscape_kinds.into_iter().collect(),
+            }
+        };
+
+        for (index, kind) in kinds {
+            let root_zone = self.workspaces[index].root_zone();
+            self.zone_manager.set_kind(root_zone, kind).ok();
+        }
+
+        let workspace = self.active_workspace();
+        self.apply_layout(workspace);
+        self.apply_stack(workspace);
+    }
+
+    pub fn mark_focus_zone(&self) {
+        let workspace = self.workspace(self.active_workspace());
+        let id = workspace
+            .active_focus_zone()
+            .map(|id| self.zone_manager.nearest_cycle(id));
+
+        info!("marking zone {:?} on workspace {}", id, workspace.number());
+        workspace.mark_zone(id);
+    }
+
+    #[inline]
+    pub fn set_layout(
+        &mut self,
+        kind: LayoutKind,
+    ) -> Result<(), StateChangeError> {
+        let workspace = self.active_workspace();
+
+        if let Some(id) = self.workspaces[workspace].active_focus_zone() {
+            info!("activating layout {:?} on workspace {}", kind, workspace);
+
+            self.zone_manager.set_kind(id, kind)?;
+            self.apply_layout(workspace);
+            self.apply_stack(workspace);
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn toggle_layout(&mut self) {
+        let workspace = self.active_workspace();
+
+        if let Some(id) = self.workspaces[workspace].active_focus_zone() {
+            let prev_kind = self.zone_manager.set_prev_kind(id);
+
+            info!(
+                "activating layout {:?} on workspace {}",
+                prev_kind, workspace
+            );
+
+            self.apply_layout(workspace);
+            self.apply_stack(workspace);
+        }
+    }
+
+    // falls back to the global cycle
+    fn layout_cycle(
+        &self,
+        workspace: Index,
+    ) -> Vec<LayoutKind> {
+        self.config
+            .workspace_layout_cycles
+            .iter()
+            .find(|(key, _)| self.workspace_by_key(key) == Some(workspace))
+            .map(|(_, cycle)| cycle)
+            .unwrap_or(&self.config.layout_cycle)
+            .iter()
+            .filter_map(|name| LayoutKind::from_name(name))
+            .collect()
+    }
+
+    pub fn cycle_layout(
+        &mut self,
+        dir: Direction,
+    ) {
+        let workspace = self.active_workspace();
+        let cycle = self.layout_cycle(workspace);
+
+        if cycle.is_empty() {
+            return;
+        }
+
+        let id = match self.workspaces[workspace].active_focus_zone() {
+            Some(id) => id,
+            None => return,
+        };
+
+        let current = self
+            .zone_manager
+            .zone(id)
+            .kind()
+            .ok()
+            .and_then(|kind| cycle.iter().position(|&k| k == kind));
+
+        // a layout outside of the cycle enters it at either end
+        let kind = cycle[match (current, dir) {
+            (Some(i), Direction::Forward) => (i + 1) % cycle.len(),
+            (Some(i), Direction::Backward) => (i + cycle.len() - 1) % cycle.len(),
+            (None, Direction::Forward) => 0,
+            (None, Direction::Backward) => cycle.len() - 1,
+        }];
+
+        info!("activating layout {:?} on workspace {}", kind, workspace);
+
+        if self.zone_manager.set_kind(id, kind).is_ok() {
+            self.apply_layout(workspace);
+            self.apply_stack(workspace);
+        }
+    }
+
+    // like monocle, but confined to the layout zone that holds the focused client
+    pub fn expand_in_zone(&mut self) {
+        let (window, zone, workspace) = match self.focused_client() {
+            Some(client) => (client.window(), client.zone(), client.workspace()),
+            None => return,
+        };
+
+        if self.zone_manager.toggle_expanded(zone) {
+            info!("expanding client with window {:#0x} within its zone", window);
+        } else {
+            info!("restoring zone of client with window {:#0x}", window);
+        }
+
+        self.apply_layout(workspace);
+        self.apply_stack(workspace);
+    }
+
+    // floats, shrinks, pins and raises the focused client in one go, or restores it
+    pub fn pip_focus(&mut self) {
+        let (window, frame) = match self.focused_client() {
+            Some(client) => client.windows(),
+            None => return,
+        };
+
+        let restore = self.pip_clients.borrow_mut().remove(&window);
+
+        if let Some((floating, sticky, free_region)) = restore {
+            info!("restoring client with window {:#0x} from picture-in-picture", window);
+
+            self.stack_manager.remove_window(frame);
+
+            let client = self.client_unchecked(window);
+            client.set_region(PlacementClass::Free(free_region));
+            self.set_stick_client(client, Toggle::from(sticky));
+            self.set_floating_client(client, Toggle::from(floating));
+
+            return;
+        }
+
+        info!("moving client with window {:#0x} to picture-in-picture", window);
+
+        self.stack_manager.add_window(frame, StackLayer::Above);
+
+        let client = self.client_unchecked(window);
+        self.unfullscreen(client);
+
+        self.pip_clients.borrow_mut().insert(
+            window,
+            (client.is_floating(), client.is_sticky(), client.free_region()),
+        );
+
+        let pip = self.config.picture_in_picture;
+        let screen_region = self.active_screen().placeable_region();
+
+        let dim = Dim {
+            w: pip.width.min(screen_region.dim.w - 2 * pip.margin),
+            h: pip.height.min(screen_region.dim.h - 2 * pip.margin),
+        };
+
+        let left = screen_region.pos.x + pip.margin;
+        let top = screen_region.pos.y + pip.margin;
+        let right = screen_region.pos.x + screen_region.dim.w - pip.margin - dim.w;
+        let bottom = screen_region.pos.y + screen_region.dim.h - pip.margin - dim.h;
+
+        let pos = match pip.corner {
+            Corner::TopLeft => Pos {
+                x: left,
+                y: top,
+            },
+            Corner::TopRight => Pos {
+                x: right,
+                y: top,
+            },
+            Corner::BottomLeft => Pos {
+                x: left,
+                y: bottom,
+            },
+            Corner::BottomRight => Pos {
+                x: right,
+                y: bottom,
+            },
+        };
+
+        client.set_region(PlacementClass::Free(Region {
+            pos,
+            dim,
+        }));
+
+        self.stick(client);
+        self.set_floating_client(client, Toggle::On);
+    }
+
+    #[inline(always)]
+    pub fn set_floating_focus(
+        &self,
+        toggle: Toggle,
+    ) {
+        if let Some(focus) = self.focus.get() {
+            self.set_floating_window(focus, toggle);
+        }
+    }
+
+    #[inline(always)]
+    pub fn set_floating_window(
+        &self,
+        window: Window,
+        toggle: Toggle,
     ) {
         if let Some(client) = self.client(window) {
             self.set_floating_client(client, toggle);
@@ -1521,9 +3658,27 @@ impl<'model> Model<'model> {
             client.window()
         );
 
+        let window = client.window();
         let workspace = client.workspace();
+        let was_floating = client.is_floating();
 
         client.set_floating(toggle);
+
+        // the client returns to the slot it occupied before it was floated
+        match (was_floating, client.is_floating()) {
+            (false, true) => {
+                let successor = self.workspaces[workspace].client_after(window);
+                self.float_slots.borrow_mut().insert(window, (workspace, successor));
+            },
+            (true, false) => match self.float_slots.borrow_mut().remove(&window) {
+                Some((index, successor)) if index == workspace => {
+                    self.workspaces[workspace].reinsert_client(window, successor);
+                },
+                _ => {},
+            },
+            _ => {},
+        }
+
         self.apply_layout(workspace);
         self.apply_stack(workspace);
     }
@@ -1581,9 +3736,42 @@ impl<'model> Model<'model> {
         self.apply_layout(workspace);
         self.apply_stack(workspace);
 
-        self.fullscreen_regions
-            .borrow_mut()
-            .insert(window, client.free_region());
+        self.fullscreen_regions.borrow_mut().insert(
+            window,
+            (client.free_region(), self.active_screen().full_region()),
+        );
+    }
+
+    // the region restored after fullscreen follows the client to the screen it is shown on
+    fn anchor_fullscreen_region(
+        &self,
+        window: Window,
+    ) {
+        let screen_region = self.active_screen().full_region();
+
+        if let Some((free_region, anchor)) = self.fullscreen_regions.borrow_mut().get_mut(&window) {
+            if *anchor != screen_region {
+                *free_region = free_region.transfer(*anchor, screen_region);
+                *anchor = screen_region;
+            }
+        }
+    }
+
+    // fullscreen clients on every workspace are stretched across the new screen
+    // geometry, and the regions they return to are carried over along with them
+    fn reconcile_fullscreen_regions(&self) {
+        let placeable_region = self.active_screen().placeable_region();
+        let windows: Vec<Window> = self.fullscreen_regions.borrow().keys().copied().collect();
+
+        for window in windows {
+            self.anchor_fullscreen_region(window);
+
+            if let Some(client) = self.client_any(window) {
+                if !client.is_contained() {
+                    client.set_region(PlacementClass::Tile(placeable_region));
+                }
+            }
+        }
     }
 
     #[inline(always)]
@@ -1599,8 +3787,10 @@ impl<'model> Model<'model> {
         let workspace = client.workspace();
         info!("disabling fullscreen for client with window {:#0x}", window);
 
-        if let Some(free_region) = self.fullscreen_regions.borrow().get(&window).cloned() {
-            client.set_region(PlacementClass::Free(free_region));
+        if let Some(&(free_region, screen_region)) = self.fullscreen_regions.borrow().get(&window) {
+            client.set_region(PlacementClass::Free(
+                free_region.transfer(screen_region, self.active_screen().full_region()),
+            ));
         }
 
         self.conn
@@ -1613,24 +3803,120 @@ impl<'model> Model<'model> {
         self.fullscreen_regions.borrow_mut().remove(&window);
     }
 
-    #[inline(always)]
-    pub fn set_contained_focus(
+    #[inline]
+    pub fn set_maximized_focus(
         &self,
+        state: WindowState,
         toggle: Toggle,
     ) {
-        if let Some(focus) = self.focus.get() {
-            self.set_contained_window(focus, toggle);
+        if let Some(client) = self.focus.get().and_then(|focus| self.client(focus)) {
+            self.set_maximized_client(client, state, toggle);
         }
     }
 
-    #[inline(always)]
-    pub fn set_contained_window(
+    fn is_maximized(
         &self,
-        window: Window,
-        toggle: Toggle,
-    ) {
-        if let Some(client) = self.client(window) {
-            self.set_contained_client(client, toggle);
+        client: &Client,
+        state: WindowState,
+    ) -> bool {
+        match (self.maximized_spans.borrow().get(&client.window()), state) {
+            (Some((horz, _)), WindowState::MaximizedHorz) => horz.is_some(),
+            (Some((_, vert)), WindowState::MaximizedVert) => vert.is_some(),
+            _ => false,
+        }
+    }
+
+    // stretches a free client across the placeable region along one axis, keeping
+    // the span it had before so that it can be restored
+    fn set_maximized_client(
+        &self,
+        client: &Client,
+        state: WindowState,
+        toggle: Toggle,
+    ) {
+        if !self.is_free(client) || client.is_fullscreen() {
+            return;
+        }
+
+        let window = client.window();
+        let maximize = toggle.eval(self.is_maximized(client, state));
+
+        if maximize == self.is_maximized(client, state) {
+            return;
+        }
+
+        info!(
+            "{}maximizing client with window {:#0x} ({:?})",
+            if maximize { "" } else { "un" },
+            window,
+            state
+        );
+
+        let screen_region = self.active_screen().placeable_region();
+        let mut region = client.free_region();
+
+        let mut maximized_spans = self.maximized_spans.borrow_mut();
+        let (horz, vert) = maximized_spans.entry(window).or_default();
+
+        let (span, pos, len, screen_pos, screen_len) = match state {
+            WindowState::MaximizedHorz => (
+                horz,
+                &mut region.pos.x,
+                &mut region.dim.w,
+                screen_region.pos.x,
+                screen_region.dim.w,
+            ),
+            WindowState::MaximizedVert => (
+                vert,
+                &mut region.pos.y,
+                &mut region.dim.h,
+                screen_region.pos.y,
+                screen_region.dim.h,
+            ),
+            _ => return,
+        };
+
+        if maximize {
+            *span = Some((*pos, *len));
+            *pos = screen_pos;
+            *len = screen_len;
+        } else if let Some((prev_pos, prev_len)) = span.take() {
+            *pos = prev_pos;
+            *len = prev_len;
+        }
+
+        if maximized_spans.get(&window) == Some(&(None, None)) {
+            maximized_spans.remove(&window);
+        }
+
+        drop(maximized_spans);
+
+        self.conn.set_window_state(window, state, maximize);
+        client.set_region(PlacementClass::Free(region));
+
+        let workspace = client.workspace();
+        self.apply_layout(workspace);
+        self.apply_stack(workspace);
+    }
+
+    #[inline(always)]
+    pub fn set_contained_focus(
+        &self,
+        toggle: Toggle,
+    ) {
+        if let Some(focus) = self.focus.get() {
+            self.set_contained_window(focus, toggle);
+        }
+    }
+
+    #[inline(always)]
+    pub fn set_contained_window(
+        &self,
+        window: Window,
+        toggle: Toggle,
+    ) {
+        if let Some(client) = self.client(window) {
+            self.set_contained_client(client, toggle);
         }
     }
 
@@ -1908,7 +4194,29 @@ impl<'model> Model<'model> {
         client.set_sticky(Toggle::On);
         self.render_decoration(client);
 
-        self.sticky_clients.borrow_mut().insert(window);
+        self.sticky_clients.borrow_mut().insert(
+            window,
+            self.screen_containing(client.free_region()).full_region(),
+        );
+    }
+
+    // sticky clients keep their position relative to the screen they were last shown on
+    fn anchor_sticky_clients(&self) {
+        let screen_region = self.active_screen().full_region();
+
+        for (window, anchor) in self.sticky_clients.borrow_mut().iter_mut() {
+            if *anchor == screen_region {
+                continue;
+            }
+
+            if let Some(client) = self.client_map.get(window) {
+                client.set_region(PlacementClass::Free(
+                    client.free_region().transfer(*anchor, screen_region),
+                ));
+            }
+
+            *anchor = screen_region;
+        }
     }
 
     #[inline(always)]
@@ -1976,6 +4284,7 @@ impl<'model> Model<'model> {
         if let Some(client) = self.client(window) {
             client.set_focused(Toggle::On);
             client.set_urgent(Toggle::Off);
+            self.publish_occupancy();
         }
 
         let id = client.zone();
@@ -1997,6 +4306,11 @@ impl<'model> Model<'model> {
         }
 
         self.focus.set(Some(window));
+        self.publish_title();
+
+        #[cfg(feature = "dbus")]
+        self.announce_focus();
+
         self.render_decoration(client);
         self.apply_stack(workspace);
     }
@@ -2022,6 +4336,14 @@ impl<'model> Model<'model> {
         client.set_warp_pos(self.conn.get_pointer_position());
         client.set_focused(Toggle::Off);
 
+        if self.pass_through.get() == Some(window) {
+            self.end_pass_through();
+        }
+
+        if self.confined.get() == Some(window) {
+            self.release_confined_pointer();
+        }
+
         self.conn.regrab_buttons(frame);
         self.render_decoration(client);
     }
@@ -2037,6 +4359,10 @@ impl<'model> Model<'model> {
             _ if workspace.is_empty() => {
                 self.conn.unfocus();
                 self.focus.set(None);
+                self.publish_title();
+
+                #[cfg(feature = "dbus")]
+                self.announce_focus();
             },
             _ => {},
         }
@@ -2117,6 +4443,96 @@ impl<'model> Model<'model> {
         self.focus_window(window);
     }
 
+    pub fn cycle_same_class(
+        &self,
+        dir: Direction,
+    ) {
+        let focus = match self.focused_client() {
+            Some(focus) => focus,
+            None => return,
+        };
+
+        let class = focus.class();
+        let mut clients: Vec<&Client> = self
+            .client_map
+            .values()
+            .filter(|&client| client.is_managed() && client.class() == class)
+            .collect();
+
+        if clients.len() < 2 {
+            return;
+        }
+
+        clients.sort_by_key(|client| Reverse(client.last_focused()));
+
+        // clients are visited from the most recently focused one on, in the order
+        // they had when the cycle started, for as long as the focus stays put
+        let mut clients: Vec<Window> = clients.iter().map(|client| client.window()).collect();
+
+        if let Some((cycled_to, order)) = self.class_cycle.borrow().as_ref() {
+            if *cycled_to == focus.window() {
+                clients.sort_by_key(|window| {
+                    order.iter().position(|ordered| ordered == window).unwrap_or(order.len())
+                });
+            }
+        }
+
+        let index = clients
+            .iter()
+            .position(|&window| window == focus.window())
+            .unwrap_or(0);
+
+        let window = clients[Util::next_index(clients.iter(), index, dir)];
+        self.class_cycle.replace(Some((window, clients)));
+
+        self.jumped_from.set(Some(focus.window()));
+
+        info!(
+            "cycling to client with window {:#0x} of class {}",
+            window, class
+        );
+
+        self.focus_window(window);
+    }
+
+    pub fn cycle_fullscreen(
+        &self,
+        dir: Direction,
+    ) {
+        let workspace = self.active_workspace();
+        let mut clients: Vec<&Client> = self
+            .client_map
+            .values()
+            .filter(|&client| {
+                client.is_managed()
+                    && client.workspace() == workspace
+                    && client.is_fullscreen()
+                    && !client.is_contained()
+                    && !client.is_iconified()
+            })
+            .collect();
+
+        if clients.is_empty() {
+            return;
+        }
+
+        clients.sort_by_key(|client| client.managed_since());
+
+        let window = match self
+            .focus
+            .get()
+            .and_then(|focus| clients.iter().position(|client| client.window() == focus))
+        {
+            Some(index) => clients[Util::next_index(clients.iter(), index, dir)].window(),
+            None => clients[0].window(),
+        };
+
+        info!("cycling to fullscreen client with window {:#0x}", window);
+
+        self.focus_window(window);
+        self.apply_stack(workspace);
+    }
+
     #[inline(always)]
     pub fn center_focus(&self) {
         if let Some(focus) = self.focus.get() {
@@ -2487,6 +4903,8 @@ impl<'model> Model<'model> {
             return;
         }
 
+        self.release_confined_pointer();
+
         if let Some(client) = self.client(window) {
             self.move_buffer.set(
                 client.window(),
@@ -2495,7 +4913,7 @@ impl<'model> Model<'model> {
                 client.free_region(),
             );
 
-            self.conn.confine_pointer(self.move_buffer.handle());
+            self.conn.confine_pointer(self.move_buffer.handle(), CursorShape::Move);
         }
     }
 
@@ -2503,6 +4921,7 @@ impl<'model> Model<'model> {
     pub fn stop_moving(&self) {
         if self.move_buffer.is_occupied() {
             self.conn.release_pointer();
+            self.commit_outline(self.move_buffer.window());
             self.move_buffer.unset();
         }
     }
@@ -2526,11 +4945,19 @@ impl<'model> Model<'model> {
             return;
         }
 
-        client.set_region(PlacementClass::Free(Region {
+        let region = Region {
             pos: self.move_buffer.window_region().unwrap().pos
                 + self.move_buffer.grip_pos().unwrap().dist(*pos),
             dim: client.free_region().dim,
-        }));
+        };
+
+        if self.config.outline_move_resize {
+            self.outline.set(Some(region));
+            self.conn.draw_outline(Some(region));
+            return;
+        }
+
+        client.set_region(PlacementClass::Free(region));
 
         let placement = Placement {
             method: PlacementMethod::Free,
@@ -2552,27 +4979,51 @@ impl<'model> Model<'model> {
             return;
         }
 
+        self.release_confined_pointer();
+
         if let Some(client) = self.client(window) {
             let pos = self.conn.get_pointer_position();
+            let grip = Grip::Corner(client.free_region().nearest_corner(pos));
 
-            self.resize_buffer.set(
-                client.window(),
-                Grip::Corner(client.free_region().nearest_corner(pos)),
-                pos,
-                client.free_region(),
-            );
-
-            self.conn.confine_pointer(self.resize_buffer.handle());
+            self.resize_buffer.set(client.window(), grip, pos, client.free_region());
+            self.conn.confine_pointer(self.resize_buffer.handle(), CursorShape::Resize(grip));
         }
     }
 
     pub fn stop_resizing(&self) {
         if self.resize_buffer.is_occupied() {
             self.conn.release_pointer();
+            self.commit_outline(self.resize_buffer.window());
             self.resize_buffer.unset();
         }
     }
 
+    // in outline mode, the client is only placed once the move or resize ends
+    fn commit_outline(
+        &self,
+        window: Option<Window>,
+    ) {
+        let region = match self.outline.take() {
+            Some(region) => region,
+            None => return,
+        };
+
+        self.conn.draw_outline(None);
+
+        if let Some(client) = window.and_then(|window| self.client(window)) {
+            let placement = Placement {
+                method: PlacementMethod::Free,
+                kind: PlacementTarget::Client(client.window()),
+                zone: client.zone(),
+                region: PlacementRegion::NewRegion(region),
+                decoration: client.decoration(),
+            };
+
+            self.update_client_placement(client, &placement);
+            self.place_client(client, placement.method);
+        }
+    }
+
     #[inline(always)]
     pub fn handle_resize(
         &self,
@@ -2630,6 +5081,12 @@ impl<'model> Model<'model> {
             region.pos.x = window_region.pos.x + (window_region.dim.w - region.dim.w);
         }
 
+        if self.config.outline_move_resize {
+            self.outline.set(Some(region));
+            self.conn.draw_outline(Some(region));
+            return;
+        }
+
         if region == client.previous_region() {
             return;
         }
@@ -2649,12 +5106,34 @@ impl<'model> Model<'model> {
     pub fn run(
         &mut self,
         mut key_bindings: KeyBindings,
+        repeatable_keys: RepeatableKeys,
         mut mouse_bindings: MouseBindings,
     ) {
         while self.running {
-            if let Some(event) = self.conn.step() {
+            let expired = self.timers.borrow_mut().expire();
+            for kind in expired {
+                self.handle_timer(kind);
+            }
+
+            self.handle_signals();
+
+            if !self.running {
+                break;
+            }
+
+            self.update_auto_layouts();
+            self.conn.flush();
+
+            if let Some(event) = self.conn.poll() {
                 trace!("received event: {:?}", event);
 
+                #[cfg(feature = "profiling")]
+                let _dispatch = tracing::info_span!("dispatch").entered();
+
+                if self.debug_hud.get() {
+                    self.record_event(format!("{:?}", event));
+                }
+
                 match event {
                     Event::Mouse {
                         event,
@@ -2662,7 +5141,7 @@ impl<'model> Model<'model> {
                     } => self.handle_mouse(event, on_root, &mut mouse_bindings),
                     Event::Key {
                         event,
-                    } => self.handle_key(event, &mut key_bindings),
+                    } => self.handle_key(event, &mut key_bindings, &repeatable_keys),
                     Event::MapRequest {
                         window,
                         ignore,
@@ -2698,14 +5177,17 @@ impl<'model> Model<'model> {
                     } => self.handle_configure(window, region, on_root),
                     Event::StateRequest {
                         window,
-                        state,
+                        states,
                         action,
                         on_root,
-                    } => self.handle_state_request(window, state, action, on_root),
+                    } => states.into_iter().for_each(|state| {
+                        self.handle_state_request(window, state, action, on_root)
+                    }),
                     Event::FocusRequest {
                         window,
+                        source,
                         on_root,
-                    } => self.handle_focus_request(window, on_root),
+                    } => self.handle_focus_request(window, source, on_root),
                     Event::CloseRequest {
                         window,
                         on_root,
@@ -2743,20 +5225,1068 @@ impl<'model> Model<'model> {
                         on_root,
                     } => self.handle_frame_extents_request(window, on_root),
                     Event::ScreenChange => self.handle_screen_change(),
+                    Event::ManagerReplaced => self.handle_manager_replaced(),
+                }
+
+                continue;
+            }
+
+            if self.debug_hud.get() {
+                self.render_debug_hud();
+            }
+
+            self.report_status();
+            self.wait();
+        }
+    }
+
+    pub fn toggle_debug_hud(&self) {
+        self.debug_hud.set(!self.debug_hud.get());
+        self.recent_events.borrow_mut().clear();
+
+        if !self.debug_hud.get() {
+            self.conn.draw_debug_hud(None);
+        }
+    }
+
+    fn record_event(
+        &self,
+        mut event: String,
+    ) {
+        let mut events = self.recent_events.borrow_mut();
+
+        if let Some((end, _)) = event.char_indices().nth(Self::EVENT_WIDTH) {
+            event.truncate(end);
+        }
+
+        if events.len() == Self::RECENT_EVENTS {
+            events.pop_front();
+        }
+
+        events.push_back(event);
+    }
+
+    fn render_debug_hud(&self) {
+        let window = |window: Option<Window>| {
+            window.map_or_else(|| "-".to_owned(), |window| format!("{:#0x}", window))
+        };
+
+        let workspace = self.workspace(self.active_workspace());
+
+        let mut zones = Vec::new();
+        let mut zone = workspace.active_focus_zone();
+
+        while let Some(id) = zone {
+            zones.push(id.to_string());
+            zone = self.zone_manager.parent_id(id);
+        }
+
+        zones.reverse();
+
+        let stack = self.stacking_order.borrow();
+        let mut lines = vec![
+            format!(
+                "focus     {} {}",
+                window(self.focus.get()),
+                self.focus
+                    .get()
+                    .and_then(|focus| self.client_any(focus))
+                    .map(Client::name)
+                    .unwrap_or_default(),
+            ),
+            format!("workspace {} ({})", workspace.number(), workspace.name()),
+            format!("zones     {}", zones.join(" > ")),
+            format!(
+                "stack     {}",
+                stack
+                    .iter()
+                    .rev()
+                    .map(|&window| format!("{:#0x}", window))
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            ),
+            format!(
+                "buffers   move {} resize {}",
+                window(self.move_buffer.window()),
+                window(self.resize_buffer.window()),
+            ),
+            "events".to_owned(),
+        ];
+
+        lines.extend(self.recent_events.borrow().iter().map(|event| format!("  {}", event)));
+
+        let pos = self.active_screen().placeable_region().pos;
+        self.conn.draw_debug_hud(Some((
+            Pos {
+                x: pos.x + 10,
+                y: pos.y + 10,
+            },
+            &lines,
+        )));
+    }
+
+    fn wait(&mut self) {
+        let timeout = self
+            .timers
+            .borrow()
+            .timeout()
+            .map_or(-1, |timeout| timeout.as_millis().min(i32::MAX as u128) as i32);
+
+        let mut fds = vec![PollFd::new(self.conn.fd(), PollFlags::POLLIN)];
+
+        if let Some(ipc) = &self.ipc {
+            fds.push(PollFd::new(ipc.fd(), PollFlags::POLLIN));
+        }
+
+        if let Some(fifo) = &self.fifo {
+            fds.push(PollFd::new(fifo.fd(), PollFlags::POLLIN));
+        }
+
+        if let Some(signals) = &self.signals {
+            fds.push(PollFd::new(signals.fd(), PollFlags::POLLIN));
+        }
+
+        #[cfg(feature = "dbus")]
+        if let Some(dbus) = &self.dbus {
+            fds.push(PollFd::new(dbus.fd(), PollFlags::POLLIN));
+        }
+
+        poll(&mut fds, timeout).ok();
+        self.handle_ipc();
+        self.handle_fifo();
+
+        #[cfg(feature = "dbus")]
+        self.handle_dbus();
+    }
+
+    fn handle_signals(&mut self) {
+        let signals = match self.signals.as_mut() {
+            Some(signals) => signals.pending(),
+            None => return,
+        };
+
+        for signal in signals {
+            debug!("received signal {}", signal);
+
+            match signal {
+                Signal::SIGCHLD => self.reap_children(),
+                Signal::SIGTERM | Signal::SIGINT => {
+                    self.exit();
+                    return;
+                },
+                Signal::SIGHUP | Signal::SIGUSR1 => self.reload_config(),
+                _ => {},
+            }
+        }
+    }
+
+    fn reap_children(&mut self) {
+        for (pid, status) in reap_children() {
+            debug!("reaped child {}: {:?}", pid, status);
+
+            self.pid_map.remove(&pid);
+            self.autostart.borrow_mut().exited(pid, status);
+
+            if self.locker.get() == Some(pid) {
+                self.unlock();
+            }
+        }
+    }
+
+    #[cfg_attr(feature = "profiling", tracing::instrument(skip_all))]
+    fn handle_ipc(&mut self) {
+        while let Some(request) = self.ipc.as_ref().and_then(|ipc| ipc.accept()) {
+            let reply = match &request.command {
+                _ if request.access == IpcAccess::Denied => Err(anyhow!("permission denied")),
+                Ok(command) if request.access == IpcAccess::ReadOnly && !command.is_read_only() => {
+                    Err(anyhow!("permission denied"))
+                },
+                Ok(IpcCommand::Subscribe) => {
+                    if let Some(ipc) = &self.ipc {
+                        ipc.subscribe(request);
+                    }
+
+                    continue;
+                },
+                Ok(command) => self.execute_ipc_command(command),
+                Err(err) => Err(anyhow!("{}", err)),
+            };
+
+            request.reply(reply);
+        }
+    }
+
+    // there is no one to reply to, so failures are only logged
+    fn handle_fifo(&mut self) {
+        let commands = match self.fifo.as_mut() {
+            Some(fifo) => fifo.pending(),
+            None => return,
+        };
+
+        for (access, command) in commands {
+            let result = match &command {
+                _ if access == IpcAccess::Denied => Err(anyhow!("permission denied")),
+                Ok(command) if access == IpcAccess::ReadOnly && !command.is_read_only() => {
+                    Err(anyhow!("permission denied"))
+                },
+                Ok(command) => self.execute_ipc_command(command).map(drop),
+                Err(err) => Err(anyhow!("{}", err)),
+            };
+
+            if let Err(err) = result {
+                warn!("could not run command from IPC FIFO: {}", err);
+            }
+        }
+    }
+
+    // every method is subject to the IPC tokens, the access is checked again here
+    #[cfg(feature = "dbus")]
+    fn handle_dbus(&mut self) {
+        let requests = match &self.dbus {
+            Some(dbus) => dbus.pending(),
+            None => return,
+        };
+
+        for request in requests {
+            let reply = if request.access.permits(&request.command) {
+                self.execute_ipc_command(&request.command)
+            } else {
+                Err(anyhow!("permission denied"))
+            };
+
+            request.reply(reply);
+        }
+    }
+
+    #[cfg(feature = "dbus")]
+    fn announce_focus(&self) {
+        if let Some(dbus) = &self.dbus {
+            let focus = self.focus.get();
+            let title = focus
+                .and_then(|focus| self.client_any(focus))
+                .map(Client::name)
+                .unwrap_or_default();
+
+            dbus.focus_changed(focus, &title);
+        }
+    }
+
+    // the span name, count, average and maximum time spent in microseconds per line
+    #[cfg(feature = "profiling")]
+    fn report_timings(
+        &self,
+        reset: bool,
+    ) -> winsys::Result<String> {
+        let profiler = self
+            .profiler
+            .as_ref()
+            .ok_or_else(|| anyhow!("profiler is not installed"))?;
+
+        let report = profiler.report();
+
+        if reset {
+            profiler.reset();
+        }
+
+        Ok(report)
+    }
+
+    #[cfg(not(feature = "profiling"))]
+    fn report_timings(
+        &self,
+        _reset: bool,
+    ) -> winsys::Result<String> {
+        Err(anyhow!("built without profiling support"))
+    }
+
+    fn execute_ipc_command(
+        &mut self,
+        command: &IpcCommand,
+    ) -> winsys::Result<String> {
+        debug!("IPC {:?}", command);
+
+        match *command {
+            IpcCommand::ReloadRules {
+                apply,
+                workspace,
+            } => {
+                self.reload_rules(apply, workspace);
+                Ok(format!("{} rules loaded", self.config.rules.len()))
+            },
+            IpcCommand::ApplyRules(ref sel) => {
+                self.apply_rules_window(self.select_window(sel)?, true);
+                Ok(String::new())
+            },
+            IpcCommand::AddRule(ref rule) => {
+                info!("adding rule {}", rule);
+                self.runtime_rules.push(rule.clone());
+                Ok((self.runtime_rules.len() - 1).to_string())
+            },
+            IpcCommand::ListRules => Ok(self
+                .runtime_rules
+                .iter()
+                .enumerate()
+                .map(|(index, rule)| format!("{} {}", index, rule))
+                .collect::<Vec<String>>()
+                .join("\n")),
+            IpcCommand::RemoveRule(index) => {
+                if index >= self.runtime_rules.len() {
+                    return Err(anyhow!("no rule {}", index));
+                }
+
+                info!("removing rule {}", self.runtime_rules.remove(index));
+                Ok(String::new())
+            },
+            IpcCommand::Focus(ref sel) => {
+                self.focus_window(self.select_window(sel)?);
+                Ok(String::new())
+            },
+            IpcCommand::Close(ref sel) => {
+                self.kill_window(self.select_window(sel)?);
+                Ok(String::new())
+            },
+            IpcCommand::ToggleFloat(ref sel) => {
+                self.set_floating_window(self.select_window(sel)?, Toggle::Reverse);
+                Ok(String::new())
+            },
+            IpcCommand::ToggleFullscreen(ref sel) => {
+                self.set_fullscreen_window(self.select_window(sel)?, Toggle::Reverse);
+                Ok(String::new())
+            },
+            IpcCommand::Flash(ref sel) => {
+                let window = self.select_window(sel)?;
+
+                if let Some(client) = self.client_any(window) {
+                    self.flash_client(client);
+                }
+
+                Ok(String::new())
+            },
+            IpcCommand::Workspace(workspace) => {
+                if workspace >= self.workspaces.len() {
+                    return Err(anyhow!("no workspace {}", workspace));
+                }
+
+                self.activate_workspace(workspace);
+                Ok(String::new())
+            },
+            IpcCommand::SetLayout(kind) => {
+                self.set_layout(kind).ok();
+                Ok(String::new())
+            },
+            IpcCommand::Spawn(ref command) => {
+                Util::spawn(command.as_str());
+                Ok(String::new())
+            },
+            IpcCommand::Run(ref name) => self.run_action(name),
+            IpcCommand::SendToWorkspace(workspace, ref sel) => {
+                if workspace >= self.workspaces.len() {
+                    return Err(anyhow!("no workspace {}", workspace));
+                }
+
+                self.move_window_to_workspace(self.select_window(sel)?, workspace);
+                Ok(String::new())
+            },
+            IpcCommand::Timings {
+                reset,
+            } => self.report_timings(reset),
+            IpcCommand::Action(action) => {
+                self.perform_action(action)?;
+                Ok(String::new())
+            },
+            IpcCommand::Lock => {
+                self.lock();
+                Ok(String::new())
+            },
+            IpcCommand::IgnoreStruts(window) => {
+                self.ignore_struts(window);
+                Ok(String::new())
+            },
+            IpcCommand::SaveLayout(ref name) => {
+                if self.save_layout_profile(name) {
+                    Ok(String::new())
+                } else {
+                    Err(anyhow!("no layout to save"))
+                }
+            },
+            IpcCommand::ApplyLayout(ref name, workspace) => {
+                let workspace = workspace.unwrap_or_else(|| self.active_workspace());
+
+                if workspace >= self.workspaces.len() {
+                    Err(anyhow!("no workspace {}", workspace))
+                } else if self.apply_layout_profile(name, workspace) {
+                    Ok(String::new())
+                } else {
+                    Err(anyhow!("could not apply layout profile {}", name))
+                }
+            },
+            IpcCommand::SetMargin(edge, value, workspace) => {
+                let workspace = workspace.unwrap_or_else(|| self.active_workspace());
+
+                if workspace >= self.workspaces.len() {
+                    Err(anyhow!("no workspace {}", workspace))
+                } else if self.set_workspace_margin(workspace, edge, value).is_ok() {
+                    Ok(String::new())
+                } else {
+                    Err(anyhow!("could not set margin on workspace {}", workspace))
+                }
+            },
+            IpcCommand::MarginPreset(ref name, workspace) => {
+                let workspace = workspace.unwrap_or_else(|| self.active_workspace());
+
+                if workspace >= self.workspaces.len() {
+                    Err(anyhow!("no workspace {}", workspace))
+                } else if self.apply_margin_preset(name, workspace) {
+                    Ok(String::new())
+                } else {
+                    Err(anyhow!("could not apply margin preset {}", name))
+                }
+            },
+            IpcCommand::Clients => Ok(self
+                .client_map
+                .values()
+                .filter(|client| client.is_managed())
+                .map(|client| {
+                    format!(
+                        "{:#0x} {} {} {} {}",
+                        client.window(),
+                        client.workspace(),
+                        client
+                            .monitor()
+                            .map_or_else(|| "-".to_owned(), |monitor| monitor.to_string()),
+                        client.class(),
+                        client.name(),
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join("\n")),
+            IpcCommand::Consumers => Ok(self
+                .client_map
+                .values()
+                .filter(|client| client.consumer_len() > 0)
+                .map(|client| {
+                    client.consumers().into_iter().fold(
+                        format!("{:#0x}", client.window()),
+                        |line, consumer| format!("{} {:#0x}", line, consumer),
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join("\n")),
+            IpcCommand::Consume(ref sel) => {
+                self.consume_focus(self.select_window(sel)?)?;
+                Ok(String::new())
+            },
+            IpcCommand::Unconsume => {
+                self.unconsume_focus()?;
+                Ok(String::new())
+            },
+            IpcCommand::Usage => {
+                let focus = self.focused_client().ok_or_else(|| anyhow!("no focused client"))?;
+                let pid = focus.pid();
+
+                // without periodic sampling, the CPU share is not known
+                let usage = match self.usage.borrow().usage() {
+                    Some(usage) if Some(usage.pid) == pid => Some(usage),
+                    _ => None,
+                };
+
+                let usage = usage
+                    .or_else(|| self.sample_usage())
+                    .ok_or_else(|| anyhow!("no process usage known for focused client"))?;
+
+                Ok(format!(
+                    "{:#0x} {} {} {}",
+                    focus.window(),
+                    usage.pid,
+                    usage.cpu.map_or_else(|| "-".to_owned(), |cpu| format!("{:.1}", cpu)),
+                    usage.rss,
+                ))
+            },
+            IpcCommand::Version => Ok(format!(
+                "{} {}",
+                env!("CARGO_PKG_NAME"),
+                env!("CARGO_PKG_VERSION")
+            )),
+            IpcCommand::ConfigError => Ok(self.config.error.clone().unwrap_or_default()),
+            IpcCommand::Subscribe => Err(anyhow!("subscribing requires a socket connection")),
+            IpcCommand::Features => {
+                let features = [
+                    ("core", cfg!(feature = "core")),
+                    ("bar", cfg!(feature = "bar")),
+                    ("client", cfg!(feature = "client")),
+                    ("ctl", cfg!(feature = "ctl")),
+                    ("scripting", cfg!(feature = "scripting")),
+                    ("dbus", cfg!(feature = "dbus")),
+                    ("profiling", cfg!(feature = "profiling")),
+                ];
+
+                // one `feature` or `command` entry per line
+                Ok(features
+                    .iter()
+                    .filter(|&&(_, enabled)| enabled)
+                    .map(|(name, _)| format!("feature {}", name))
+                    .chain(
+                        IpcCommand::NAMES
+                            .iter()
+                            .chain(Action::NAMES)
+                            .map(|name| format!("command {}", name)),
+                    )
+                    .collect::<Vec<String>>()
+                    .join("\n"))
+            },
+        }
+    }
+
+    pub fn run_action(
+        &mut self,
+        name: &str,
+    ) -> winsys::Result<String> {
+        let lines = self
+            .config
+            .actions
+            .get(name)
+            .ok_or_else(|| anyhow!("unknown action {}", name))?;
+
+        // all commands are parsed before any of them runs
+        let commands = lines
+            .iter()
+            .flat_map(|line| line.split(';'))
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| match IpcCommand::parse(line)? {
+                IpcCommand::Run(_) => Err(anyhow!("actions cannot run other actions")),
+                command => Ok(command),
+            })
+            .collect::<winsys::Result<Vec<IpcCommand>>>()?;
+
+        info!("running action {}", name);
+
+        // the workspace is arranged once, after the last command has run
+        self.deferred_arrangement.set(Some((false, false)));
+
+        let result = commands
+            .iter()
+            .try_for_each(|command| self.execute_ipc_command(command).map(drop));
+
+        if let Some((layout, stack)) = self.deferred_arrangement.take() {
+            let workspace = self.active_workspace();
+
+            if layout {
+                self.apply_layout(workspace);
+            }
+
+            if stack {
+                self.apply_stack(workspace);
+            }
+        }
+
+        result.map(|_| String::new())
+    }
+
+    // the state changes that do not go through are reported as errors
+    pub fn perform_action(
+        &mut self,
+        action: Action,
+    ) -> winsys::Result<()> {
+        match action {
+            Action::CycleFocus(dir) => self.cycle_focus(dir),
+            Action::DragFocus(dir) => self.drag_focus(dir),
+            Action::RotateClients(dir) => self.rotate_clients(dir),
+            Action::CycleSameClass(dir) => self.cycle_same_class(dir),
+            Action::CycleFullscreen(dir) => self.cycle_fullscreen(dir),
+            Action::CycleZones(dir) => self.cycle_zones(dir),
+            Action::ToggleZone => self.toggle_zone(),
+            Action::CreateLayoutZone => self.create_layout_zone(),
+            Action::CreateTabZone => self.create_tab_zone(),
+            Action::DeleteZone => self.delete_zone(),
+            Action::MarkZone => self.mark_focus_zone(),
+            Action::ExpandInZone => self.expand_in_zone(),
+            Action::CycleLayout(dir) => self.cycle_layout(dir),
+            Action::ToggleLayout => self.toggle_layout(),
+            Action::NextWorkspace(dir) => self.activate_next_workspace(dir),
+            Action::WorkspaceTowards(edge) => self.activate_workspace_towards(edge),
+            Action::ToggleWorkspace => self.toggle_workspace(),
+            Action::WorkspaceBack => self.workspace_back(),
+            Action::MoveToNextWorkspace(dir) => self.move_focus_to_next_workspace(dir),
+            Action::ChangeGapSize(change) => self.change_gap_size(change)?,
+            Action::ResetGapSize => self.reset_gap_size()?,
+            Action::ChangeMainCount(change) => self.change_main_count(change)?,
+            Action::ChangeMainFactor(change) => self.change_main_factor(change)?,
+            Action::ChangeMargin(edge, change) => self.change_margin(edge, change)?,
+            Action::ResetMargin => self.reset_margin()?,
+            Action::CopyPrevLayoutData => self.copy_prev_layout_data()?,
+            Action::ResetLayoutData => self.reset_layout_data()?,
+            Action::SetFloating(toggle) => self.set_floating_focus(toggle),
+            Action::SetFullscreen(toggle) => self.set_fullscreen_focus(toggle),
+            Action::SetMaximized(state, toggle) => self.set_maximized_focus(state, toggle),
+            Action::SetContained(toggle) => self.set_contained_focus(toggle),
+            Action::SetInvincible(toggle) => self.set_invincible_focus(toggle),
+            Action::SetProducing(toggle) => self.set_producing_focus(toggle),
+            Action::SetIconifyable(toggle) => self.set_iconifyable_focus(toggle),
+            Action::SetIconify(toggle) => self.set_iconify_focus(toggle),
+            Action::SetStick(toggle) => self.set_stick_focus(toggle),
+            Action::PopDeiconify => self.pop_deiconify(),
+            Action::DeiconifyAll => self.deiconify_all(self.active_workspace()),
+            Action::Center => self.center_focus(),
+            Action::Snap(edge) => self.snap_focus(edge),
+            Action::Nudge(edge, step) => self.nudge_focus(edge, step),
+            Action::Stretch(edge, step) => self.stretch_focus(edge, step),
+            Action::GrowRatio(step) => self.grow_ratio_focus(step),
+            Action::Pip => self.pip_focus(),
+            Action::Pause => self.pause_focus(),
+            Action::Resume => self.resume_focus(),
+            Action::Teleport => self.teleport_focus(),
+            Action::Hint => self.hint_focus(),
+            Action::TogglePassThrough => self.toggle_pass_through(),
+            Action::ConfinePointer => self.confine_pointer_focus(),
+            Action::ToggleScreenStruts => self.toggle_screen_struts(),
+            Action::ToggleDebugHud => self.toggle_debug_hud(),
+            Action::ReloadConfig => self.reload_config(),
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "scripting")]
+    fn run_script_binding(
+        &mut self,
+        input: &KeyInput,
+    ) -> bool {
+        let commands = match &self.script {
+            Some(script) if script.is_bound(input) => {
+                debug!("processing script binding: {:?}", input);
+
+                script.call(
+                    input,
+                    self.active_workspace(),
+                    self.focused_client().map(Client::class).unwrap_or_default(),
+                )
+            },
+            _ => return false,
+        };
+
+        match commands {
+            Ok(commands) => {
+                for command in &commands {
+                    if let Err(err) = self.execute_ipc_command(command) {
+                        warn!("script command {:?} failed: {}", command, err);
+                    }
+                }
+            },
+            Err(err) => warn!("{}", err),
+        }
+
+        true
+    }
+
+    fn select_window(
+        &self,
+        sel: &WindowSelector,
+    ) -> winsys::Result<Window> {
+        sel.resolve(
+            &self.client_map,
+            &self.workspaces,
+            &self.zone_manager,
+            self.focus.get(),
+        )
+        .ok_or_else(|| anyhow!("no client matches {:?}", sel))
+    }
+
+    // the defaults (or, on reload, the running configuration) stay in effect
+    fn report_config_error(&self) {
+        if let Some(error) = &self.config.error {
+            Util::notify(concat!(WM_NAME!(), ": configuration error"), error);
+        }
+    }
+
+    pub fn reload_config(&mut self) {
+        info!("reloading configuration");
+
+        let mut config = Config::load_from(&self.config.path);
+
+        if config.error.is_some() {
+            self.config.error = config.error;
+            self.report_config_error();
+            return;
+        }
+
+        self.options.apply(&mut config);
+
+        let decoration_strategy = self.config.decoration_strategy;
+        let kiosk = self.config.kiosk;
+        self.config = config;
+
+        // the bindings are restricted to those of kiosk mode once, at startup
+        if self.config.kiosk != kiosk {
+            warn!("kiosk mode changes take effect after a restart");
+            self.config.kiosk = kiosk;
+        }
+
+        // frames of managed clients are created according to the strategy
+        if self.config.decoration_strategy != decoration_strategy {
+            warn!("decoration strategy changes take effect after a restart");
+            self.config.decoration_strategy = decoration_strategy;
+        }
+
+        self.auto_layout_steps.borrow_mut().clear();
+        self.status.replace(StatusReporter::open(&self.config.status));
+        self.repeatable_bindings.replace(self.config.repeatable_keys());
+        self.binding_throttles.replace(self.config.binding_throttles());
+        self.binding_invocations.borrow_mut().clear();
+        self.set_layout_defaults();
+        self.publish_desktop_layout();
+        self.schedule_audit();
+        self.schedule_usage();
+        self.load_theme();
+
+        self.partitions
+            .iter()
+            .for_each(|partition| self.configure_screen(partition.screen()));
+        self.update_workarea();
+
+        // re-placing clients applies changed decoration presets and corner radii
+        let workspace = self.active_workspace();
+        self.apply_layout(workspace);
+        self.apply_stack(workspace);
+
+        self.restore_grabs();
+    }
+
+    fn grab_bindings(&self) {
+        self.conn.grab_bindings(
+            &self.bound_keys.iter().collect::<Vec<&KeyInput>>(),
+            &self.bound_buttons.iter().collect::<Vec<&MouseInput>>(),
+        );
+    }
+
+    fn restore_grabs(&self) {
+        // the locker holds the keyboard until it exits
+        if self.locker.get().is_some() {
+            return;
+        }
+
+        self.conn.ungrab_bindings();
+
+        if self.pass_through.get().is_some() {
+            self.conn.grab_bindings(&[&self.pass_through_key], &[]);
+        } else {
+            self.grab_bindings();
+        }
+    }
+
+    pub fn toggle_pass_through(&self) {
+        if self.pass_through.get().is_some() {
+            self.end_pass_through();
+            return;
+        }
+
+        if let Some(focus) = self.focus.get() {
+            info!("passing keys through to window {:#0x}", focus);
+
+            self.pass_through.set(Some(focus));
+            self.key_repeat.set(None);
+            self.held_keys.borrow_mut().clear();
+            self.timers.borrow_mut().cancel(TimerKind::KeyRepeat);
+            self.restore_grabs();
+        }
+    }
+
+    fn end_pass_through(&self) {
+        if let Some(window) = self.pass_through.take() {
+            info!("no longer passing keys through to window {:#0x}", window);
+            self.restore_grabs();
+        }
+    }
+
+    // the pointer cannot leave the frame of the focused client until the binding
+    // is pressed again, the client loses focus or is grabbed with the mouse
+    pub fn confine_pointer_focus(&self) {
+        if self.confined.get().is_some() {
+            self.release_confined_pointer();
+            return;
+        }
+
+        if self.move_buffer.is_occupied() || self.resize_buffer.is_occupied() {
+            return;
+        }
+
+        if let Some(client) = self.focus.get().and_then(|focus| self.client(focus)) {
+            let (window, frame) = client.windows();
+
+            // a pointer outside of the frame is first moved onto its center
+            if self.conn.confine_pointer_to(frame) {
+                info!("confining pointer to window {:#0x}", window);
+                self.confined.set(Some(window));
+            } else {
+                warn!("could not confine pointer to window {:#0x}", window);
+            }
+        }
+    }
+
+    fn release_confined_pointer(&self) {
+        if let Some(window) = self.confined.take() {
+            info!("releasing pointer from window {:#0x}", window);
+            self.conn.unconfine_pointer();
+        }
+    }
+
+    pub fn lock(&self) {
+        if self.locker.get().is_some() {
+            return;
+        }
+
+        let locker = match &self.config.locker {
+            Some(locker) => locker,
+            None => {
+                warn!("no locker configured");
+                return;
+            },
+        };
+
+        info!("locking session with {}", locker);
+
+        // grabs are released first, so that the locker can grab the keyboard itself
+        self.key_repeat.set(None);
+        self.held_keys.borrow_mut().clear();
+        self.timers.borrow_mut().cancel(TimerKind::KeyRepeat);
+        self.conn.ungrab_bindings();
+
+        match Util::spawn_child(locker) {
+            Some(pid) => self.locker.set(Some(pid)),
+            None => self.restore_grabs(),
+        }
+    }
+
+    fn unlock(&self) {
+        info!("locker exited, restoring bindings");
+
+        self.locker.set(None);
+        self.restore_grabs();
+    }
+
+    fn publish_desktop_layout(&self) {
+        let (columns, rows) = self.config.desktop_layout.grid(self.workspaces.len());
+
+        self.conn.set_desktop_layout(
+            self.config.desktop_layout.orientation == DesktopOrientation::Vertical,
+            columns,
+            rows,
+        );
+    }
+
+    fn schedule_audit(&self) {
+        if self.config.audit_interval > 0 {
+            self.timers.borrow_mut().schedule_repeating(
+                TimerKind::Audit,
+                Duration::from_secs(self.config.audit_interval),
+            );
+        } else {
+            self.timers.borrow_mut().cancel(TimerKind::Audit);
+        }
+    }
+
+    fn schedule_usage(&self) {
+        if self.config.usage_interval > 0 {
+            self.timers.borrow_mut().schedule_repeating(
+                TimerKind::Usage,
+                Duration::from_secs(self.config.usage_interval),
+            );
+        } else {
+            self.timers.borrow_mut().cancel(TimerKind::Usage);
+        }
+    }
+
+    fn sample_usage(&self) -> Option<Usage> {
+        let pid = self.focused_client().and_then(Client::pid);
+        self.usage.borrow_mut().sample(pid)
+    }
+
+    pub fn reload_rules(
+        &mut self,
+        apply: bool,
+        workspace: bool,
+    ) {
+        info!("reloading rules");
+        let config = Config::load_from(&self.config.path);
+
+        if config.error.is_some() {
+            self.config.error = config.error;
+            self.report_config_error();
+            return;
+        }
+
+        self.config.rules = config.rules;
+
+        if apply {
+            let windows: Vec<Window> = self
+                .client_map
+                .values()
+                .filter(|client| client.is_managed())
+                .map(|client| client.window())
+                .collect();
+
+            for window in windows {
+                self.apply_rules_window(window, workspace);
+            }
+        }
+    }
+
+    #[inline(always)]
+    pub fn apply_rules_focus(&self) {
+        if let Some(focus) = self.focus.get() {
+            self.apply_rules_window(focus, true);
+        }
+    }
+
+    #[inline(always)]
+    pub fn apply_rules_window(
+        &self,
+        window: Window,
+        with_workspace: bool,
+    ) {
+        if let Some(client) = self.client(window) {
+            self.apply_rules_client(client, with_workspace);
+        }
+    }
+
+    fn apply_rules_client(
+        &self,
+        client: &Client,
+        with_workspace: bool,
+    ) {
+        info!("applying rules to client with window {:#0x}", client.window());
+
+        let rules = self.detect_rules(&client.name(), &client.class(), &client.instance());
+
+        if let Some(float) = rules.float {
+            if float != client.is_floating() {
+                self.set_floating_client(client, Toggle::from(float));
+            }
+        }
+
+        if let Some(fullscreen) = rules.fullscreen {
+            if fullscreen != client.is_fullscreen() {
+                self.set_fullscreen_client(client, Toggle::from(fullscreen));
+            }
+        }
+
+        if rules.center() {
+            self.center_client(client);
+        }
+
+        if with_workspace {
+            if let Some(workspace) = rules.workspace {
+                if workspace != client.workspace() {
+                    self.move_client_to_workspace(client, workspace);
                 }
             }
+        }
+    }
+
+    #[cfg_attr(feature = "profiling", tracing::instrument(skip_all))]
+    fn handle_timer(
+        &mut self,
+        kind: TimerKind,
+    ) {
+        debug!("TIMER {:?}", kind);
+
+        match kind {
+            TimerKind::Audit => self.audit(),
+            TimerKind::KeyRepeat => self.repeat_key(),
+            TimerKind::Autostart => self.autostart.borrow_mut().supervise(),
+            TimerKind::Usage => {
+                self.sample_usage();
+            },
+            TimerKind::Flash => self.flash_step(),
+        }
+    }
+
+    fn repeat_key(&mut self) {
+        if let Some((key, action, count)) = self.key_repeat.get() {
+            action(self);
+
+            let interval = (self.config.key_repeat_interval as f32
+                * self.config.key_repeat_acceleration.powi(count as i32))
+                as u64;
+
+            self.key_repeat.set(Some((key, action, count + 1)));
+            self.timers.borrow_mut().schedule(
+                TimerKind::KeyRepeat,
+                Duration::from_millis(interval.max(self.config.key_repeat_min_interval)),
+            );
+        }
+    }
+
+    fn audit(&mut self) {
+        let orphaned_frames: Vec<(Window, Window)> = self
+            .frame_map
+            .iter()
+            .filter(|&(_, window)| self.conn.get_window_geometry(*window).is_err())
+            .map(|(&frame, &window)| (frame, window))
+            .collect();
+
+        for (frame, window) in orphaned_frames {
+            warn!(
+                "audit: frame {:#0x} holds destroyed window {:#0x}",
+                frame, window
+            );
+
+            if self.client_map.contains_key(&window) {
+                self.handle_destroy(window);
+            } else {
+                self.conn.destroy_window(frame);
+                self.retired.borrow_mut().retire(frame);
+                self.frame_map.remove(&frame);
+                self.window_map.remove(&window);
+            }
+        }
 
-            self.conn.flush();
+        let destroyed_windows: Vec<Window> = self
+            .unmanaged_windows
+            .borrow()
+            .iter()
+            .filter(|&&window| self.conn.get_window_geometry(window).is_err())
+            .copied()
+            .collect();
+
+        for window in destroyed_windows {
+            warn!("audit: unmanaged window {:#0x} no longer exists", window);
+            self.handle_destroy(window);
         }
     }
 
     #[inline(always)]
+    #[cfg_attr(feature = "profiling", tracing::instrument(skip_all))]
     fn handle_mouse(
         &mut self,
         event: MouseEvent,
         on_root: bool,
         mouse_bindings: &mut MouseBindings,
     ) {
+        if !self.hints.borrow().is_empty() {
+            if event.kind == MouseEventKind::Press {
+                self.finish_hinting(None);
+            }
+
+            return;
+        }
+
+        if self.teleporting.get().is_some() {
+            if event.kind == MouseEventKind::Press {
+                let screen = self
+                    .partitions
+                    .iter()
+                    .map(|partition| partition.screen())
+                    .find(|screen| screen.full_encompasses(event.root_rpos));
+
+                self.finish_teleport(
+                    screen.map(|screen| TeleportTarget::Screen(screen.placeable_region())),
+                );
+            }
+
+            return;
+        }
+
         let mut input = event.input;
         let window = event.window;
 
@@ -2836,19 +6366,125 @@ impl<'model> Model<'model> {
         }
     }
 
+    // a throttled binding runs at most once per interval, other bindings skip
+    // their autorepeated presses
+    fn admit_key_press(
+        &self,
+        input: &KeyInput,
+        autorepeat: bool,
+    ) -> bool {
+        let interval = match self.binding_throttles.borrow().get(input) {
+            Some(&interval) => interval,
+            None => return !autorepeat,
+        };
+
+        let now = Instant::now();
+        let mut invocations = self.binding_invocations.borrow_mut();
+
+        if let Some(&last) = invocations.get(input) {
+            if now.duration_since(last) < interval {
+                return false;
+            }
+        }
+
+        invocations.insert(input.clone(), now);
+        true
+    }
+
     #[inline(always)]
+    #[cfg_attr(feature = "profiling", tracing::instrument(skip_all))]
     fn handle_key(
         &mut self,
         event: KeyEvent,
         key_bindings: &mut KeyBindings,
+        repeatable_keys: &RepeatableKeys,
     ) {
-        if let Some(action) = key_bindings.get_mut(&event.input) {
+        let repeating = self.key_repeat.get().map(|(key, ..)| key);
+
+        if event.kind == KeyEventKind::Release {
+            self.held_keys.borrow_mut().remove(&event.input.key);
+
+            if repeating == Some(event.input.key) {
+                self.key_repeat.set(None);
+                self.timers.borrow_mut().cancel(TimerKind::KeyRepeat);
+            }
+
+            return;
+        }
+
+        // a press of a key that has not been released since is an autorepeat
+        let held = !self.held_keys.borrow_mut().insert(event.input.key);
+        let autorepeat = held && self.config.detect_autorepeat;
+
+        if repeating == Some(event.input.key) {
+            return;
+        }
+
+        if !self.hints.borrow().is_empty() {
+            self.handle_hint_key(event.input.key);
+            return;
+        }
+
+        if self.teleporting.get().is_some() {
+            self.finish_teleport(match event.input.key {
+                Key::Shift
+                | Key::Control
+                | Key::Alt
+                | Key::Super
+                | Key::LeftShift
+                | Key::RightShift
+                | Key::LeftControl
+                | Key::RightContol
+                | Key::LeftAlt
+                | Key::RightAlt
+                | Key::LeftSuper
+                | Key::RightSuper => return,
+                Key::One => Some(0),
+                Key::Two => Some(1),
+                Key::Three => Some(2),
+                Key::Four => Some(3),
+                Key::Five => Some(4),
+                Key::Six => Some(5),
+                Key::Seven => Some(6),
+                Key::Eight => Some(7),
+                Key::Nine => Some(8),
+                Key::Zero => Some(9),
+                _ => None,
+            }
+            .map(TeleportTarget::Workspace));
+
+            return;
+        }
+
+        if !self.admit_key_press(&event.input, autorepeat) {
+            return;
+        }
+
+        #[cfg(feature = "scripting")]
+        if !self.config.kiosk && self.run_script_binding(&event.input) {
+            return;
+        }
+
+        if let Some(&mut action) = key_bindings.get_mut(&event.input) {
             debug!("processing key binding: {:?}", event.input);
             action(self);
+
+            // a throttled binding is not repeated, its throttle would be bypassed
+            if (repeatable_keys.contains(&event.input)
+                || self.repeatable_bindings.borrow().contains(&event.input))
+                && !self.binding_throttles.borrow().contains_key(&event.input)
+            {
+                self.key_repeat.set(Some((event.input.key, action, 0)));
+                self.timers.borrow_mut().schedule(
+                    TimerKind::KeyRepeat,
+                    Duration::from_millis(self.config.key_repeat_delay),
+                );
+            }
         }
     }
 
     #[inline(always)]
+    #[cfg_attr(feature = "profiling", tracing::instrument(skip_all))]
     fn handle_map_request(
         &mut self,
         window: Window,
@@ -2856,19 +6492,19 @@ impl<'model> Model<'model> {
     ) {
         debug!("MAP_REQUEST for window {:#0x}", window);
 
-        let workspace = self.active_workspace();
-
         if ignore {
             if let Some(struts) = self.conn.get_window_strut(window) {
-                let screen = self.active_screen();
-                screen.add_struts(struts);
+                self.add_struts(struts);
 
-                if !screen.showing_struts() {
+                if self
+                    .partitions
+                    .iter()
+                    .map(|partition| partition.screen())
+                    .any(|screen| screen.has_strut_window(window) && !screen.showing_struts())
+                {
                     self.conn.unmap_window(window);
                 } else {
-                    screen.compute_placeable_region();
-                    self.apply_layout(workspace);
-                    self.apply_stack(workspace);
+                    self.update_placeable_regions();
                 }
             }
 
@@ -2881,12 +6517,15 @@ impl<'model> Model<'model> {
                 (_, WindowType::Desktop) => Some(StackLayer::Desktop),
                 (_, WindowType::Dock) => {
                     if let Ok(geometry) = geometry {
-                        let screen = self.active_screen();
+                        let screen = self.screen_containing(geometry);
                         let full_region = screen.full_region();
 
                         if !screen.contains_window(window) {
                             let strut = match (
-                                (geometry.pos.x, geometry.pos.y),
+                                (
+                                    geometry.pos.x - full_region.pos.x,
+                                    geometry.pos.y - full_region.pos.y,
+                                ),
                                 (geometry.dim.w, geometry.dim.h),
                             ) {
                                 ((0, 0), (w, h)) if w == full_region.dim.w => Some((Edge::Top, h)),
@@ -2903,14 +6542,12 @@ impl<'model> Model<'model> {
                             };
 
                             if let Some((edge, width)) = strut {
-                                screen.add_strut(edge, window, width as u32);
+                                self.add_strut(screen, edge, window, width as u32);
 
                                 if !screen.showing_struts() {
                                     self.conn.unmap_window(window);
                                 } else {
-                                    screen.compute_placeable_region();
-                                    self.apply_layout(workspace);
-                                    self.apply_stack(workspace);
+                                    self.update_screen_placeable_region(screen);
                                 }
                             }
                         }
@@ -2936,6 +6573,7 @@ impl<'model> Model<'model> {
     }
 
     #[inline]
+    #[cfg_attr(feature = "profiling", tracing::instrument(skip_all))]
     fn handle_map(
         &self,
         window: Window,
@@ -2945,6 +6583,7 @@ impl<'model> Model<'model> {
     }
 
     #[inline]
+    #[cfg_attr(feature = "profiling", tracing::instrument(skip_all))]
     fn handle_enter(
         &self,
         window: Window,
@@ -2953,6 +6592,10 @@ impl<'model> Model<'model> {
     ) {
         debug!("ENTER for window {:#0x}", window);
 
+        if self.locker.get().is_some() {
+            return;
+        }
+
         if let Some(client) = self.client(window) {
             if let Some(focus) = self.focus.get() {
                 if client.window() != focus {
@@ -2967,6 +6610,7 @@ impl<'model> Model<'model> {
     }
 
     #[inline]
+    #[cfg_attr(feature = "profiling", tracing::instrument(skip_all))]
     fn handle_leave(
         &self,
         window: Window,
@@ -2974,32 +6618,36 @@ impl<'model> Model<'model> {
         _window_rpos: Pos,
     ) {
         debug!("LEAVE for window {:#0x}", window);
-        self.unfocus_window(window);
+
+        if self.locker.get().is_none() {
+            self.unfocus_window(window);
+        }
     }
 
     #[inline]
+    #[cfg_attr(feature = "profiling", tracing::instrument(skip_all))]
     fn handle_destroy(
         &mut self,
         window: Window,
     ) {
         debug!("DESTROY for window {:#0x}", window);
 
-        let screen = self.active_screen();
-
-        if screen.has_strut_window(window) {
-            screen.remove_window_strut(window);
-            screen.compute_placeable_region();
+        // the frame was destroyed along with its client, which is gone already
+        if self.retired.borrow_mut().bury(window) {
+            return;
+        }
 
-            let workspace = self.active_workspace();
-            self.apply_layout(workspace);
-            self.apply_stack(workspace);
+        if self.remove_struts(window) {
+            self.update_placeable_region();
         }
 
+        self.ignored_struts.borrow_mut().remove(&window);
         self.unmanaged_windows.borrow_mut().remove(&window);
         self.remove_window(window);
     }
 
     #[inline]
+    #[cfg_attr(feature = "profiling", tracing::instrument(skip_all))]
     fn handle_expose(
         &self,
         _window: Window,
@@ -3007,6 +6655,7 @@ impl<'model> Model<'model> {
     }
 
     #[inline]
+    #[cfg_attr(feature = "profiling", tracing::instrument(skip_all))]
     fn handle_unmap(
         &mut self,
         window: Window,
@@ -3014,27 +6663,40 @@ impl<'model> Model<'model> {
     ) {
         debug!("UNMAP for window {:#0x}", window);
 
-        if self.unmanaged_windows.borrow().contains(&window) {
-            return;
-        }
+        let unmap = self.retired.borrow().classify_unmap(
+            window,
+            self.unmanaged_windows.borrow().contains(&window),
+            self.client_any(window),
+        );
 
-        self.handle_destroy(window);
+        if unmap == Unmap::Withdrawn {
+            self.handle_destroy(window);
+        }
     }
 
     #[inline]
+    #[cfg_attr(feature = "profiling", tracing::instrument(skip_all))]
     fn handle_configure(
         &mut self,
         window: Window,
-        _region: Region,
+        region: Region,
         on_root: bool,
     ) {
         if on_root {
             debug!("CONFIGURE for window {:#0x}", window);
             self.acquire_partitions();
+            return;
+        }
+
+        if let Some(client) = self.client_map.get(&window) {
+            if client.frame() != window {
+                self.shape_frame(client, region);
+            }
         }
     }
 
     #[inline]
+    #[cfg_attr(feature = "profiling", tracing::instrument(skip_all))]
     fn handle_state_request(
         &self,
         window: Window,
@@ -3055,24 +6717,18 @@ impl<'model> Model<'model> {
         match action {
             ToggleAction::Add => match state {
                 WindowState::Fullscreen => self.fullscreen(client),
-                WindowState::Sticky => self.stick(client),
-                WindowState::DemandsAttention => {
-                    self.conn.set_icccm_window_hints(window, Hints {
-                        urgent: true,
-                        input: None,
-                        initial_state: None,
-                        group: None,
-                    });
-
-                    if let Some(client) = self.client_any(window) {
-                        client.set_urgent(Toggle::On);
-                        self.render_decoration(client);
-                    }
+                WindowState::MaximizedHorz | WindowState::MaximizedVert => {
+                    self.set_maximized_client(client, state, Toggle::On);
                 },
+                WindowState::Sticky => self.stick(client),
+                WindowState::DemandsAttention => self.demand_attention(client),
                 _ => {},
             },
             ToggleAction::Remove => match state {
                 WindowState::Fullscreen => self.unfullscreen(client),
+                WindowState::MaximizedHorz | WindowState::MaximizedVert => {
+                    self.set_maximized_client(client, state, Toggle::Off);
+                },
                 WindowState::Sticky => self.unstick(client),
                 WindowState::DemandsAttention => {
                     self.conn.set_icccm_window_hints(window, Hints {
@@ -3085,17 +6741,23 @@ impl<'model> Model<'model> {
                     if let Some(client) = self.client_any(window) {
                         client.set_urgent(Toggle::Off);
                         self.render_decoration(client);
+                        self.publish_occupancy();
                     }
                 },
                 _ => {},
             },
             ToggleAction::Toggle => {
-                let is_fullscreen = client.is_fullscreen();
+                let is_set = match state {
+                    WindowState::MaximizedHorz | WindowState::MaximizedVert => {
+                        self.is_maximized(client, state)
+                    },
+                    _ => client.is_fullscreen(),
+                };
 
                 self.handle_state_request(
                     window,
                     state,
-                    if is_fullscreen {
+                    if is_set {
                         ToggleAction::Remove
                     } else {
                         ToggleAction::Add
@@ -3107,19 +6769,105 @@ impl<'model> Model<'model> {
     }
 
     #[inline]
+    #[cfg_attr(feature = "profiling", tracing::instrument(skip_all))]
     fn handle_focus_request(
         &self,
         window: Window,
+        source: RequestSource,
         on_root: bool,
     ) {
-        debug!("FOCUS_REQUEST for window {:#0x}", window);
+        debug!("FOCUS_REQUEST for window {:#0x} from {:?}", window, source);
 
-        if !on_root {
-            self.focus_window(window);
+        let client = match self.client_any(window) {
+            Some(client) if !on_root => client,
+            _ => return,
+        };
+
+        let policy = match source {
+            RequestSource::Pager => self.config.pager_activation,
+            _ => self.config.activation,
+        };
+
+        match policy {
+            ActivationPolicy::Always => self.focus_window(window),
+            ActivationPolicy::Smart if client.workspace() == self.active_workspace() => {
+                self.focus_window(window);
+            },
+            ActivationPolicy::Smart | ActivationPolicy::Urgent => {
+                if Some(client.window()) != self.focus.get() {
+                    self.demand_attention(client);
+                }
+            },
+            ActivationPolicy::Ignore => {},
+        }
+    }
+
+    fn demand_attention(
+        &self,
+        client: &Client,
+    ) {
+        self.conn.set_icccm_window_hints(client.window(), Hints {
+            urgent: true,
+            input: None,
+            initial_state: None,
+            group: None,
+        });
+
+        client.set_urgent(Toggle::On);
+        self.render_decoration(client);
+        self.publish_occupancy();
+
+        if self.config.flash.on_urgent {
+            self.flash_client(client);
+        }
+    }
+
+    // draws attention to a client by repeatedly inverting its decoration colors
+    pub fn flash_client(
+        &self,
+        client: &Client,
+    ) {
+        let toggles = 2 * self.config.flash.count;
+
+        if toggles == 0 {
+            return;
+        }
+
+        self.flashing.borrow_mut().insert(client.window(), toggles);
+
+        let mut timers = self.timers.borrow_mut();
+
+        if !timers.is_scheduled(TimerKind::Flash) {
+            timers.schedule_repeating(
+                TimerKind::Flash,
+                Duration::from_millis(self.config.flash.interval.max(1)),
+            );
+        }
+    }
+
+    fn flash_step(&self) {
+        let mut flashing = self.flashing.borrow_mut();
+
+        flashing.retain(|&window, toggles| {
+            let client = match self.client_any(window) {
+                Some(client) => client,
+                None => return false,
+            };
+
+            *toggles -= 1;
+            client.set_flashing(Toggle::from(*toggles % 2 == 1));
+            self.render_decoration(client);
+
+            *toggles > 0
+        });
+
+        if flashing.is_empty() {
+            self.timers.borrow_mut().cancel(TimerKind::Flash);
         }
     }
 
     #[inline]
+    #[cfg_attr(feature = "profiling", tracing::instrument(skip_all))]
     fn handle_close_request(
         &self,
         window: Window,
@@ -3127,12 +6875,13 @@ impl<'model> Model<'model> {
     ) {
         debug!("CLOSE_REQUEST for window {:#0x}", window);
 
-        if !on_root {
+        if !on_root && !self.config.kiosk {
             self.conn.kill_window(window);
         }
     }
 
     #[inline]
+    #[cfg_attr(feature = "profiling", tracing::instrument(skip_all))]
     fn handle_workspace_request(
         &self,
         window: Option<Window>,
@@ -3145,12 +6894,13 @@ impl<'model> Model<'model> {
             window.map(|window| format!("{:#0x}", window))
         );
 
-        if on_root {
+        if on_root && !self.config.kiosk {
             self.activate_workspace(index);
         }
     }
 
     #[inline]
+    #[cfg_attr(feature = "profiling", tracing::instrument(skip_all))]
     fn handle_placement_request(
         &self,
         window: Window,
@@ -3235,6 +6985,7 @@ impl<'model> Model<'model> {
     }
 
     #[inline]
+    #[cfg_attr(feature = "profiling", tracing::instrument(skip_all))]
     fn handle_grip_request(
         &self,
         window: Window,
@@ -3248,8 +6999,9 @@ impl<'model> Model<'model> {
         );
 
         if let Some(grip) = grip {
-            self.move_buffer.unset();
-            self.resize_buffer.unset();
+            self.stop_moving();
+            self.stop_resizing();
+            self.release_confined_pointer();
 
             if let Some(client) = self.client(window) {
                 self.resize_buffer.set(
@@ -3259,7 +7011,7 @@ impl<'model> Model<'model> {
                     client.free_region(),
                 );
 
-                self.conn.confine_pointer(self.resize_buffer.handle());
+                self.conn.confine_pointer(self.resize_buffer.handle(), CursorShape::Resize(grip));
             }
         } else {
             self.start_moving(window);
@@ -3267,6 +7019,7 @@ impl<'model> Model<'model> {
     }
 
     #[inline]
+    #[cfg_attr(feature = "profiling", tracing::instrument(skip_all))]
     fn handle_restack_request(
         &mut self,
         window: Window,
@@ -3288,6 +7041,7 @@ impl<'model> Model<'model> {
     }
 
     #[inline]
+    #[cfg_attr(feature = "profiling", tracing::instrument(skip_all))]
     fn handle_property(
         &self,
         window: Window,
@@ -3300,12 +7054,36 @@ impl<'model> Model<'model> {
             PropertyKind::Name => {
                 if let Some(client) = self.client_any(window) {
                     client.set_name(self.conn.get_icccm_window_name(window));
+
+                    if Some(client.window()) == self.focus.get() {
+                        self.publish_title();
+                    }
                 }
             },
             PropertyKind::Class => {
                 if let Some(client) = self.client_any(window) {
-                    client.set_class(self.conn.get_icccm_window_class(window));
-                    client.set_instance(self.conn.get_icccm_window_instance(window));
+                    let class = self.conn.get_icccm_window_class(window);
+                    let instance = self.conn.get_icccm_window_instance(window);
+
+                    if class == client.class() && instance == client.instance() {
+                        return;
+                    }
+
+                    info!(
+                        "client with window {:#0x} changed class to {} ({})",
+                        window, class, instance
+                    );
+
+                    client.set_class(class);
+                    client.set_instance(instance);
+
+                    if client.is_managed() {
+                        if self.config.rerun_rules_on_class_change {
+                            self.apply_rules_client(client, true);
+                        }
+
+                        self.learn_workspace(client);
+                    }
                 }
             },
             PropertyKind::Size => {
@@ -3343,22 +7121,25 @@ impl<'model> Model<'model> {
                     }
                 }
             },
+            PropertyKind::OpaqueRegion => {
+                if let Some(client) = self.client_any(window) {
+                    client.set_opaque_region(self.conn.get_window_opaque_region(client.window()));
+                    self.render_decoration(client);
+                }
+            },
             PropertyKind::Strut => {
                 if let Some(struts) = self.conn.get_window_strut(window) {
-                    let screen = self.active_screen();
-                    screen.remove_window_strut(window);
-                    screen.add_struts(struts);
-                    screen.compute_placeable_region();
-
-                    let workspace = self.active_workspace();
-                    self.apply_layout(workspace);
-                    self.apply_stack(workspace);
+                    self.remove_struts(window);
+                    self.add_struts(struts);
+                    self.update_placeable_regions();
                 }
             },
+            PropertyKind::Resources => self.load_theme(),
         }
     }
 
     #[inline]
+    #[cfg_attr(feature = "profiling", tracing::instrument(skip_all))]
     fn handle_frame_extents_request(
         &self,
         window: Window,
@@ -3371,18 +7152,37 @@ impl<'model> Model<'model> {
             if let Some(client) = self.client_any(window) {
                 client.frame_extents()
             } else {
-                Decoration::NO_DECORATION.extents()
+                self.estimate_decoration(window).extents()
             },
         );
     }
 
     #[cold]
+    #[cfg_attr(feature = "profiling", tracing::instrument(skip_all))]
     fn handle_screen_change(&mut self) {
         debug!("SCREEN_CHANGE");
 
         self.acquire_partitions();
         self.workspaces
             .activate_for(&Selector::AtIndex(self.active_screen().number()));
+
+        self.update_workarea();
+        self.update_orientation_layouts();
+
+        self.anchor_sticky_clients();
+        self.reconcile_fullscreen_regions();
+        self.apply_layout(self.active_workspace());
+
+        self.client_map.values().for_each(|client| {
+            self.update_client_monitor(client);
+        });
+    }
+
+    #[cold]
+    #[cfg_attr(feature = "profiling", tracing::instrument(skip_all))]
+    fn handle_manager_replaced(&mut self) {
+        info!("another window manager acquired the manager selection");
+        self.exit();
     }
 
     #[cold]
@@ -3393,6 +7193,12 @@ impl<'model> Model<'model> {
             self.deiconify_all(workspace);
         });
 
+        for window in self.paused.borrow().clone() {
+            if let Some(client) = self.client_any(window) {
+                self.resume_client(client);
+            }
+        }
+
         self.client_map.iter().for_each(|(&window, client)| {
             self.conn.unparent_window(window, client.free_region().pos);
         });
@@ -0,0 +1,118 @@
+use crate::config::Config;
+
+use simplelog::LevelFilter;
+
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+use clap::value_t;
+use clap::App;
+use clap::Arg;
+use clap::Result;
+
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    pub config: Option<PathBuf>,
+    pub replace: bool,
+    pub log_level: Option<LevelFilter>,
+    pub no_autostart: bool,
+    pub kiosk: bool,
+    pub screen: Option<usize>,
+    pub print_default_config: bool,
+}
+
+impl Options {
+    fn app() -> App<'static, 'static> {
+        App::new(WM_NAME!())
+            .bin_name(WM_NAME!())
+            .version(env!("CARGO_PKG_VERSION"))
+            .about(env!("CARGO_PKG_DESCRIPTION").trim())
+            .arg(
+                Arg::with_name("config")
+                    .short("c")
+                    .long("config")
+                    .value_name("path")
+                    .help("Reads the configuration from <path>"),
+            )
+            .arg(
+                Arg::with_name("log-level")
+                    .short("l")
+                    .long("log-level")
+                    .value_name("level")
+                    .possible_values(&["off", "error", "warn", "info", "debug", "trace"])
+                    .case_insensitive(true)
+                    .help("Sets the level of messages logged to stderr"),
+            )
+            .arg(
+                Arg::with_name("replace")
+                    .long("replace")
+                    .help("Replaces the running window manager"),
+            )
+            .arg(
+                Arg::with_name("no-autostart")
+                    .long("no-autostart")
+                    .help("Skips the autostart entries and scripts"),
+            )
+            .arg(
+                Arg::with_name("kiosk")
+                    .long("kiosk")
+                    .help("Only allows the bindings needed to leave the session"),
+            )
+            .arg(
+                Arg::with_name("screen")
+                    .long("screen")
+                    .value_name("number")
+                    .help("Manages screen <number> of the display instead of that in DISPLAY"),
+            )
+            .arg(
+                Arg::with_name("print-default-config")
+                    .long("print-default-config")
+                    .help("Prints a commented default configuration"),
+            )
+    }
+
+    // exits on --help, --version or invalid arguments
+    pub fn parse() -> Self {
+        Self::parse_from(std::env::args_os()).unwrap_or_else(|err| err.exit())
+    }
+
+    pub fn parse_from<I, T>(args: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let matches = Self::app().get_matches_from_safe(args)?;
+
+        Ok(Self {
+            config: matches.value_of("config").map(PathBuf::from),
+            replace: matches.is_present("replace"),
+            log_level: if matches.is_present("log-level") {
+                Some(value_t!(matches, "log-level", LevelFilter)?)
+            } else {
+                None
+            },
+            no_autostart: matches.is_present("no-autostart"),
+            kiosk: matches.is_present("kiosk"),
+            screen: if matches.is_present("screen") {
+                Some(value_t!(matches, "screen", usize)?)
+            } else {
+                None
+            },
+            print_default_config: matches.is_present("print-default-config"),
+        })
+    }
+
+    // the command line takes precedence over the configuration, also when it is
+    // reloaded
+    pub fn apply(
+        &self,
+        config: &mut Config,
+    ) {
+        config.kiosk |= self.kiosk;
+
+        if self.no_autostart {
+            config.startup_scripts = false;
+            config.autostart.clear();
+        }
+    }
+}
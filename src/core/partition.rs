@@ -32,6 +32,11 @@ impl Partition {
         self.index
     }
 
+    #[inline]
+    pub fn name(&self) -> String {
+        self.screen.name()
+    }
+
     #[inline]
     pub fn full_region(&self) -> Region {
         self.screen.full_region()
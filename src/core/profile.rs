@@ -1 +1,120 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
 
+use tracing::span;
+use tracing::Subscriber;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::layer::Layer;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Registry;
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Timing {
+    count: u32,
+    total: Duration,
+    max: Duration,
+}
+
+type Timings = Arc<Mutex<HashMap<&'static str, Timing>>>;
+
+// the time spent inside a span is accumulated under the name of the span
+struct TimingLayer {
+    timings: Timings,
+}
+
+impl<S> Layer<S> for TimingLayer
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    fn on_enter(
+        &self,
+        id: &span::Id,
+        ctx: Context<'_, S>,
+    ) {
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().replace(Instant::now());
+        }
+    }
+
+    fn on_exit(
+        &self,
+        id: &span::Id,
+        ctx: Context<'_, S>,
+    ) {
+        let span = match ctx.span(id) {
+            Some(span) => span,
+            None => return,
+        };
+
+        let elapsed = match span.extensions_mut().remove::<Instant>() {
+            Some(entered) => entered.elapsed(),
+            None => return,
+        };
+
+        if let Ok(mut timings) = self.timings.lock() {
+            let timing = timings.entry(span.name()).or_default();
+            timing.count += 1;
+            timing.total += elapsed;
+            timing.max = timing.max.max(elapsed);
+        }
+    }
+}
+
+pub struct Profiler {
+    timings: Timings,
+}
+
+impl Profiler {
+    pub fn install() -> Option<Self> {
+        let timings = Timings::default();
+        let subscriber = Registry::default().with(TimingLayer {
+            timings: timings.clone(),
+        });
+
+        match tracing::subscriber::set_global_default(subscriber) {
+            Ok(()) => Some(Self {
+                timings,
+            }),
+            Err(err) => {
+                warn!("unable to install profiler: {}", err);
+                None
+            },
+        }
+    }
+
+    // one line per span, in microseconds, the most expensive in total first
+    pub fn report(&self) -> String {
+        let timings = match self.timings.lock() {
+            Ok(timings) => timings,
+            Err(_) => return String::new(),
+        };
+
+        let mut timings: Vec<(&&str, &Timing)> = timings.iter().collect();
+        timings.sort_by_key(|(_, timing)| Reverse(timing.total));
+
+        timings
+            .iter()
+            .map(|(name, timing)| {
+                format!(
+                    "{} {} {} {}",
+                    name,
+                    timing.count,
+                    timing.total.as_micros() / u128::from(timing.count.max(1)),
+                    timing.max.as_micros(),
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    pub fn reset(&self) {
+        if let Ok(mut timings) = self.timings.lock() {
+            timings.clear();
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use crate::client::Client;
+use crate::util::BuildIdHasher;
+
+use winsys::window::Window;
+
+use std::collections::HashMap;
+
+// the server may hand out the id of a destroyed window again, but only after it
+// has reported the destruction; events that arrive for a window destroyed by the
+// window manager itself before its DestroyNotify are therefore stale, even if
+// the id has since been given to a new window
+// what an UnmapNotify amounts to; unmaps the window manager caused itself are
+// consumed here, before the window is taken for withdrawn, so that removing a
+// client never depends on whether an unmap of it was still expected
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Unmap {
+    Stale,
+    Expected,
+    Withdrawn,
+}
+
+#[derive(Debug, Default)]
+pub struct RetiredWindows {
+    pending: HashMap<Window, u32, BuildIdHasher>,
+}
+
+impl RetiredWindows {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn retire(
+        &mut self,
+        window: Window,
+    ) {
+        *self.pending.entry(window).or_insert(0) += 1;
+    }
+
+    #[inline]
+    pub fn is_retired(
+        &self,
+        window: Window,
+    ) -> bool {
+        self.pending.contains_key(&window)
+    }
+
+    pub fn classify_unmap(
+        &self,
+        window: Window,
+        unmanaged: bool,
+        client: Option<&Client>,
+    ) -> Unmap {
+        if unmanaged || self.is_retired(window) {
+            return Unmap::Stale;
+        }
+
+        match client {
+            Some(client) if client.consume_unmap_if_expecting() => Unmap::Expected,
+            _ => Unmap::Withdrawn,
+        }
+    }
+
+    // true if the DestroyNotify was that of a retired window
+    pub fn bury(
+        &mut self,
+        window: Window,
+    ) -> bool {
+        match self.pending.get_mut(&window) {
+            Some(count) if *count > 1 => {
+                *count -= 1;
+                true
+            },
+            Some(_) => {
+                self.pending.remove(&window);
+                true
+            },
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use winsys::window::WindowType;
+
+    #[test]
+    fn events_before_destroy_notify_are_stale() {
+        let mut retired = RetiredWindows::new();
+
+        retired.retire(0x400001);
+
+        assert!(retired.is_retired(0x400001));
+        assert!(!retired.is_retired(0x400002));
+        assert!(retired.bury(0x400001));
+        assert!(!retired.is_retired(0x400001));
+        assert!(!retired.bury(0x400001));
+    }
+
+    #[test]
+    fn reused_id_is_live_once_all_destructions_are_reported() {
+        let mut retired = RetiredWindows::new();
+
+        // the id is destroyed, reused and destroyed again before the first
+        // DestroyNotify is handled
+        retired.retire(0x400001);
+        retired.retire(0x400001);
+
+        assert!(retired.bury(0x400001));
+        assert!(retired.is_retired(0x400001));
+        assert!(retired.bury(0x400001));
+        assert!(!retired.is_retired(0x400001));
+
+        // the DestroyNotify of the window that now holds the id is genuine
+        assert!(!retired.bury(0x400001));
+    }
+
+    fn client(window: Window) -> Client {
+        Client::new(
+            0,
+            window,
+            window + 1,
+            "name",
+            "class",
+            "instance",
+            WindowType::Normal,
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn expected_unmaps_are_consumed_before_withdrawal() {
+        let retired = RetiredWindows::new();
+        let client = client(0x400001);
+
+        client.expect_unmap();
+        client.expect_unmap();
+
+        assert_eq!(retired.classify_unmap(0x400001, false, Some(&client)), Unmap::Expected);
+        assert_eq!(retired.classify_unmap(0x400001, false, Some(&client)), Unmap::Expected);
+        assert!(!client.is_expecting_unmap());
+        assert_eq!(retired.classify_unmap(0x400001, false, Some(&client)), Unmap::Withdrawn);
+        assert_eq!(retired.classify_unmap(0x400001, false, None), Unmap::Withdrawn);
+    }
+
+    #[test]
+    fn closed_frame_does_not_shadow_reused_id() {
+        let mut retired = RetiredWindows::new();
+        let closed = client(0x400001);
+
+        // the client is closed while an unmap of it was still expected, its frame
+        // is destroyed by the window manager
+        closed.expect_unmap();
+        retired.retire(closed.frame());
+
+        assert_eq!(retired.classify_unmap(closed.frame(), false, None), Unmap::Stale);
+        assert!(retired.bury(closed.frame()));
+
+        // a new window is handed the id of the frame and is withdrawn in turn
+        let opened = client(closed.frame());
+
+        assert_eq!(retired.classify_unmap(opened.window(), false, Some(&opened)), Unmap::Withdrawn);
+        assert_eq!(retired.classify_unmap(opened.window(), true, Some(&opened)), Unmap::Stale);
+    }
+}
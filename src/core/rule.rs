@@ -1,6 +1,15 @@
 use crate::change::Toggle;
 use crate::client::Client;
 
+use winsys::Result;
+
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::anyhow;
+
+use serde::Deserialize;
+
 #[derive(Debug)]
 pub struct Rules {
     pub float: Option<bool>,
@@ -8,6 +17,7 @@ pub struct Rules {
     pub fullscreen: Option<bool>,
     pub workspace: Option<usize>,
     pub context: Option<usize>,
+    pub dedicated: Option<bool>,
 }
 
 impl Rules {
@@ -43,6 +53,10 @@ impl Rules {
     pub fn fullscreen(&self) -> bool {
         self.fullscreen.map_or(false, |fullscreen| fullscreen)
     }
+
+    pub fn dedicated(&self) -> bool {
+        self.dedicated == Some(true)
+    }
 }
 
 impl Default for Rules {
@@ -53,6 +67,165 @@ impl Default for Rules {
             fullscreen: None,
             workspace: None,
             context: None,
+            dedicated: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Rule {
+    pub name: Option<String>,
+    pub class: Option<String>,
+    pub instance: Option<String>,
+    pub float: Option<bool>,
+    pub center: Option<bool>,
+    pub fullscreen: Option<bool>,
+    pub workspace: Option<usize>,
+    pub dedicated: Option<bool>,
+}
+
+impl Rule {
+    // fields are given as in the configuration, e.g. class=mpv float=true
+    pub fn parse<'a>(fields: impl Iterator<Item = &'a str>) -> Result<Self> {
+        fn value<T: FromStr>(
+            key: &str,
+            value: &str,
+        ) -> Result<Option<T>> {
+            value
+                .parse()
+                .map(Some)
+                .map_err(|_| anyhow!("invalid value {} for {}", value, key))
+        }
+
+        let mut rule = Self::default();
+        let mut empty = true;
+
+        for field in fields {
+            let (key, val) = match field.find('=') {
+                Some(i) => (&field[..i], &field[i + 1..]),
+                None => return Err(anyhow!("field {} lacks a value", field)),
+            };
+
+            match key {
+                "name" => rule.name = Some(val.to_owned()),
+                "class" => rule.class = Some(val.to_owned()),
+                "instance" => rule.instance = Some(val.to_owned()),
+                "float" => rule.float = value(key, val)?,
+                "center" => rule.center = value(key, val)?,
+                "fullscreen" => rule.fullscreen = value(key, val)?,
+                "workspace" => rule.workspace = value(key, val)?,
+                "dedicated" => rule.dedicated = value(key, val)?,
+                _ => return Err(anyhow!("unknown field {}", key)),
+            }
+
+            empty = false;
+        }
+
+        if empty {
+            return Err(anyhow!("missing rule fields"));
+        }
+
+        Ok(rule)
+    }
+
+    pub fn matches(
+        &self,
+        name: &str,
+        class: &str,
+        instance: &str,
+    ) -> bool {
+        fn field_matches(
+            field: &Option<String>,
+            value: &str,
+        ) -> bool {
+            match field {
+                Some(field) => field == value,
+                None => true,
+            }
         }
+
+        field_matches(&self.name, name)
+            && field_matches(&self.class, class)
+            && field_matches(&self.instance, instance)
+    }
+
+    pub fn apply(
+        &self,
+        rules: &mut Rules,
+    ) {
+        if self.float.is_some() {
+            rules.float = self.float;
+        }
+
+        if self.center.is_some() {
+            rules.center = self.center;
+        }
+
+        if self.fullscreen.is_some() {
+            rules.fullscreen = self.fullscreen;
+        }
+
+        if self.workspace.is_some() {
+            rules.workspace = self.workspace;
+        }
+
+        if self.dedicated.is_some() {
+            rules.dedicated = self.dedicated;
+        }
+    }
+}
+
+// the fields that are set, in the form parsed by Rule::parse
+impl fmt::Display for Rule {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        let strings = [
+            ("name", &self.name),
+            ("class", &self.class),
+            ("instance", &self.instance),
+        ];
+
+        let flags = [
+            ("float", self.float),
+            ("center", self.center),
+            ("fullscreen", self.fullscreen),
+            ("dedicated", self.dedicated),
+        ];
+
+        let fields: Vec<String> = strings
+            .iter()
+            .filter_map(|(key, value)| value.as_ref().map(|value| format!("{}={}", key, value)))
+            .chain(
+                flags
+                    .iter()
+                    .filter_map(|(key, value)| value.map(|value| format!("{}={}", key, value))),
+            )
+            .chain(self.workspace.map(|workspace| format!("workspace={}", workspace)))
+            .collect();
+
+        write!(f, "{}", fields.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsed_rule_displays_as_its_fields() {
+        let rule = Rule::parse("class=mpv float=true workspace=5".split(' ')).unwrap();
+
+        assert_eq!(rule.class.as_deref(), Some("mpv"));
+        assert_eq!(rule.float, Some(true));
+        assert_eq!(rule.workspace, Some(5));
+        assert_eq!(rule.to_string(), "class=mpv float=true workspace=5");
+        assert_eq!(Rule::parse(rule.to_string().split(' ')).unwrap(), rule);
+
+        assert!(Rule::parse(std::iter::empty()).is_err());
+        assert!(Rule::parse("float=maybe".split(' ')).is_err());
+        assert!(Rule::parse("opacity=0.5".split(' ')).is_err());
     }
 }
@@ -0,0 +1,126 @@
+use crate::client::Client;
+use crate::cycle::Cycle;
+use crate::identify::Index;
+use crate::util::BuildIdHasher;
+use crate::workspace::ClientSelector;
+use crate::workspace::Workspace;
+use crate::zone::ZoneManager;
+
+use winsys::window::Window;
+use winsys::Result;
+
+use std::collections::HashMap;
+
+use anyhow::anyhow;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WindowSelector {
+    Focused,
+    Last,
+    Newest,
+    Oldest,
+    Name(String),
+    Class(String),
+    Instance(String),
+    OnWorkspace(Index, WorkspacePosition),
+    Window(Window),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkspacePosition {
+    First,
+    Last,
+    Focused,
+    Master,
+}
+
+impl WindowSelector {
+    pub fn parse(selector: &str) -> Result<Self> {
+        match selector {
+            "focused" => return Ok(Self::Focused),
+            "last" => return Ok(Self::Last),
+            "newest" => return Ok(Self::Newest),
+            "oldest" => return Ok(Self::Oldest),
+            _ => {},
+        }
+
+        if let Some(hex) = selector.strip_prefix("0x") {
+            return Window::from_str_radix(hex, 16)
+                .map(Self::Window)
+                .map_err(|err| anyhow!("invalid window {}: {}", selector, err));
+        }
+
+        match selector.split_once(':') {
+            Some(("name", name)) => Ok(Self::Name(name.to_owned())),
+            Some(("class", class)) => Ok(Self::Class(class.to_owned())),
+            Some(("instance", instance)) => Ok(Self::Instance(instance.to_owned())),
+            Some(("ws", workspace)) => {
+                let (index, position) = match workspace.split_once('.') {
+                    Some((index, position)) => (index, position),
+                    None => (workspace, "focused"),
+                };
+
+                let index = index
+                    .parse()
+                    .map_err(|err| anyhow!("invalid workspace {}: {}", index, err))?;
+
+                let position = match position {
+                    "first" => WorkspacePosition::First,
+                    "last" => WorkspacePosition::Last,
+                    "focused" => WorkspacePosition::Focused,
+                    "master" => WorkspacePosition::Master,
+                    _ => return Err(anyhow!("unknown workspace position {}", position)),
+                };
+
+                Ok(Self::OnWorkspace(index, position))
+            },
+            _ => Err(anyhow!("unknown selector {}", selector)),
+        }
+    }
+
+    pub fn resolve(
+        &self,
+        client_map: &HashMap<Window, Client, BuildIdHasher>,
+        workspaces: &Cycle<Workspace>,
+        zone_manager: &ZoneManager,
+        focus: Option<Window>,
+    ) -> Option<Window> {
+        let managed = || client_map.values().filter(|client| client.is_managed());
+
+        match self {
+            Self::Focused => focus,
+            Self::Last => managed()
+                .filter(|client| Some(client.window()) != focus)
+                .max_by_key(|client| client.last_focused())
+                .map(Client::window),
+            Self::Newest => managed()
+                .max_by_key(|client| client.managed_since())
+                .map(Client::window),
+            Self::Oldest => managed()
+                .min_by_key(|client| client.managed_since())
+                .map(Client::window),
+            Self::Name(name) => managed()
+                .filter(|client| client.name() == *name)
+                .max_by_key(|client| client.last_focused())
+                .map(Client::window),
+            Self::Class(class) => managed()
+                .filter(|client| client.class() == *class)
+                .max_by_key(|client| client.last_focused())
+                .map(Client::window),
+            Self::Instance(instance) => managed()
+                .filter(|client| client.instance() == *instance)
+                .max_by_key(|client| client.last_focused())
+                .map(Client::window),
+            Self::OnWorkspace(index, position) => workspaces.get(*index)?.get_client_for(
+                match position {
+                    WorkspacePosition::First => ClientSelector::First,
+                    WorkspacePosition::Last => ClientSelector::Last,
+                    WorkspacePosition::Focused => ClientSelector::AtActive,
+                    WorkspacePosition::Master => ClientSelector::AtMaster,
+                },
+                zone_manager,
+            ),
+            Self::Window(window) => client_map.get(window).map(Client::window),
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use winsys::connection::Pid;
+
+use std::convert::TryFrom;
+use std::os::unix::io::AsRawFd;
+use std::os::unix::io::RawFd;
+
+use nix::sys::signal::SigSet;
+use nix::sys::signal::Signal;
+use nix::sys::signalfd::SfdFlags;
+use nix::sys::signalfd::SignalFd;
+use nix::sys::wait::waitpid;
+use nix::sys::wait::WaitPidFlag;
+use nix::sys::wait::WaitStatus;
+
+pub struct SignalWatcher {
+    fd: SignalFd,
+}
+
+impl SignalWatcher {
+    // the signals are blocked and delivered through a file descriptor instead, so that
+    // they can be polled alongside the X connection; spawned children get their signal
+    // mask reset by std::process::Command
+    pub fn new(signals: &[Signal]) -> Option<Self> {
+        let mut mask = SigSet::empty();
+        signals.iter().for_each(|&signal| mask.add(signal));
+
+        if let Err(err) = mask.thread_block() {
+            warn!("unable to block signals: {}", err);
+            return None;
+        }
+
+        match SignalFd::with_flags(&mask, SfdFlags::SFD_NONBLOCK | SfdFlags::SFD_CLOEXEC) {
+            Ok(fd) => Some(Self {
+                fd,
+            }),
+            Err(err) => {
+                warn!("unable to create signal file descriptor: {}", err);
+                mask.thread_unblock().ok();
+                None
+            },
+        }
+    }
+
+    #[inline]
+    pub fn fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+
+    pub fn pending(&mut self) -> Vec<Signal> {
+        let mut signals = Vec::new();
+
+        while let Ok(Some(info)) = self.fd.read_signal() {
+            match Signal::try_from(info.ssi_signo as i32) {
+                Ok(signal) if !signals.contains(&signal) => signals.push(signal),
+                _ => {},
+            }
+        }
+
+        signals
+    }
+}
+
+pub fn reap_children() -> Vec<(Pid, WaitStatus)> {
+    let mut reaped = Vec::new();
+
+    loop {
+        match waitpid(None, Some(WaitPidFlag::WNOHANG)) {
+            Ok(WaitStatus::StillAlive) | Err(_) => break,
+            Ok(status) => {
+                if let Some(pid) = status.pid() {
+                    reaped.push((pid.as_raw() as Pid, status));
+                }
+            },
+        }
+    }
+
+    reaped
+}
@@ -0,0 +1,153 @@
+use crate::identify::Index;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Affinity {
+    pub class: String,
+    pub instance: String,
+    pub workspace: Index,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LayoutProfile {
+    pub name: String,
+    pub kind: String,
+    pub main_count: u32,
+    pub main_factor: f32,
+    pub gap_size: u32,
+    pub margin: [i32; 4],
+}
+
+// margins are stored as left, right, top and bottom
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkspaceMargin {
+    pub workspace: Index,
+    pub margin: [i32; 4],
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct State {
+    affinity: Vec<Affinity>,
+    profiles: Vec<LayoutProfile>,
+    margins: Vec<WorkspaceMargin>,
+}
+
+impl State {
+    fn path() -> PathBuf {
+        env::var_os("XDG_STATE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/state")))
+            .unwrap_or_default()
+            .join(WM_NAME!())
+            .join("state.toml")
+    }
+
+    pub fn load() -> Self {
+        let path = Self::path();
+
+        match fs::read_to_string(&path) {
+            Ok(contents) => match toml::from_str(&contents) {
+                Ok(state) => state,
+                Err(err) => {
+                    warn!("could not parse {}: {}", path.display(), err);
+                    Default::default()
+                },
+            },
+            Err(_) => Default::default(),
+        }
+    }
+
+    pub fn save(&self) {
+        let path = Self::path();
+
+        if let Some(dir) = path.parent() {
+            drop(fs::create_dir_all(dir));
+        }
+
+        match toml::to_string(self) {
+            Ok(contents) => {
+                if let Err(err) = fs::write(&path, contents) {
+                    warn!("could not write {}: {}", path.display(), err);
+                }
+            },
+            Err(err) => warn!("could not serialize state: {}", err),
+        }
+    }
+
+    pub fn learned_workspace(
+        &self,
+        class: &str,
+        instance: &str,
+    ) -> Option<Index> {
+        self.affinity
+            .iter()
+            .find(|affinity| affinity.class == class && affinity.instance == instance)
+            .map(|affinity| affinity.workspace)
+    }
+
+    pub fn layout_profile(
+        &self,
+        name: &str,
+    ) -> Option<&LayoutProfile> {
+        self.profiles.iter().find(|profile| profile.name == name)
+    }
+
+    pub fn store_layout_profile(
+        &mut self,
+        profile: LayoutProfile,
+    ) {
+        self.profiles.retain(|stored| stored.name != profile.name);
+        self.profiles.push(profile);
+    }
+
+    pub fn workspace_margins(&self) -> &[WorkspaceMargin] {
+        &self.margins
+    }
+
+    pub fn store_workspace_margin(
+        &mut self,
+        workspace: Index,
+        margin: [i32; 4],
+    ) {
+        self.margins.retain(|stored| stored.workspace != workspace);
+        self.margins.push(WorkspaceMargin {
+            workspace,
+            margin,
+        });
+    }
+
+    pub fn learn_workspace(
+        &mut self,
+        class: &str,
+        instance: &str,
+        workspace: Index,
+    ) -> bool {
+        match self
+            .affinity
+            .iter_mut()
+            .find(|affinity| affinity.class == class && affinity.instance == instance)
+        {
+            Some(affinity) if affinity.workspace == workspace => false,
+            Some(affinity) => {
+                affinity.workspace = workspace;
+                true
+            },
+            None => {
+                self.affinity.push(Affinity {
+                    class: class.to_owned(),
+                    instance: instance.to_owned(),
+                    workspace,
+                });
+
+                true
+            },
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use crate::config::StatusConfig;
+
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io;
+use std::io::Write;
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::PathBuf;
+
+use nix::fcntl::OFlag;
+use nix::sys::stat::Mode;
+use nix::unistd;
+
+enum Output {
+    Stdout,
+    Fifo {
+        path: PathBuf,
+        file: Option<File>,
+    },
+}
+
+// a line is only written when it differs from the one written last; lines that
+// cannot be written (no reader on the FIFO, or a full pipe) are retried on the
+// next report rather than blocking the event loop
+pub struct StatusReporter {
+    output: Output,
+    format: String,
+    written: String,
+}
+
+impl StatusReporter {
+    pub fn open(config: &StatusConfig) -> Option<Self> {
+        let output = match config.output.as_deref() {
+            None => return None,
+            Some("-") => Output::Stdout,
+            Some(path) => {
+                let path = PathBuf::from(path);
+
+                match path.metadata() {
+                    Ok(metadata) if metadata.file_type().is_fifo() => {},
+                    Ok(_) => {
+                        warn!("status output {} exists and is not a FIFO", path.display());
+                        return None;
+                    },
+                    Err(_) => {
+                        if let Err(err) = unistd::mkfifo(&path, Mode::S_IRUSR | Mode::S_IWUSR) {
+                            warn!("could not create status FIFO {}: {}", path.display(), err);
+                            return None;
+                        }
+                    },
+                }
+
+                Output::Fifo {
+                    path,
+                    file: None,
+                }
+            },
+        };
+
+        Some(Self {
+            output,
+            format: config.format.clone(),
+            written: String::new(),
+        })
+    }
+
+    // the placeholders {workspaces}, {layout} and {title} are substituted
+    pub fn report(
+        &mut self,
+        workspaces: &str,
+        layout: &str,
+        title: &str,
+    ) {
+        let line = self
+            .format
+            .replace("{workspaces}", workspaces)
+            .replace("{layout}", layout)
+            .replace("{title}", &title.replace('\n', " "));
+
+        if line == self.written {
+            return;
+        }
+
+        if self.write(&line).is_ok() {
+            self.written = line;
+        }
+    }
+
+    fn write(
+        &mut self,
+        line: &str,
+    ) -> io::Result<()> {
+        match &mut self.output {
+            Output::Stdout => {
+                let stdout = io::stdout();
+                let mut stdout = stdout.lock();
+
+                writeln!(stdout, "{}", line).and_then(|_| stdout.flush())
+            },
+            Output::Fifo {
+                path,
+                file,
+            } => {
+                // opening a FIFO without a reader fails with ENXIO when non-blocking
+                if file.is_none() {
+                    *file = Some(
+                        OpenOptions::new()
+                            .write(true)
+                            .custom_flags(OFlag::O_NONBLOCK.bits())
+                            .open(path)?,
+                    );
+                }
+
+                // lines shorter than PIPE_BUF are written in full or not at all
+                let line = format!("{}\n", line);
+                let result = file
+                    .as_mut()
+                    .map_or(Ok(()), |fifo| fifo.write_all(line.as_bytes()));
+
+                // the reader went away, it is reopened on the next report
+                if let Err(err) = &result {
+                    if err.kind() != io::ErrorKind::WouldBlock {
+                        *file = None;
+                    }
+                }
+
+                result
+            },
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use std::time::Duration;
+use std::time::Instant;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum TimerKind {
+    Audit,
+    KeyRepeat,
+    Autostart,
+    Usage,
+    Flash,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Timer {
+    kind: TimerKind,
+    deadline: Instant,
+    interval: Option<Duration>,
+}
+
+#[derive(Debug, Default)]
+pub struct Timers {
+    timers: Vec<Timer>,
+}
+
+impl Timers {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn schedule(
+        &mut self,
+        kind: TimerKind,
+        after: Duration,
+    ) {
+        self.cancel(kind);
+        self.timers.push(Timer {
+            kind,
+            deadline: Instant::now() + after,
+            interval: None,
+        });
+    }
+
+    pub fn schedule_repeating(
+        &mut self,
+        kind: TimerKind,
+        interval: Duration,
+    ) {
+        self.cancel(kind);
+        self.timers.push(Timer {
+            kind,
+            deadline: Instant::now() + interval,
+            interval: Some(interval),
+        });
+    }
+
+    pub fn cancel(
+        &mut self,
+        kind: TimerKind,
+    ) {
+        self.timers.retain(|timer| timer.kind != kind);
+    }
+
+    pub fn is_scheduled(
+        &self,
+        kind: TimerKind,
+    ) -> bool {
+        self.timers.iter().any(|timer| timer.kind == kind)
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        let now = Instant::now();
+
+        self.timers
+            .iter()
+            .map(|timer| timer.deadline.saturating_duration_since(now))
+            .min()
+    }
+
+    pub fn expire(&mut self) -> Vec<TimerKind> {
+        let now = Instant::now();
+        let mut expired = Vec::new();
+
+        self.timers.retain(|timer| {
+            if timer.deadline > now {
+                return true;
+            }
+
+            expired.push(timer.kind);
+            timer.interval.is_some()
+        });
+
+        self.timers
+            .iter_mut()
+            .filter(|timer| timer.deadline <= now)
+            .for_each(|timer| {
+                if let Some(interval) = timer.interval {
+                    timer.deadline = now + interval;
+                }
+            });
+
+        expired
+    }
+}
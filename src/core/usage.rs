@@ -0,0 +1,105 @@
+use winsys::connection::Pid;
+
+use std::fs;
+use std::time::Instant;
+
+use nix::unistd::sysconf;
+use nix::unistd::SysconfVar;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Usage {
+    pub pid: Pid,
+    pub cpu: Option<f32>,
+    pub rss: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct UsageMonitor {
+    previous: Option<(Pid, u64, Instant)>,
+    usage: Option<Usage>,
+}
+
+impl UsageMonitor {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn usage(&self) -> Option<Usage> {
+        self.usage
+    }
+
+    // the CPU share is only known from the second sample of the same process on
+    pub fn sample(
+        &mut self,
+        pid: Option<Pid>,
+    ) -> Option<Usage> {
+        let pid = match pid {
+            Some(pid) => pid,
+            None => {
+                self.previous = None;
+                self.usage = None;
+                return None;
+            },
+        };
+
+        let (ticks, rss) = match (cpu_ticks(pid), resident_kib(pid)) {
+            (Some(ticks), Some(rss)) => (ticks, rss),
+            _ => {
+                self.previous = None;
+                self.usage = None;
+                return None;
+            },
+        };
+
+        let now = Instant::now();
+        let cpu = match self.previous {
+            Some((previous_pid, previous_ticks, previous_time)) if previous_pid == pid => {
+                let elapsed = now.duration_since(previous_time).as_secs_f32();
+                let ticks_per_sec = sysconf(SysconfVar::CLK_TCK).ok().flatten().unwrap_or(100);
+
+                if elapsed > 0.0 {
+                    let seconds =
+                        ticks.saturating_sub(previous_ticks) as f32 / ticks_per_sec as f32;
+
+                    Some(100.0 * seconds / elapsed)
+                } else {
+                    None
+                }
+            },
+            _ => None,
+        };
+
+        self.previous = Some((pid, ticks, now));
+        self.usage = Some(Usage {
+            pid,
+            cpu,
+            rss,
+        });
+
+        self.usage
+    }
+}
+
+// user and system time, in clock ticks
+fn cpu_ticks(pid: Pid) -> Option<u64> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+
+    // the command name may contain spaces, the fields after it cannot
+    let fields = stat[stat.rfind(')')? + 1..]
+        .split_whitespace()
+        .collect::<Vec<&str>>();
+
+    let utime = fields.get(11)?.parse::<u64>().ok()?;
+    let stime = fields.get(12)?.parse::<u64>().ok()?;
+
+    Some(utime + stime)
+}
+
+fn resident_kib(pid: Pid) -> Option<u64> {
+    fs::read_to_string(format!("/proc/{}/status", pid))
+        .ok()?
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))
+        .and_then(|value| value.split_whitespace().next())
+        .and_then(|value| value.parse().ok())
+}
@@ -2,6 +2,7 @@ use crate::change::Change;
 use crate::change::Direction;
 use crate::identify::Index;
 
+use winsys::connection::Pid;
 use winsys::input::Button;
 use winsys::input::Modifier;
 
@@ -121,6 +122,36 @@ impl Util {
         }
     }
 
+    // labels of equal length, so that none is the prefix of another
+    pub fn hint_labels(
+        alphabet: &[char],
+        count: usize,
+    ) -> Vec<String> {
+        if alphabet.len() < 2 {
+            return Vec::new();
+        }
+
+        let mut length = 1;
+        while alphabet.len().pow(length) < count {
+            length += 1;
+        }
+
+        (0..count)
+            .map(|mut n| {
+                let mut label: Vec<char> = (0..length)
+                    .map(|_| {
+                        let c = alphabet[n % alphabet.len()];
+                        n /= alphabet.len();
+                        c
+                    })
+                    .collect();
+
+                label.reverse();
+                label.into_iter().collect()
+            })
+            .collect()
+    }
+
     pub fn spawn<S: Into<String>>(cmd: S) {
         let cmd = cmd.into();
         let args: Vec<&str> = cmd.split_whitespace().collect();
@@ -141,6 +172,36 @@ impl Util {
         };
     }
 
+    pub fn spawn_child(cmd: &str) -> Option<Pid> {
+        let args: Vec<&str> = cmd.split_whitespace().collect();
+        let (program, args) = args.split_first()?;
+
+        match Command::new(program)
+            .args(args)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        {
+            Ok(child) => Some(child.id()),
+            Err(err) => {
+                warn!("could not spawn {}: {}", cmd, err);
+                None
+            },
+        }
+    }
+
+    pub fn notify(
+        summary: &str,
+        body: &str,
+    ) {
+        Command::new("notify-send")
+            .args(["--urgency=critical", summary, body])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok();
+    }
+
     pub fn spawn_shell<S: Into<String>>(cmd: S) {
         let cmd = cmd.into();
 
@@ -10,6 +10,7 @@ use crate::identify::Ident;
 use crate::identify::Identify;
 use crate::identify::Index;
 use crate::layout::Layout;
+use crate::layout::LayoutData;
 use crate::placement::Placement;
 use crate::placement::PlacementMethod;
 use crate::placement::PlacementRegion;
@@ -40,6 +41,14 @@ pub enum ClientSelector {
     Last,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZoneSelector {
+    Focus,
+    Parent,
+    Root,
+    Marked,
+}
+
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 pub enum BufferKind {
     Move,
@@ -154,6 +163,7 @@ pub struct Workspace {
     root_zone: ZoneId,
     focus_zones: RefCell<Cycle<ZoneId>>,
     spawn_zones: RefCell<Cycle<ZoneId>>,
+    marked_zone: Cell<Option<ZoneId>>,
     clients: RefCell<Cycle<Window>>,
     icons: RefCell<Cycle<Window>>,
 }
@@ -170,6 +180,7 @@ impl Workspace {
             root_zone,
             focus_zones: RefCell::new(Cycle::new(vec![root_zone], true)),
             spawn_zones: RefCell::new(Cycle::new(vec![root_zone], true)),
+            marked_zone: Cell::new(None),
             clients: RefCell::new(Cycle::new(Vec::new(), true)),
             icons: RefCell::new(Cycle::new(Vec::new(), true)),
         }
@@ -260,11 +271,55 @@ impl Workspace {
         self.focus_zones.borrow().active_element().copied()
     }
 
+    // the most recently focused zone other than the active one
+    pub fn previous_focus_zone(&self) -> Option<ZoneId> {
+        let zones = self.focus_zones.borrow();
+        let active = zones.active_element().copied();
+
+        zones
+            .stack()
+            .into_iter()
+            .rev()
+            .find(|&id| Some(id) != active)
+    }
+
     #[inline(always)]
     pub fn active_spawn_zone(&self) -> Option<ZoneId> {
         self.spawn_zones.borrow().active_element().copied()
     }
 
+    #[inline(always)]
+    pub fn spawn_zones(&self) -> Vec<ZoneId> {
+        self.spawn_zones.borrow().as_vec()
+    }
+
+    #[inline(always)]
+    pub fn mark_zone(
+        &self,
+        id: Option<ZoneId>,
+    ) {
+        self.marked_zone.set(id);
+    }
+
+    pub fn select_zone(
+        &self,
+        sel: ZoneSelector,
+        zone_manager: &ZoneManager,
+    ) -> Option<ZoneId> {
+        match sel {
+            ZoneSelector::Focus => self.active_focus_zone(),
+            ZoneSelector::Parent => self
+                .active_focus_zone()
+                .map(|id| zone_manager.nearest_cycle(id))
+                .and_then(|id| zone_manager.parent_id(id)),
+            ZoneSelector::Root => Some(self.root_zone),
+            ZoneSelector::Marked => self
+                .marked_zone
+                .get()
+                .filter(|&id| zone_manager.zone_checked(id).is_some()),
+        }
+    }
+
     #[inline(always)]
     pub fn focused_client(&self) -> Option<Window> {
         self.clients.borrow().active_element().copied()
@@ -326,6 +381,45 @@ impl Workspace {
         self.clients.borrow_mut().insert_at(insert, window);
     }
 
+    #[inline(always)]
+    pub fn client_after(
+        &self,
+        window: Window,
+    ) -> Option<Window> {
+        let clients = self.clients.borrow();
+        let index = clients.index_for(&Selector::AtIdent(window))?;
+
+        clients.get(index + 1).copied()
+    }
+
+    // moves the client in front of its successor, leaving the active client as is
+    pub fn reinsert_client(
+        &self,
+        window: Window,
+        successor: Option<Window>,
+    ) {
+        let mut clients = self.clients.borrow_mut();
+
+        if !clients.contains(&window) {
+            return;
+        }
+
+        let active = clients.active_element().copied();
+        let insert = match successor {
+            Some(successor) if successor != window && clients.contains(&successor) => {
+                InsertPos::BeforeIdent(successor)
+            },
+            _ => InsertPos::Back,
+        };
+
+        clients.remove_for(&Selector::AtIdent(window));
+        clients.insert_at(&insert, window);
+
+        if let Some(active) = active {
+            clients.activate_for(&Selector::AtIdent(active));
+        }
+    }
+
     #[inline(always)]
     pub fn replace_client(
         &self,
@@ -547,12 +641,11 @@ impl Workspace {
     #[inline(always)]
     pub fn copy_prev_layout_data(
         &self,
+        sel: ZoneSelector,
         zone_manager: &mut ZoneManager,
     ) -> Result<(), StateChangeError> {
-        let &id = self
-            .focus_zones
-            .borrow()
-            .active_element()
+        let id = self
+            .select_zone(sel, zone_manager)
             .ok_or(StateChangeError::EarlyStop)?;
 
         let prev_data = zone_manager
@@ -570,12 +663,11 @@ impl Workspace {
     #[inline(always)]
     pub fn reset_layout_data(
         &self,
+        sel: ZoneSelector,
         zone_manager: &mut ZoneManager,
     ) -> Result<(), StateChangeError> {
-        let &id = self
-            .focus_zones
-            .borrow()
-            .active_element()
+        let id = self
+            .select_zone(sel, zone_manager)
             .ok_or(StateChangeError::EarlyStop)?;
 
         let default_data = zone_manager
@@ -592,13 +684,12 @@ impl Workspace {
     #[inline(always)]
     pub fn change_gap_size(
         &self,
+        sel: ZoneSelector,
         change: Change<u32>,
         zone_manager: &mut ZoneManager,
     ) -> Result<(), StateChangeError> {
-        let &id = self
-            .focus_zones
-            .borrow()
-            .active_element()
+        let id = self
+            .select_zone(sel, zone_manager)
             .ok_or(StateChangeError::EarlyStop)?;
 
         let data = zone_manager
@@ -620,12 +711,11 @@ impl Workspace {
     #[inline(always)]
     pub fn reset_gap_size(
         &self,
+        sel: ZoneSelector,
         zone_manager: &mut ZoneManager,
     ) -> Result<(), StateChangeError> {
-        let &id = self
-            .focus_zones
-            .borrow()
-            .active_element()
+        let id = self
+            .select_zone(sel, zone_manager)
             .ok_or(StateChangeError::EarlyStop)?;
 
         let default_data = zone_manager
@@ -642,13 +732,12 @@ impl Workspace {
     #[inline(always)]
     pub fn change_main_count(
         &self,
+        sel: ZoneSelector,
         change: Change<u32>,
         zone_manager: &mut ZoneManager,
     ) -> Result<(), StateChangeError> {
-        let &id = self
-            .focus_zones
-            .borrow()
-            .active_element()
+        let id = self
+            .select_zone(sel, zone_manager)
             .ok_or(StateChangeError::EarlyStop)?;
 
         let data = zone_manager
@@ -670,13 +759,12 @@ impl Workspace {
     #[inline(always)]
     pub fn change_main_factor(
         &self,
+        sel: ZoneSelector,
         change: Change<f32>,
         zone_manager: &mut ZoneManager,
     ) -> Result<(), StateChangeError> {
-        let &id = self
-            .focus_zones
-            .borrow()
-            .active_element()
+        let id = self
+            .select_zone(sel, zone_manager)
             .ok_or(StateChangeError::EarlyStop)?;
 
         let data = zone_manager
@@ -700,14 +788,13 @@ impl Workspace {
     #[inline(always)]
     pub fn change_margin(
         &self,
+        sel: ZoneSelector,
         edge: Edge,
         change: Change<i32>,
         zone_manager: &mut ZoneManager,
     ) -> Result<(), StateChangeError> {
-        let &id = self
-            .focus_zones
-            .borrow()
-            .active_element()
+        let id = self
+            .select_zone(sel, zone_manager)
             .ok_or(StateChangeError::EarlyStop)?;
 
         let data = zone_manager
@@ -737,15 +824,70 @@ impl Workspace {
         }
     }
 
+    #[inline(always)]
+    pub fn set_margin(
+        &self,
+        sel: ZoneSelector,
+        edge: Edge,
+        value: i32,
+        zone_manager: &mut ZoneManager,
+    ) -> Result<(), StateChangeError> {
+        let id = self
+            .select_zone(sel, zone_manager)
+            .ok_or(StateChangeError::EarlyStop)?;
+
+        let data = zone_manager
+            .active_data_mut(id)
+            .ok_or(StateChangeError::EarlyStop)?;
+
+        Self::set_data_margin(data, edge, value);
+        Ok(())
+    }
+
+    // unlike set_margin, the margin is kept when the zone changes layouts
+    pub fn set_persistent_margin(
+        &self,
+        sel: ZoneSelector,
+        edge: Edge,
+        value: i32,
+        zone_manager: &mut ZoneManager,
+    ) -> Result<(), StateChangeError> {
+        let id = self
+            .select_zone(sel, zone_manager)
+            .ok_or(StateChangeError::EarlyStop)?;
+
+        zone_manager
+            .all_data_mut(id)
+            .ok_or(StateChangeError::EarlyStop)?
+            .into_iter()
+            .for_each(|data| Self::set_data_margin(data, edge, value));
+
+        Ok(())
+    }
+
+    fn set_data_margin(
+        data: &mut LayoutData,
+        edge: Edge,
+        value: i32,
+    ) {
+        let (edge_value, edge_max) = match edge {
+            Edge::Left => (&mut data.margin.left, Layout::MAX_MARGIN.left),
+            Edge::Right => (&mut data.margin.right, Layout::MAX_MARGIN.right),
+            Edge::Top => (&mut data.margin.top, Layout::MAX_MARGIN.top),
+            Edge::Bottom => (&mut data.margin.bottom, Layout::MAX_MARGIN.bottom),
+        };
+
+        *edge_value = value.clamp(0, edge_max);
+    }
+
     #[inline(always)]
     pub fn reset_margin(
         &self,
+        sel: ZoneSelector,
         zone_manager: &mut ZoneManager,
     ) -> Result<(), StateChangeError> {
-        let &id = self
-            .focus_zones
-            .borrow()
-            .active_element()
+        let id = self
+            .select_zone(sel, zone_manager)
             .ok_or(StateChangeError::EarlyStop)?;
 
         let default_data = zone_manager
@@ -11,12 +11,14 @@ use crate::layout::Apply;
 use crate::layout::Layout;
 use crate::layout::LayoutConfig;
 use crate::layout::LayoutData;
+use crate::layout::LayoutDefaults;
 use crate::layout::LayoutKind;
 use crate::placement::Placement;
 use crate::placement::PlacementMethod;
 use crate::placement::PlacementRegion;
 use crate::placement::PlacementTarget;
 
+use winsys::geometry::Pos;
 use winsys::geometry::Region;
 use winsys::window::Window;
 
@@ -46,6 +48,7 @@ pub struct Zone {
     region: Cell<Region>,
     decoration: Cell<Decoration>,
     is_visible: Cell<bool>,
+    expanded: Cell<bool>,
 }
 
 impl Zone {
@@ -68,6 +71,7 @@ impl Zone {
             region: Cell::new(region),
             decoration: Cell::new(Decoration::NO_DECORATION),
             is_visible: Cell::new(true),
+            expanded: Cell::new(false),
         })
     }
 
@@ -137,6 +141,13 @@ impl Zone {
         }
     }
 
+    pub fn all_data_mut(&mut self) -> Option<Vec<&mut LayoutData>> {
+        match self.content {
+            ZoneContent::Layout(ref mut layout, _) => Some(layout.all_data_mut()),
+            _ => None,
+        }
+    }
+
     pub fn prev_data(&self) -> Option<&LayoutData> {
         match self.content {
             ZoneContent::Layout(ref layout, _) => Some(layout.prev_data()),
@@ -166,6 +177,7 @@ enum ZoneChange {
 pub struct ZoneManager {
     zone_map: HashMap<ZoneId, Zone>,
     persistent_data_copy: bool,
+    layout_defaults: LayoutDefaults,
 }
 
 impl ZoneManager {
@@ -173,14 +185,27 @@ impl ZoneManager {
         Self {
             zone_map: HashMap::new(),
             persistent_data_copy: true,
+            layout_defaults: Default::default(),
         }
     }
 
+    // only layout zones created afterwards start out with the new defaults
+    pub fn set_layout_defaults(
+        &mut self,
+        defaults: LayoutDefaults,
+    ) {
+        self.layout_defaults = defaults;
+    }
+
     pub fn new_zone(
         &mut self,
         parent: Option<ZoneId>,
-        content: ZoneContent,
+        mut content: ZoneContent,
     ) -> ZoneId {
+        if let ZoneContent::Layout(layout, _) = &mut content {
+            layout.apply_defaults(&self.layout_defaults);
+        }
+
         let (id, zone) = Zone::new(parent, content, Region::new(0, 0, 0, 0));
         let parent = parent.and_then(|p| self.zone_map.get_mut(&p));
 
@@ -276,7 +301,9 @@ impl ZoneManager {
         let cycle = self.nearest_cycle(id);
         let cycle = self.zone(cycle);
 
-        cycle.default_data()
+        cycle
+            .default_data()
+            .map(|data| self.layout_defaults.apply(data))
     }
 
     pub fn active_prev_data(
@@ -299,6 +326,16 @@ impl ZoneManager {
         cycle.data_mut()
     }
 
+    pub fn all_data_mut(
+        &mut self,
+        id: ZoneId,
+    ) -> Option<Vec<&mut LayoutData>> {
+        let cycle = self.nearest_cycle(id);
+        let cycle = self.zone_mut(cycle);
+
+        cycle.all_data_mut()
+    }
+
     pub fn active_layoutconfig(
         &self,
         id: ZoneId,
@@ -366,6 +403,35 @@ impl ZoneManager {
         }
     }
 
+    pub fn is_tab(
+        &self,
+        id: ZoneId,
+    ) -> bool {
+        matches!(
+            self.zone_map.get(&id).map(|zone| &zone.content),
+            Some(ZoneContent::Tab(_))
+        )
+    }
+
+    // the parent layout zone of the zone hands its entire region to its active
+    // element while expanded; returns whether the zone is now expanded
+    pub fn toggle_expanded(
+        &self,
+        id: ZoneId,
+    ) -> bool {
+        let parent = match self.parent_id(id).and_then(|id| self.zone_map.get(&id)) {
+            Some(parent) => parent,
+            None => return false,
+        };
+
+        if let ZoneContent::Layout(..) = parent.content {
+            parent.expanded.set(!parent.expanded.get());
+            parent.expanded.get()
+        } else {
+            false
+        }
+    }
+
     pub fn nearest_cycle(
         &self,
         id: ZoneId,
@@ -390,6 +456,22 @@ impl ZoneManager {
         }
     }
 
+    // the innermost visible zone among the given ones that encloses the position
+    pub fn zone_at(
+        &self,
+        ids: &[ZoneId],
+        pos: Pos,
+    ) -> Option<ZoneId> {
+        ids.iter()
+            .filter_map(|id| self.zone_map.get(id))
+            .filter(|zone| zone.is_visible.get() && zone.region.get().encompasses(pos))
+            .min_by_key(|zone| {
+                let dim = zone.region.get().dim;
+                dim.w as u64 * dim.h as u64
+            })
+            .map(|zone| zone.id)
+    }
+
     pub fn next_cycle(
         &self,
         mut id: ZoneId,
@@ -588,6 +670,27 @@ impl ZoneManager {
                     zones.iter().map(|id| Some(id) == active_element).collect(),
                 );
 
+                let application = if zone.expanded.get() && method == PlacementMethod::Tile {
+                    zones
+                        .iter()
+                        .zip(application)
+                        .map(|(id, (disposition, is_visible))| {
+                            if Some(id) != active_element {
+                                return (disposition, false);
+                            }
+
+                            match disposition {
+                                Disposition::Unchanged(decoration)
+                                | Disposition::Changed(_, decoration) => {
+                                    (Disposition::Changed(region, decoration), is_visible)
+                                },
+                            }
+                        })
+                        .collect()
+                } else {
+                    application
+                };
+
                 zones.into_iter().zip(application.into_iter()).for_each(
                     |(id, (disposition, is_visible))| {
                         let (region, decoration) = match disposition {
@@ -0,0 +1,290 @@
+use std::process;
+
+use clap::App;
+use clap::AppSettings;
+use clap::Arg;
+use clap::ArgMatches;
+use clap::SubCommand;
+
+#[path = "../client/ipc.rs"]
+mod ipc;
+
+const QUERIES: &[&str] = &[
+    "clients",
+    "consumers",
+    "usage",
+    "timings",
+    "version",
+    "features",
+    "config-error",
+];
+
+fn app() -> App<'static, 'static> {
+    let selector = Arg::with_name("selector")
+        .help("The window to act on, the focused one if omitted")
+        .default_value("focused");
+
+    App::new("wzrdctl")
+        .version(env!("CARGO_PKG_VERSION"))
+        .about("Controls a running wzrd over its IPC socket")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .setting(AppSettings::AllowExternalSubcommands)
+        .setting(AppSettings::VersionlessSubcommands)
+        .subcommand(
+            SubCommand::with_name("workspace")
+                .about("Activates a workspace")
+                .arg(Arg::with_name("workspace").required(true)),
+        )
+        .subcommand(
+            SubCommand::with_name("send")
+                .about("Sends a window to a workspace")
+                .arg(Arg::with_name("workspace").required(true))
+                .arg(selector.clone()),
+        )
+        .subcommand(
+            SubCommand::with_name("layout")
+                .about("Sets the layout of the active workspace")
+                .arg(Arg::with_name("layout").required(true)),
+        )
+        .subcommand(
+            SubCommand::with_name("margin")
+                .about("Sets a margin of a workspace, the active one if omitted")
+                .arg(
+                    Arg::with_name("edge")
+                        .required(true)
+                        .possible_values(&["left", "right", "top", "bottom"]),
+                )
+                .arg(Arg::with_name("value").required(true))
+                .arg(Arg::with_name("workspace")),
+        )
+        .subcommand(
+            SubCommand::with_name("margin-preset")
+                .about("Applies a margin preset to a workspace, the active one if omitted")
+                .arg(Arg::with_name("preset").required(true))
+                .arg(Arg::with_name("workspace")),
+        )
+        .subcommand(
+            SubCommand::with_name("focus")
+                .about("Focuses a window")
+                .arg(selector.clone()),
+        )
+        .subcommand(
+            SubCommand::with_name("close")
+                .about("Closes a window")
+                .arg(selector.clone()),
+        )
+        .subcommand(
+            SubCommand::with_name("float")
+                .about("Toggles whether a window floats")
+                .arg(selector.clone()),
+        )
+        .subcommand(
+            SubCommand::with_name("fullscreen")
+                .about("Toggles whether a window is fullscreen")
+                .arg(selector.clone()),
+        )
+        .subcommand(
+            SubCommand::with_name("flash")
+                .about("Flashes the decoration of a window")
+                .arg(selector),
+        )
+        .subcommand(
+            SubCommand::with_name("spawn")
+                .about("Spawns a command")
+                .setting(AppSettings::TrailingVarArg)
+                .arg(Arg::with_name("command").required(true).multiple(true)),
+        )
+        .subcommand(
+            SubCommand::with_name("run")
+                .about("Runs a configured action")
+                .arg(Arg::with_name("action").required(true)),
+        )
+        .subcommand(
+            SubCommand::with_name("reload-rules")
+                .about("Reloads the rules from the configuration")
+                .arg(
+                    Arg::with_name("apply")
+                        .long("apply")
+                        .help("Re-applies the rules to managed clients"),
+                )
+                .arg(
+                    Arg::with_name("workspace")
+                        .long("workspace")
+                        .help("Lets the rules move clients to other workspaces"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("rule")
+                .about("Manages the rules added at runtime")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("add")
+                        .about("Adds a rule for windows mapped from now on")
+                        .arg(
+                            Arg::with_name("fields")
+                                .help("Fields as in the configuration, e.g. class=mpv float=true")
+                                .required(true)
+                                .multiple(true),
+                        ),
+                )
+                .subcommand(SubCommand::with_name("list").about("Lists the rules"))
+                .subcommand(
+                    SubCommand::with_name("remove")
+                        .about("Removes a rule")
+                        .arg(Arg::with_name("index").required(true)),
+                ),
+        )
+        .subcommand(SubCommand::with_name("lock").about("Locks the session"))
+        .subcommand(
+            SubCommand::with_name("subscribe")
+                .about("Prints the workspaces, layout and title whenever they change"),
+        )
+        .subcommand(
+            SubCommand::with_name("query")
+                .about("Queries the state of the window manager")
+                .arg(
+                    Arg::with_name("query")
+                        .required(true)
+                        .possible_values(QUERIES),
+                ),
+        )
+}
+
+// translates the invocation into a command line of the IPC protocol; commands
+// that are not known here are passed on as they are
+fn command(matches: &ArgMatches<'_>) -> String {
+    let (name, args) = match matches.subcommand() {
+        (name, Some(args)) => (name, args),
+        (name, None) => return name.to_owned(),
+    };
+
+    let values = |names: &[&str]| {
+        names
+            .iter()
+            .filter_map(|&name| args.value_of(name))
+            .collect::<Vec<&str>>()
+            .join(" ")
+    };
+
+    let line = match name {
+        "workspace" => format!("workspace {}", values(&["workspace"])),
+        "send" => format!("send-to-workspace {}", values(&["workspace", "selector"])),
+        "layout" => format!("set-layout {}", values(&["layout"])),
+        "margin" => format!("set-margin {}", values(&["edge", "value", "workspace"])),
+        "margin-preset" => format!("margin-preset {}", values(&["preset", "workspace"])),
+        "focus" => format!("focus {}", values(&["selector"])),
+        "close" => format!("close {}", values(&["selector"])),
+        "float" => format!("toggle-float {}", values(&["selector"])),
+        "fullscreen" => format!("toggle-fullscreen {}", values(&["selector"])),
+        "flash" => format!("flash {}", values(&["selector"])),
+        "spawn" => format!(
+            "spawn {}",
+            args.values_of("command")
+                .map(|command| command.collect::<Vec<&str>>().join(" "))
+                .unwrap_or_default()
+        ),
+        "run" => format!("run {}", values(&["action"])),
+        "reload-rules" => ["reload-rules", "--apply", "--workspace"]
+            .iter()
+            .zip(&[true, args.is_present("apply"), args.is_present("workspace")])
+            .filter(|(_, &present)| present)
+            .map(|(arg, _)| *arg)
+            .collect::<Vec<&str>>()
+            .join(" "),
+        "rule" => match args.subcommand() {
+            ("add", Some(add)) => format!(
+                "rule add {}",
+                add.values_of("fields")
+                    .map(|fields| fields.collect::<Vec<&str>>().join(" "))
+                    .unwrap_or_default()
+            ),
+            ("remove", Some(remove)) => {
+                format!("rule remove {}", remove.value_of("index").unwrap_or_default())
+            },
+            (command, _) => format!("rule {}", command),
+        },
+        "query" => values(&["query"]),
+        name => match args.values_of("") {
+            Some(external) => format!("{} {}", name, external.collect::<Vec<&str>>().join(" ")),
+            None => name.to_owned(),
+        },
+    };
+
+    line.trim_end().to_owned()
+}
+
+// lays out whitespace-separated fields in aligned columns, the last of which
+// takes the remainder of the line
+fn table(
+    header: &[&str],
+    lines: &[String],
+) -> Vec<String> {
+    let rows: Vec<Vec<&str>> = std::iter::once(header.to_vec())
+        .chain(
+            lines
+                .iter()
+                .map(|line| line.splitn(header.len(), ' ').collect::<Vec<&str>>()),
+        )
+        .collect();
+
+    let widths: Vec<usize> = (0..header.len())
+        .map(|i| {
+            rows.iter()
+                .map(|row| row.get(i).map_or(0, |field| field.chars().count()))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    rows.iter()
+        .map(|row| {
+            row.iter()
+                .zip(&widths)
+                .map(|(field, &width)| format!("{:<width$}", field, width = width))
+                .collect::<Vec<String>>()
+                .join("  ")
+                .trim_end()
+                .to_owned()
+        })
+        .collect()
+}
+
+fn pretty(
+    command: &str,
+    lines: Vec<String>,
+) -> Vec<String> {
+    if lines.is_empty() {
+        return lines;
+    }
+
+    match command {
+        "clients" => table(&["WINDOW", "WORKSPACE", "MONITOR", "CLASS", "NAME"], &lines),
+        "consumers" => table(&["PRODUCER", "CONSUMERS"], &lines),
+        "usage" => table(&["WINDOW", "PID", "CPU%", "RSS"], &lines),
+        "timings" => table(&["SPAN", "COUNT", "AVG(us)", "MAX(us)"], &lines),
+        "rule list" => table(&["INDEX", "RULE"], &lines),
+        _ => lines,
+    }
+}
+
+pub fn main() {
+    let matches = app().get_matches();
+    let command = command(&matches);
+
+    let result = match command.as_str() {
+        "subscribe" => ipc::subscribe(|line| println!("{}", line)),
+        command => ipc::request(command).map(|lines| {
+            pretty(command, lines)
+                .iter()
+                .for_each(|line| println!("{}", line));
+        }),
+    };
+
+    match result {
+        Ok(()) => {},
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
+        },
+    }
+}
@@ -6,6 +6,7 @@ use crate::geometry::Region;
 use crate::geometry::Strut;
 use crate::hints::Hints;
 use crate::hints::SizeHints;
+use crate::input::CursorShape;
 use crate::input::KeyInput;
 use crate::input::MouseInput;
 use crate::screen::Screen;
@@ -16,14 +17,25 @@ use crate::window::WindowType;
 use crate::Result;
 
 use std::collections::HashMap;
+use std::os::unix::io::RawFd;
 
 pub type Pid = u32;
 
+// the _NET_WM_DESKTOP value of windows that are shown on all desktops
+pub const ALL_DESKTOPS: usize = 0xffff_ffff;
+
 pub trait Connection {
     fn flush(&self) -> bool;
+    fn fd(&self) -> RawFd;
     fn step(&self) -> Option<Event>;
+    fn poll(&self) -> Option<Event>;
     fn connected_outputs(&self) -> Vec<Screen>;
     fn top_level_windows(&self) -> Vec<Window>;
+    fn get_resource(
+        &self,
+        name: &str,
+        class: &str,
+    ) -> Option<String>;
     fn get_pointer_position(&self) -> Pos;
     fn warp_pointer_center_of_window_or_root(
         &self,
@@ -42,16 +54,24 @@ pub trait Connection {
     fn confine_pointer(
         &self,
         window: Window,
+        cursor: CursorShape,
     );
+    fn confine_pointer_to(
+        &self,
+        window: Window,
+    ) -> bool;
+    fn unconfine_pointer(&self);
     fn release_pointer(&self);
+    fn grab_input(&self) -> bool;
+    fn release_input(&self);
     fn cleanup(&self);
 
     // Window manipulation
     fn create_frame(
         &self,
         region: Region,
-    ) -> Window;
-    fn create_handle(&self) -> Window;
+    ) -> Result<Window>;
+    fn create_handle(&self) -> Result<Window>;
     fn init_window(
         &self,
         window: Window,
@@ -139,6 +159,21 @@ pub trait Connection {
         key_codes: &[&KeyInput],
         mouse_bindings: &[&MouseInput],
     );
+    fn ungrab_bindings(&self);
+    fn set_window_input_shape(
+        &self,
+        window: Window,
+        regions: &[Region],
+    );
+    fn set_window_bounding_shape(
+        &self,
+        window: Window,
+        regions: &[Region],
+    );
+    fn reset_window_bounding_shape(
+        &self,
+        window: Window,
+    );
     fn regrab_buttons(
         &self,
         window: Window,
@@ -163,6 +198,24 @@ pub trait Connection {
         window: Window,
         color: u32,
     );
+    fn fill_window_regions(
+        &self,
+        window: Window,
+        color: u32,
+        regions: &[Region],
+    );
+    fn draw_outline(
+        &self,
+        region: Option<Region>,
+    );
+    fn draw_hints(
+        &self,
+        hints: &[(Region, String)],
+    );
+    fn draw_debug_hud(
+        &self,
+        hud: Option<(Pos, &[String])>,
+    );
     fn update_window_offset(
         &self,
         window: Window,
@@ -173,6 +226,10 @@ pub trait Connection {
         &self,
         window: Window,
     ) -> Result<Region>;
+    fn get_window_opaque_region(
+        &self,
+        window: Window,
+    ) -> Vec<Region>;
     fn get_window_pid(
         &self,
         window: Window,
@@ -251,6 +308,11 @@ pub trait Connection {
         window: Window,
         index: usize,
     );
+    fn set_window_monitor(
+        &self,
+        window: Window,
+        monitor: Option<usize>,
+    );
     fn set_window_state(
         &self,
         window: Window,
@@ -264,20 +326,38 @@ pub trait Connection {
     );
     fn set_desktop_geometry(
         &self,
-        geometries: &[&Region],
+        geometry: Dim,
     );
     fn set_desktop_viewport(
         &self,
-        viewports: &[&Region],
+        viewports: &[Pos],
     );
     fn set_workarea(
         &self,
         workareas: &[&Region],
     );
+    fn set_desktop_layout(
+        &self,
+        vertical: bool,
+        columns: usize,
+        rows: usize,
+    );
     fn update_desktops(
         &self,
         desktop_names: &[&str],
     );
+    fn set_workspace_occupancy(
+        &self,
+        occupancy: &[(usize, usize)],
+    );
+    fn set_focused_title(
+        &self,
+        title: Option<&str>,
+    );
+    fn set_layout_symbol(
+        &self,
+        symbol: Option<char>,
+    );
     fn update_client_list(
         &self,
         clients: &[Window],
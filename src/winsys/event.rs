@@ -50,12 +50,13 @@ pub enum Event {
     },
     StateRequest {
         window: Window,
-        state: WindowState,
+        states: Vec<WindowState>,
         action: ToggleAction,
         on_root: bool,
     },
     FocusRequest {
         window: Window,
+        source: RequestSource,
         on_root: bool,
     },
     CloseRequest {
@@ -100,6 +101,7 @@ pub enum Event {
         on_root: bool,
     },
     ScreenChange,
+    ManagerReplaced,
 }
 
 #[derive(Debug, Copy, Clone, PartialOrd, Ord, PartialEq, Eq)]
@@ -108,6 +110,14 @@ pub enum StackMode {
     Below,
 }
 
+// the source indication of EWMH client messages
+#[derive(Debug, Copy, Clone, PartialOrd, Ord, PartialEq, Eq)]
+pub enum RequestSource {
+    Legacy,
+    Application,
+    Pager,
+}
+
 #[derive(Debug, Copy, Clone, PartialOrd, Ord, PartialEq, Eq)]
 pub enum ToggleAction {
     Toggle,
@@ -121,4 +131,6 @@ pub enum PropertyKind {
     Class,
     Size,
     Strut,
+    OpaqueRegion,
+    Resources,
 }
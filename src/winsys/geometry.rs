@@ -16,7 +16,7 @@ pub enum Edge {
     Bottom,
 }
 
-#[derive(Debug, Copy, Clone, PartialOrd, Ord, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialOrd, Ord, PartialEq, Eq, Hash)]
 pub enum Corner {
     TopLeft,
     TopRight,
@@ -267,6 +267,119 @@ impl Region {
         self.encompasses(region.pos) || region.encompasses(self.pos)
     }
 
+    pub fn intersect(
+        &self,
+        other: Region,
+    ) -> Option<Region> {
+        let left = self.pos.x.max(other.pos.x);
+        let top = self.pos.y.max(other.pos.y);
+        let right = (self.pos.x + self.dim.w).min(other.pos.x + other.dim.w);
+        let bottom = (self.pos.y + self.dim.h).min(other.pos.y + other.dim.h);
+
+        if left >= right || top >= bottom {
+            None
+        } else {
+            Some(Region::new(left, top, right - left, bottom - top))
+        }
+    }
+
+    // moves the region from one enclosing region to another, keeping its relative offset
+    // as far as it still fits
+    pub fn transfer(
+        &self,
+        from: Region,
+        to: Region,
+    ) -> Region {
+        Region {
+            pos: Pos {
+                x: to.pos.x + (self.pos.x - from.pos.x).min(to.dim.w - self.dim.w).max(0),
+                y: to.pos.y + (self.pos.y - from.pos.y).min(to.dim.h - self.dim.h).max(0),
+            },
+            dim: self.dim,
+        }
+    }
+
+    // approximates a region with rounded corners by horizontal strips
+    pub fn rounded(
+        &self,
+        radius: i32,
+    ) -> Vec<Region> {
+        let radius = radius.min(self.dim.w / 2).min(self.dim.h / 2);
+
+        if radius <= 0 {
+            return vec![*self];
+        }
+
+        let mut regions = Vec::with_capacity(2 * radius as usize + 1);
+
+        for row in 0..radius {
+            let dy = (radius - row) as f64 - 0.5;
+            let inset =
+                radius - ((radius * radius) as f64 - dy * dy).max(0.0).sqrt().round() as i32;
+
+            let width = self.dim.w - 2 * inset;
+
+            regions.push(Region::new(self.pos.x + inset, self.pos.y + row, width, 1));
+            regions.push(Region::new(
+                self.pos.x + inset,
+                self.pos.y + self.dim.h - 1 - row,
+                width,
+                1,
+            ));
+        }
+
+        regions.push(Region::new(
+            self.pos.x,
+            self.pos.y + radius,
+            self.dim.w,
+            self.dim.h - 2 * radius,
+        ));
+
+        regions
+            .into_iter()
+            .filter(|region| region.dim.w > 0 && region.dim.h > 0)
+            .collect()
+    }
+
+    pub fn subtract(
+        &self,
+        other: Region,
+    ) -> Vec<Region> {
+        let left = self.pos.x.max(other.pos.x);
+        let top = self.pos.y.max(other.pos.y);
+        let right = (self.pos.x + self.dim.w).min(other.pos.x + other.dim.w);
+        let bottom = (self.pos.y + self.dim.h).min(other.pos.y + other.dim.h);
+
+        if left >= right || top >= bottom {
+            return vec![*self];
+        }
+
+        vec![
+            Region::new(
+                self.pos.x,
+                self.pos.y,
+                self.dim.w,
+                top - self.pos.y,
+            ),
+            Region::new(
+                self.pos.x,
+                bottom,
+                self.dim.w,
+                self.pos.y + self.dim.h - bottom,
+            ),
+            Region::new(self.pos.x, top, left - self.pos.x, bottom - top),
+            Region::new(
+                right,
+                top,
+                self.pos.x + self.dim.w - right,
+                bottom - top,
+            ),
+        ]
+        .into_iter()
+        .filter(|region| region.dim.w > 0 && region.dim.h > 0)
+        .collect()
+    }
+
     pub fn nearest_corner(
         &self,
         mut pos: Pos,
@@ -780,16 +893,30 @@ impl Ratio {
 pub struct Strut {
     pub window: Window,
     pub width: u32,
+    // the (inclusive) span along the edge that is reserved, in root coordinates
+    pub start: u32,
+    pub end: u32,
 }
 
 impl Strut {
     pub fn new(
         window: Window,
         width: u32,
+    ) -> Self {
+        Self::partial(window, width, 0, u32::MAX)
+    }
+
+    pub fn partial(
+        window: Window,
+        width: u32,
+        start: u32,
+        end: u32,
     ) -> Self {
         Self {
             window,
             width,
+            start,
+            end,
         }
     }
 }
@@ -811,3 +938,41 @@ impl Ord for Strut {
         other.width.cmp(&self.width)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rounded_without_radius_is_unchanged() {
+        let region = Region::new(10, 20, 300, 200);
+
+        assert_eq!(region.rounded(0), vec![region]);
+        assert_eq!(region.rounded(-4), vec![region]);
+    }
+
+    #[test]
+    fn rounded_insets_corner_rows_only() {
+        let region = Region::new(10, 20, 40, 30);
+        let strips = region.rounded(4);
+
+        assert_eq!(strips.len(), 9);
+        assert!(strips.contains(&Region::new(10, 24, 40, 22)));
+
+        strips.iter().for_each(|strip| {
+            assert!(strip.pos.x >= region.pos.x);
+            assert!(strip.pos.y >= region.pos.y);
+            assert!(strip.pos.x + strip.dim.w <= region.pos.x + region.dim.w);
+            assert!(strip.pos.y + strip.dim.h <= region.pos.y + region.dim.h);
+        });
+
+        let top = strips.iter().find(|strip| strip.pos.y == region.pos.y).unwrap();
+        let bottom = strips
+            .iter()
+            .find(|strip| strip.pos.y == region.pos.y + region.dim.h - 1)
+            .unwrap();
+
+        assert!(top.pos.x > region.pos.x);
+        assert_eq!((top.pos.x, top.dim.w), (bottom.pos.x, bottom.dim.w));
+    }
+}
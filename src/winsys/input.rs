@@ -8,15 +8,18 @@ use crate::window::Window;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::convert::TryFrom;
+use std::fmt;
 use std::hash::Hash;
 use std::hash::Hasher;
 use std::vec::Vec;
 
 use anyhow::anyhow;
+use strum::Display;
 use strum::EnumIter;
+use strum::EnumString;
 use strum::IntoEnumIterator;
 
-#[derive(Debug, Copy, Clone, PartialOrd, Ord, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialOrd, Ord, PartialEq, Eq, Hash)]
 pub enum Grip {
     Edge(Edge),
     Corner(Corner),
@@ -36,6 +39,13 @@ impl Grip {
     }
 }
 
+#[derive(Debug, Copy, Clone, PartialOrd, Ord, PartialEq, Eq, Hash)]
+pub enum CursorShape {
+    Normal,
+    Move,
+    Resize(Grip),
+}
+
 #[repr(u8)]
 #[derive(Debug, PartialEq, EnumIter, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
 pub enum Modifier {
@@ -65,6 +75,24 @@ impl TryFrom<&str> for Modifier {
     }
 }
 
+// the inverse of the names accepted when parsing
+impl fmt::Display for Modifier {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        f.write_str(match self {
+            Self::Ctrl => "C",
+            Self::Shift => "S",
+            Self::Alt => "A",
+            Self::AltGr => "AltGr",
+            Self::Super => "M",
+            Self::NumLock => "Num",
+            Self::ScrollLock => "Scroll",
+        })
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum Button {
     Left,
@@ -76,6 +104,46 @@ pub enum Button {
     Forward,
 }
 
+impl TryFrom<&str> for Button {
+    type Error = anyhow::Error;
+
+    fn try_from(val: &str) -> Result<Self> {
+        match val {
+            "left" => Ok(Self::Left),
+            "middle" => Ok(Self::Middle),
+            "right" => Ok(Self::Right),
+            "scrollup" => Ok(Self::ScrollUp),
+            "scrolldown" => Ok(Self::ScrollDown),
+            "backward" => Ok(Self::Backward),
+            "forward" => Ok(Self::Forward),
+            _ => Err(anyhow!("unable to resolve \"{}\" to button", val)),
+        }
+    }
+}
+
+impl fmt::Display for Button {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        f.write_str(match self {
+            Self::Left => "left",
+            Self::Middle => "middle",
+            Self::Right => "right",
+            Self::ScrollUp => "scrollup",
+            Self::ScrollDown => "scrolldown",
+            Self::Backward => "backward",
+            Self::Forward => "forward",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum KeyEventKind {
+    Press,
+    Release,
+}
+
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 pub enum MouseEventKind {
     Press,
@@ -97,6 +165,22 @@ pub struct MouseInput {
     pub modifiers: HashSet<Modifier>,
 }
 
+impl fmt::Display for MouseInput {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        let mut modifiers: Vec<&Modifier> = self.modifiers.iter().collect();
+        modifiers.sort();
+
+        for modifier in modifiers {
+            write!(f, "{}-", modifier)?;
+        }
+
+        write!(f, "{}", self.button)
+    }
+}
+
 impl Hash for MouseInput {
     fn hash<H: Hasher>(
         &self,
@@ -118,7 +202,7 @@ pub struct MouseEvent {
     pub root_rpos: Pos,
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, EnumString, Display)]
 pub enum Key {
     Any,
     Backspace,
@@ -307,6 +391,47 @@ pub struct KeyInput {
     pub modifiers: HashSet<Modifier>,
 }
 
+// modifiers and the key name are separated by dashes, e.g. "A-S-Return"
+impl TryFrom<&str> for KeyInput {
+    type Error = anyhow::Error;
+
+    fn try_from(val: &str) -> Result<Self> {
+        let mut parts: Vec<&str> = val.split('-').collect();
+
+        let key = match parts.pop() {
+            Some(key) if !key.is_empty() => key,
+            _ => return Err(anyhow!("missing key in \"{}\"", val)),
+        };
+
+        Ok(Self {
+            key: key
+                .parse()
+                .map_err(|_| anyhow!("unable to resolve \"{}\" to key", key))?,
+            modifiers: parts
+                .into_iter()
+                .map(Modifier::try_from)
+                .collect::<Result<HashSet<Modifier>>>()?,
+        })
+    }
+}
+
+// formatted as parsed, e.g. "A-S-Return"
+impl fmt::Display for KeyInput {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        let mut modifiers: Vec<&Modifier> = self.modifiers.iter().collect();
+        modifiers.sort();
+
+        for modifier in modifiers {
+            write!(f, "{}-", modifier)?;
+        }
+
+        write!(f, "{}", self.key)
+    }
+}
+
 impl Hash for KeyInput {
     fn hash<H: Hasher>(
         &self,
@@ -322,6 +447,7 @@ impl Hash for KeyInput {
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct KeyEvent {
+    pub kind: KeyEventKind,
     pub input: KeyInput,
     pub window: Option<Window>,
 }
@@ -15,6 +15,8 @@ use std::vec::Vec;
 #[derive(Debug, Clone)]
 pub struct Screen {
     number: Cell<usize>,
+    name: RefCell<String>,
+    gap: Cell<u32>,
     full_region: Cell<Region>,
     placeable_region: Cell<Region>,
     windows: RefCell<HashMap<Window, Vec<Edge>>>,
@@ -38,6 +40,8 @@ impl Screen {
     ) -> Self {
         Screen::init(Self {
             number: Cell::new(number),
+            name: RefCell::new(String::new()),
+            gap: Cell::new(0),
             full_region: Cell::new(region),
             placeable_region: Cell::new(region),
             windows: RefCell::new(HashMap::new()),
@@ -76,6 +80,30 @@ impl Screen {
         self.number.set(number)
     }
 
+    // the name of the output (e.g., DP-1) that makes up the screen
+    #[inline]
+    pub fn name(&self) -> String {
+        self.name.borrow().to_owned()
+    }
+
+    #[inline]
+    pub fn set_name(
+        &self,
+        name: impl Into<String>,
+    ) {
+        self.name.replace(name.into());
+    }
+
+    // the gap left between the placeable region and the screen edges (or struts)
+    #[inline]
+    pub fn set_gap(
+        &self,
+        gap: u32,
+    ) {
+        self.gap.set(gap);
+        self.compute_placeable_region();
+    }
+
     #[inline]
     pub fn show_and_yield_struts(
         &self,
@@ -92,6 +120,12 @@ impl Screen {
         self.full_region.get()
     }
 
+    #[inline]
+    pub fn is_portrait(&self) -> bool {
+        let region = self.full_region.get();
+        region.dim.h > region.dim.w
+    }
+
     #[inline]
     pub fn placeable_region(&self) -> Region {
         self.placeable_region.get()
@@ -110,30 +144,120 @@ impl Screen {
         let mut region = self.full_region.get();
 
         if self.showing_struts.get() {
-            let struts = self.struts.borrow();
-
-            if let Some(strut) = struts.get(&Edge::Left).unwrap().last() {
-                region.pos.x += strut.width as i32;
-                region.dim.w -= strut.width as i32;
+            if let Some(width) = self.max_strut_val(Edge::Left) {
+                region.pos.x += width as i32;
+                region.dim.w -= width as i32;
             }
 
-            if let Some(strut) = struts.get(&Edge::Right).unwrap().last() {
-                region.dim.w -= strut.width as i32;
+            if let Some(width) = self.max_strut_val(Edge::Right) {
+                region.dim.w -= width as i32;
             }
 
-            if let Some(strut) = struts.get(&Edge::Top).unwrap().last() {
-                region.pos.y += strut.width as i32;
-                region.dim.h -= strut.width as i32;
+            if let Some(width) = self.max_strut_val(Edge::Top) {
+                region.pos.y += width as i32;
+                region.dim.h -= width as i32;
             }
 
-            if let Some(strut) = struts.get(&Edge::Bottom).unwrap().last() {
-                region.dim.h -= strut.width as i32;
+            if let Some(width) = self.max_strut_val(Edge::Bottom) {
+                region.dim.h -= width as i32;
             }
         }
 
+        let gap = self.gap.get() as i32;
+
+        if region.dim.w > 2 * gap && region.dim.h > 2 * gap {
+            region.pos.x += gap;
+            region.pos.y += gap;
+            region.dim.w -= 2 * gap;
+            region.dim.h -= 2 * gap;
+        }
+
         self.placeable_region.set(region);
     }
 
+    // the width of a strut reserved at an edge of the root region that falls within
+    // the screen, if its span overlaps the screen at all
+    pub fn strut_width(
+        &self,
+        edge: Edge,
+        strut: &Strut,
+        root: Region,
+    ) -> Option<u32> {
+        let region = self.full_region.get();
+
+        let (span_start, span_end, extent) = match edge {
+            Edge::Left | Edge::Right => (region.pos.y, region.pos.y + region.dim.h, region.dim.w),
+            Edge::Top | Edge::Bottom => (region.pos.x, region.pos.x + region.dim.w, region.dim.h),
+        };
+
+        if i64::from(strut.end) < i64::from(span_start)
+            || i64::from(strut.start) >= i64::from(span_end)
+        {
+            return None;
+        }
+
+        let width = strut.width as i32;
+        let covered = match edge {
+            Edge::Left => root.pos.x + width - region.pos.x,
+            Edge::Right => region.pos.x + region.dim.w - (root.pos.x + root.dim.w - width),
+            Edge::Top => root.pos.y + width - region.pos.y,
+            Edge::Bottom => region.pos.y + region.dim.h - (root.pos.y + root.dim.h - width),
+        };
+
+        if covered > 0 {
+            Some(covered.min(extent) as u32)
+        } else {
+            None
+        }
+    }
+
+    // a dock that only reserves its own thickness while sitting further from the
+    // edge (e.g., a second bar stacked below the first) has its strut extended to
+    // its far side, so that stacked docks accumulate rather than mask one another
+    pub fn stacked_strut_width(
+        &self,
+        edge: Edge,
+        width: u32,
+        region: Region,
+    ) -> u32 {
+        let full_region = self.full_region.get();
+
+        if full_region.intersect(region).is_none() {
+            return width;
+        }
+
+        let (near, far, extent) = match edge {
+            Edge::Left => (
+                region.pos.x - full_region.pos.x,
+                region.pos.x + region.dim.w - full_region.pos.x,
+                full_region.dim.w,
+            ),
+            Edge::Right => (
+                full_region.pos.x + full_region.dim.w - (region.pos.x + region.dim.w),
+                full_region.pos.x + full_region.dim.w - region.pos.x,
+                full_region.dim.w,
+            ),
+            Edge::Top => (
+                region.pos.y - full_region.pos.y,
+                region.pos.y + region.dim.h - full_region.pos.y,
+                full_region.dim.h,
+            ),
+            Edge::Bottom => (
+                full_region.pos.y + full_region.dim.h - (region.pos.y + region.dim.h),
+                full_region.pos.y + full_region.dim.h - region.pos.y,
+                full_region.dim.h,
+            ),
+        };
+
+        // a strut that reaches into the dock already accounts for what lies before it
+        if near <= 0 || (width as i32) > near {
+            return width;
+        }
+
+        width.max(far.min(extent) as u32)
+    }
+
+    // each window contributes at most one strut per edge; adding another replaces it
     #[inline]
     pub fn add_strut(
         &self,
@@ -144,14 +268,17 @@ impl Screen {
         let mut struts = self.struts.borrow_mut();
         let strut = struts.get_mut(&edge).unwrap();
 
+        strut.retain(|s| s.window != window);
+
         let index = strut.binary_search_by(|s| s.width.cmp(&width));
         strut.insert(index.unwrap_or_else(|e| e), Strut::new(window, width));
 
-        self.windows
-            .borrow_mut()
-            .entry(window)
-            .or_insert(vec![edge])
-            .push(edge);
+        let mut windows = self.windows.borrow_mut();
+        let edges = windows.entry(window).or_default();
+
+        if !edges.contains(&edge) {
+            edges.push(edge);
+        }
     }
 
     #[inline]
@@ -189,6 +316,7 @@ impl Screen {
         self.windows.borrow_mut().remove(&window);
     }
 
+    // only the strut at the given edge is replaced, those at other edges are kept
     #[inline]
     pub fn update_strut(
         &self,
@@ -196,10 +324,10 @@ impl Screen {
         window: Window,
         width: u32,
     ) {
-        self.remove_window_strut(window);
         self.add_strut(edge, window, width);
     }
 
+    // the space reserved at an edge, i.e., the widest strut of all windows there
     #[inline]
     pub fn max_strut_val(
         &self,
@@ -213,6 +341,21 @@ impl Screen {
             .map(|strut| strut.width)
     }
 
+    // the strut widths each window contributes to an edge, widest last
+    #[inline]
+    pub fn strut_contributions(
+        &self,
+        edge: Edge,
+    ) -> Vec<(Window, u32)> {
+        self.struts
+            .borrow()
+            .get(&edge)
+            .unwrap()
+            .iter()
+            .map(|strut| (strut.window, strut.width))
+            .collect()
+    }
+
     #[inline]
     pub fn has_strut_window(
         &self,
@@ -269,3 +412,92 @@ impl Screen {
         self.placeable_region.get().occludes(region)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screen() -> Screen {
+        Screen::new(Region::new(0, 0, 1920, 1080), 0)
+    }
+
+    #[test]
+    fn smaller_strut_is_not_masked_after_removal() {
+        let screen = screen();
+
+        screen.add_strut(Edge::Top, 1, 20);
+        screen.add_strut(Edge::Top, 2, 40);
+        screen.compute_placeable_region();
+
+        assert_eq!(screen.max_strut_val(Edge::Top), Some(40));
+        assert_eq!(screen.placeable_region(), Region::new(0, 40, 1920, 1040));
+
+        screen.remove_window_strut(2);
+        screen.compute_placeable_region();
+
+        assert_eq!(screen.max_strut_val(Edge::Top), Some(20));
+        assert_eq!(screen.placeable_region(), Region::new(0, 20, 1920, 1060));
+
+        screen.remove_window_strut(1);
+        screen.compute_placeable_region();
+
+        assert_eq!(screen.max_strut_val(Edge::Top), None);
+        assert_eq!(screen.placeable_region(), screen.full_region());
+    }
+
+    #[test]
+    fn readding_strut_replaces_contribution() {
+        let screen = screen();
+
+        screen.add_strut(Edge::Top, 1, 40);
+        screen.add_strut(Edge::Top, 2, 20);
+        screen.add_strut(Edge::Top, 1, 10);
+
+        assert_eq!(screen.strut_contributions(Edge::Top), vec![(1, 10), (2, 20)]);
+        assert_eq!(screen.max_strut_val(Edge::Top), Some(20));
+    }
+
+    #[test]
+    fn updating_strut_keeps_other_edges() {
+        let screen = screen();
+
+        screen.add_strut(Edge::Top, 1, 20);
+        screen.add_strut(Edge::Left, 1, 30);
+        screen.update_strut(Edge::Top, 1, 25);
+
+        assert_eq!(screen.max_strut_val(Edge::Top), Some(25));
+        assert_eq!(screen.max_strut_val(Edge::Left), Some(30));
+
+        screen.remove_window_strut(1);
+
+        assert!(!screen.has_strut_window(1));
+        assert_eq!(screen.max_strut_val(Edge::Top), None);
+        assert_eq!(screen.max_strut_val(Edge::Left), None);
+    }
+
+    #[test]
+    fn stacked_docks_accumulate() {
+        let screen = screen();
+
+        let top = Region::new(0, 0, 1920, 20);
+        let below = Region::new(0, 20, 1920, 20);
+
+        assert_eq!(screen.stacked_strut_width(Edge::Top, 20, top), 20);
+        assert_eq!(screen.stacked_strut_width(Edge::Top, 20, below), 40);
+        assert_eq!(screen.stacked_strut_width(Edge::Top, 40, below), 40);
+
+        screen.add_strut(Edge::Top, 1, screen.stacked_strut_width(Edge::Top, 20, top));
+        screen.add_strut(Edge::Top, 2, screen.stacked_strut_width(Edge::Top, 20, below));
+        screen.compute_placeable_region();
+
+        assert_eq!(screen.placeable_region(), Region::new(0, 40, 1920, 1040));
+
+        let bottom = Region::new(0, 1040, 1920, 20);
+
+        assert_eq!(screen.stacked_strut_width(Edge::Bottom, 20, bottom), 40);
+        assert_eq!(
+            screen.stacked_strut_width(Edge::Top, 20, Region::new(2000, 20, 100, 20)),
+            20
+        );
+    }
+}
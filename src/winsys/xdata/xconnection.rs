@@ -2,6 +2,7 @@ use crate::connection::Connection;
 use crate::connection::Pid;
 use crate::event::Event;
 use crate::event::PropertyKind;
+use crate::event::RequestSource;
 use crate::event::StackMode;
 use crate::event::ToggleAction;
 use crate::geometry::Corner;
@@ -15,10 +16,12 @@ use crate::geometry::Strut;
 use crate::hints::Hints;
 use crate::hints::SizeHints;
 use crate::input::Button;
+use crate::input::CursorShape;
 use crate::input::Modifier;
 use crate::input::Grip;
 use crate::input::Key;
 use crate::input::KeyEvent;
+use crate::input::KeyEventKind;
 use crate::input::MouseEventKind;
 use crate::input::MouseInputTarget;
 use crate::input::KeyInput;
@@ -36,7 +39,12 @@ use std::cell::RefCell;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::convert::TryFrom;
+use std::env;
+use std::os::unix::io::RawFd;
 use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
 
 use x11rb::connection;
 use x11rb::cursor::Handle as CursorHandle;
@@ -44,6 +52,12 @@ use x11rb::errors::ReplyError;
 use x11rb::properties;
 use x11rb::protocol;
 use x11rb::protocol::randr;
+use x11rb::protocol::shape;
+use x11rb::protocol::shape::ConnectionExt as _;
+use x11rb::protocol::xfixes;
+use x11rb::protocol::xfixes::ConnectionExt as _;
+use x11rb::protocol::xkb;
+use x11rb::protocol::xkb::ConnectionExt as _;
 use x11rb::protocol::xproto;
 use x11rb::protocol::xproto::ConnectionExt;
 use x11rb::protocol::xproto::EventMask;
@@ -59,6 +73,23 @@ use strum::*;
 
 type Atom = u32;
 
+const REPLACE_TIMEOUT: Duration = Duration::from_secs(5);
+const REPLACE_POLL_INTERVAL: Duration = Duration::from_millis(10);
+const RETRY_MAX_INTERVAL: Duration = Duration::from_millis(500);
+
+const CURSORS: [(CursorShape, &str); 10] = [
+    (CursorShape::Normal, "left_ptr"),
+    (CursorShape::Move, "fleur"),
+    (CursorShape::Resize(Grip::Corner(Corner::TopLeft)), "top_left_corner"),
+    (CursorShape::Resize(Grip::Corner(Corner::TopRight)), "top_right_corner"),
+    (CursorShape::Resize(Grip::Corner(Corner::BottomLeft)), "bottom_left_corner"),
+    (CursorShape::Resize(Grip::Corner(Corner::BottomRight)), "bottom_right_corner"),
+    (CursorShape::Resize(Grip::Edge(Edge::Left)), "left_side"),
+    (CursorShape::Resize(Grip::Edge(Edge::Right)), "right_side"),
+    (CursorShape::Resize(Grip::Edge(Edge::Top)), "top_side"),
+    (CursorShape::Resize(Grip::Edge(Edge::Bottom)), "bottom_side"),
+];
+
 x11rb::atom_manager! {
     pub Atoms: AtomsCookie {
         Any,
@@ -82,6 +113,8 @@ x11rb::atom_manager! {
 
         // ICCCM window manager properties
         WM_STATE,
+        MANAGER,
+        RESOURCE_MANAGER,
         WM_ICON_SIZE,
 
         // EWMH root properties
@@ -170,19 +203,37 @@ x11rb::atom_manager! {
         _NET_SYSTEM_TRAY_S0,
         _XEMBED,
         _XEMBED_INFO,
+
+        // window manager specific properties
+        _WZRD_CLIENT_MONITOR,
+        _WZRD_WORKSPACE_OCCUPANCY,
+        _WZRD_FOCUSED_TITLE,
+        _WZRD_LAYOUT_SYMBOL,
     }
 }
 
 pub struct XConnection<'conn, Conn: connection::Connection> {
     conn: &'conn Conn,
+    fd: RawFd,
     atoms: Atoms,
     type_map: HashMap<Atom, WindowType>,
     state_map: HashMap<Atom, WindowState>,
     screen: xproto::Screen,
+    screen_num: usize,
     check_window: Window,
+    manager_selection: Atom,
     background_gc: xproto::Gcontext,
-    database: Option<Database>,
+    outline_gc: xproto::Gcontext,
+    outline: Cell<Option<Region>>,
+    text_gc: xproto::Gcontext,
+    text_glyph: Dim,
+    text_ascent: i32,
+    hint_windows: RefCell<Vec<Window>>,
+    debug_hud: Cell<Option<(Window, Region)>>,
+    database: RefCell<Option<Database>>,
+    cursors: RefCell<HashMap<CursorShape, xproto::Cursor>>,
     confined_to: Cell<Option<Window>>,
+    barriers: RefCell<Vec<xfixes::Barrier>>,
     keys: RefCell<HashMap<u8, Key>>,
     keycodes: RefCell<HashMap<Key, u8>>,
     root_event_mask: EventMask,
@@ -190,28 +241,21 @@ pub struct XConnection<'conn, Conn: connection::Connection> {
     frame_event_mask: EventMask,
     mouse_event_mask: EventMask,
     regrab_event_mask: EventMask,
+    shape_supported: bool,
+    barriers_supported: bool,
 }
 
 impl<'conn, Conn: connection::Connection> XConnection<'conn, Conn> {
     pub fn new(
         conn: &'conn Conn,
+        fd: RawFd,
         screen_num: usize,
+        replace: bool,
+        retry: Duration,
     ) -> Result<Self> {
         let screen = conn.setup().roots[screen_num].clone();
         let root = screen.root;
 
-        let aux = xproto::ChangeWindowAttributesAux::default()
-            .event_mask(EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY);
-
-        let res = conn.change_window_attributes(screen.root, &aux)?.check();
-        if let Err(ReplyError::X11Error(err)) = res {
-            if err.error_kind == ErrorKind::Access {
-                return Err(anyhow!("another window manager is already running"));
-            } else {
-                return Err(anyhow!("unable to set up window manager"));
-            }
-        }
-
         let atoms = Atoms::new(conn)?.reply()?;
         let check_window = conn.generate_id()?;
 
@@ -258,9 +302,48 @@ impl<'conn, Conn: connection::Connection> XConnection<'conn, Conn> {
             0,
             xproto::WindowClass::INPUT_ONLY,
             x11rb::COPY_FROM_PARENT,
-            &xproto::CreateWindowAux::default().override_redirect(1),
+            &xproto::CreateWindowAux::default()
+                .override_redirect(1)
+                .event_mask(EventMask::PROPERTY_CHANGE),
+        )?;
+
+        let manager_selection = Self::acquire_manager_selection(
+            conn,
+            &atoms,
+            root,
+            check_window,
+            screen_num,
+            replace,
+            retry,
+        )?;
+
+        conn.change_window_attributes(
+            check_window,
+            &xproto::ChangeWindowAttributesAux::default().event_mask(EventMask::NO_EVENT),
         )?;
 
+        let aux = xproto::ChangeWindowAttributesAux::default()
+            .event_mask(EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY);
+
+        // a replaced (or exiting) window manager may still hold the redirect for a
+        // moment after having released its selection
+        let deadline = Instant::now() + if replace { REPLACE_TIMEOUT.max(retry) } else { retry };
+        let mut interval = REPLACE_POLL_INTERVAL;
+
+        loop {
+            match conn.change_window_attributes(root, &aux)?.check() {
+                Err(ReplyError::X11Error(err)) if err.error_kind == ErrorKind::Access => {
+                    if !Self::back_off(deadline, &mut interval) {
+                        return Err(anyhow!("another window manager is already running"));
+                    }
+                },
+                Err(ReplyError::X11Error(_)) => {
+                    return Err(anyhow!("unable to set up window manager"));
+                },
+                _ => break,
+            }
+        }
+
         conn.map_window(check_window)?;
         conn.configure_window(
             check_window,
@@ -275,22 +358,84 @@ impl<'conn, Conn: connection::Connection> XConnection<'conn, Conn> {
                 | randr::NotifyMask::SCREEN_CHANGE,
         )?;
 
+        // only report key releases when keys are physically released
+        if conn
+            .xkb_use_extension(1, 0)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .filter(|reply| reply.supported)
+            .is_some()
+        {
+            drop(conn.xkb_per_client_flags(
+                xkb::ID::USE_CORE_KBD.into(),
+                xkb::PerClientFlag::DETECTABLE_AUTO_REPEAT,
+                xkb::PerClientFlag::DETECTABLE_AUTO_REPEAT,
+                0u32,
+                0u32,
+                0u32,
+            ));
+        }
+
+        let shape_supported = conn
+            .extension_information(shape::X11_EXTENSION_NAME)
+            .ok()
+            .flatten()
+            .is_some();
+
+        // pointer barriers were introduced with version 5 of XFixes
+        let barriers_supported = conn
+            .extension_information(xfixes::X11_EXTENSION_NAME)
+            .ok()
+            .flatten()
+            .and_then(|_| conn.xfixes_query_version(5, 0).ok())
+            .and_then(|cookie| cookie.reply().ok())
+            .filter(|reply| reply.major_version >= 5)
+            .is_some();
+
         let background_gc = conn.generate_id()?;
         conn.create_gc(background_gc, screen.root, &xproto::CreateGCAux::default())?;
 
-        let database = Database::new_from_default(conn).ok();
-        if let Some(ref database) = database {
-            drop(
-                CursorHandle::new(conn, screen_num, &database).map(|cookie| {
-                    cookie.reply().map(|reply| {
-                        let aux = xproto::ChangeWindowAttributesAux::default()
-                            .cursor(reply.load_cursor(conn, "left_ptr").ok());
+        // drawing the outline a second time erases it again
+        let outline_gc = conn.generate_id()?;
+        conn.create_gc(
+            outline_gc,
+            screen.root,
+            &xproto::CreateGCAux::default()
+                .function(xproto::GX::XOR)
+                .foreground(screen.white_pixel ^ screen.black_pixel)
+                .line_width(2)
+                .subwindow_mode(xproto::SubwindowMode::INCLUDE_INFERIORS),
+        )?;
 
-                        drop(conn.change_window_attributes(screen.root, &aux));
-                    })
-                }),
-            );
-        }
+        // text is drawn with the core font every server provides
+        let text_font = conn.generate_id()?;
+        conn.open_font(text_font, b"fixed")?;
+
+        let (text_glyph, text_ascent) = conn
+            .query_font(text_font)?
+            .reply()
+            .map(|font| {
+                (
+                    Dim {
+                        w: font.max_bounds.character_width as i32,
+                        h: (font.font_ascent + font.font_descent) as i32,
+                    },
+                    font.font_ascent as i32,
+                )
+            })
+            .unwrap_or((Dim { w: 6, h: 13 }, 10));
+
+        let text_gc = conn.generate_id()?;
+        conn.create_gc(
+            text_gc,
+            screen.root,
+            &xproto::CreateGCAux::default()
+                .font(text_font)
+                .foreground(screen.white_pixel)
+                .background(screen.black_pixel),
+        )?;
+
+        let database = Database::new_from_default(conn).ok();
 
         let keys = RefCell::new(HashMap::new());
         let keycodes = RefCell::new(HashMap::new());
@@ -319,14 +464,26 @@ impl<'conn, Conn: connection::Connection> XConnection<'conn, Conn> {
 
         Self::init(Self {
             conn,
+            fd,
             atoms,
             type_map,
             state_map,
             screen,
+            screen_num,
             check_window,
+            manager_selection,
             background_gc,
-            database,
+            outline_gc,
+            outline: Cell::new(None),
+            text_gc,
+            text_glyph,
+            text_ascent,
+            hint_windows: RefCell::new(Vec::new()),
+            debug_hud: Cell::new(None),
+            database: RefCell::new(database),
+            cursors: RefCell::new(HashMap::new()),
             confined_to: Cell::new(None),
+            barriers: RefCell::new(Vec::new()),
             keys,
             keycodes,
             root_event_mask,
@@ -334,14 +491,224 @@ impl<'conn, Conn: connection::Connection> XConnection<'conn, Conn> {
             frame_event_mask,
             mouse_event_mask,
             regrab_event_mask,
+            shape_supported,
+            barriers_supported,
         })
     }
 
     #[inline]
     fn init(connection: Self) -> Result<Self> {
+        connection.load_cursors();
         Ok(connection)
     }
 
+    // the cursor theme is taken from XCURSOR_THEME, falling back to Xcursor.theme; the
+    // cursor handle itself already considers XCURSOR_SIZE, Xcursor.size and Xft.dpi
+    fn load_cursors(&self) {
+        let database = self.database.borrow();
+        let setting = |name: &str| {
+            database
+                .as_ref()
+                .and_then(|database| database.get_string(name, ""))
+                .map(str::to_owned)
+        };
+
+        let mut settings = String::new();
+
+        if let Some(theme) = env::var("XCURSOR_THEME")
+            .ok()
+            .filter(|theme| !theme.is_empty())
+            .or_else(|| setting("Xcursor.theme"))
+        {
+            settings.push_str(&format!("Xcursor.theme: {}\n", theme));
+        }
+
+        for name in ["Xcursor.size", "Xft.dpi"] {
+            if let Some(value) = setting(name) {
+                settings.push_str(&format!("{}: {}\n", name, value));
+            }
+        }
+
+        let handle = match CursorHandle::new(
+            self.conn,
+            self.screen_num,
+            &Database::new_from_data(settings.as_bytes()),
+        )
+        .ok()
+        .and_then(|cookie| cookie.reply().ok())
+        {
+            Some(handle) => handle,
+            None => return,
+        };
+
+        let mut cursors = self.cursors.borrow_mut();
+
+        for (shape, name) in CURSORS {
+            if let Ok(cursor) = handle.load_cursor(self.conn, name) {
+                if let Some(previous) = cursors.insert(shape, cursor) {
+                    drop(self.conn.free_cursor(previous));
+                }
+            }
+        }
+
+        let aux = xproto::ChangeWindowAttributesAux::default()
+            .cursor(cursors.get(&CursorShape::Normal).copied());
+
+        drop(self.conn.change_window_attributes(self.screen.root, &aux));
+    }
+
+    // sleeps for the current interval (doubling it for the next attempt), unless the
+    // deadline has passed
+    fn back_off(
+        deadline: Instant,
+        interval: &mut Duration,
+    ) -> bool {
+        let now = Instant::now();
+
+        if now >= deadline {
+            return false;
+        }
+
+        thread::sleep((*interval).min(deadline - now));
+        *interval = (*interval * 2).min(RETRY_MAX_INTERVAL);
+
+        true
+    }
+
+    // ICCCM 2.8: take ownership of the WM_Sn manager selection, waiting for any
+    // previous owner to give up its selection window when replacing it; without
+    // replacing, an owner that is on its way out is given the retry window to exit
+    fn acquire_manager_selection(
+        conn: &'conn Conn,
+        atoms: &Atoms,
+        root: Window,
+        owner: Window,
+        screen_num: usize,
+        replace: bool,
+        retry: Duration,
+    ) -> Result<Atom> {
+        let selection = conn
+            .intern_atom(false, format!("WM_S{}", screen_num).as_bytes())?
+            .reply()?
+            .atom;
+
+        let mut previous_owner = conn.get_selection_owner(selection)?.reply()?.owner;
+
+        if !replace {
+            let deadline = Instant::now() + retry;
+            let mut interval = REPLACE_POLL_INTERVAL;
+
+            while previous_owner != x11rb::NONE {
+                if !Self::back_off(deadline, &mut interval) {
+                    return Err(anyhow!(
+                        "another window manager is already running (use --replace to replace it)"
+                    ));
+                }
+
+                previous_owner = conn.get_selection_owner(selection)?.reply()?.owner;
+            }
+        }
+
+        if previous_owner != x11rb::NONE {
+            conn.change_window_attributes(
+                previous_owner,
+                &xproto::ChangeWindowAttributesAux::default()
+                    .event_mask(EventMask::STRUCTURE_NOTIFY),
+            )?;
+        }
+
+        // the selection must not be acquired at CurrentTime, so obtain a server
+        // timestamp through a zero-length property change on the owner window
+        conn.change_property8(
+            xproto::PropMode::APPEND,
+            owner,
+            atoms.WM_NAME,
+            xproto::AtomEnum::STRING,
+            &[],
+        )?;
+
+        conn.flush()?;
+
+        let timestamp = loop {
+            if let XEvent::PropertyNotify(event) = conn.wait_for_event()? {
+                if event.window == owner {
+                    break event.time;
+                }
+            }
+        };
+
+        conn.set_selection_owner(owner, selection, timestamp)?;
+
+        if conn.get_selection_owner(selection)?.reply()?.owner != owner {
+            return Err(anyhow!("unable to acquire the window manager selection"));
+        }
+
+        if previous_owner != x11rb::NONE {
+            let deadline = Instant::now() + REPLACE_TIMEOUT;
+            let mut destroyed = false;
+
+            conn.flush()?;
+
+            while !destroyed && Instant::now() < deadline {
+                match conn.poll_for_event()? {
+                    Some(XEvent::DestroyNotify(event)) => {
+                        destroyed = event.window == previous_owner;
+                    },
+                    Some(_) => {},
+                    None => thread::sleep(REPLACE_POLL_INTERVAL),
+                }
+            }
+
+            if !destroyed {
+                conn.kill_client(previous_owner)?;
+            }
+        }
+
+        let event = xproto::ClientMessageEvent {
+            response_type: CLIENT_MESSAGE_EVENT,
+            format: 32,
+            sequence: 0,
+            window: root,
+            type_: atoms.MANAGER,
+            data: [timestamp, selection, owner, 0, 0].into(),
+        };
+
+        conn.send_event(false, root, EventMask::STRUCTURE_NOTIFY, event)?;
+
+        Ok(selection)
+    }
+
+    fn set_window_shape(
+        &self,
+        window: Window,
+        kind: shape::SK,
+        regions: &[Region],
+    ) {
+        if !self.shape_supported {
+            return;
+        }
+
+        drop(
+            self.conn.shape_rectangles(
+                shape::SO::SET,
+                kind,
+                xproto::ClipOrdering::UNSORTED,
+                window,
+                0,
+                0,
+                &regions
+                    .iter()
+                    .map(|region| xproto::Rectangle {
+                        x: region.pos.x as i16,
+                        y: region.pos.y as i16,
+                        width: region.dim.w as u16,
+                        height: region.dim.h as u16,
+                    })
+                    .collect::<Vec<xproto::Rectangle>>(),
+            ),
+        );
+    }
+
     pub fn window_is_any_of_types(
         &self,
         window: Window,
@@ -880,6 +1247,35 @@ impl<'conn, Conn: connection::Connection> XConnection<'conn, Conn> {
         }
     }
 
+    #[inline]
+    fn translate_event(
+        &self,
+        event: XEvent,
+    ) -> Option<Event> {
+        match event {
+            XEvent::ButtonPress(e) => self.on_button_press(&e),
+            XEvent::ButtonRelease(e) => self.on_button_release(&e),
+            XEvent::MotionNotify(e) => self.on_motion_notify(&e),
+            XEvent::KeyPress(e) => self.on_key(KeyEventKind::Press, &e),
+            XEvent::KeyRelease(e) => self.on_key(KeyEventKind::Release, &e),
+            XEvent::MapRequest(e) => self.on_map_request(&e),
+            XEvent::MapNotify(e) => self.on_map_notify(&e),
+            XEvent::EnterNotify(e) => self.on_enter_notify(&e),
+            XEvent::LeaveNotify(e) => self.on_leave_notify(&e),
+            XEvent::DestroyNotify(e) => self.on_destroy_notify(&e),
+            XEvent::Expose(e) => self.on_expose(&e),
+            XEvent::UnmapNotify(e) => self.on_unmap_notify(&e),
+            XEvent::ConfigureRequest(e) => self.on_configure_request(&e),
+            XEvent::ConfigureNotify(e) => self.on_configure_notify(&e),
+            XEvent::PropertyNotify(e) => self.on_property_notify(&e),
+            XEvent::ClientMessage(e) => self.on_client_message(&e),
+            XEvent::MappingNotify(e) => self.on_mapping_notify(&e),
+            XEvent::RandrNotify(e) => self.on_randr_notify(&e),
+            XEvent::SelectionClear(e) => self.on_selection_clear(&e),
+            _ => None,
+        }
+    }
+
     #[inline]
     fn on_button_press(
         &self,
@@ -1073,12 +1469,14 @@ impl<'conn, Conn: connection::Connection> XConnection<'conn, Conn> {
     }
 
     #[inline]
-    fn on_key_press(
+    fn on_key(
         &self,
+        kind: KeyEventKind,
         event: &xproto::KeyPressEvent,
     ) -> Option<Event> {
         Some(Event::Key {
             event: KeyEvent {
+                kind,
                 input: KeyInput {
                     key: self.get_key(event.detail),
                     modifiers: {
@@ -1184,6 +1582,18 @@ impl<'conn, Conn: connection::Connection> XConnection<'conn, Conn> {
         })
     }
 
+    #[inline]
+    fn on_selection_clear(
+        &self,
+        event: &xproto::SelectionClearEvent,
+    ) -> Option<Event> {
+        if event.owner == self.check_window && event.selection == self.manager_selection {
+            Some(Event::ManagerReplaced)
+        } else {
+            None
+        }
+    }
+
     #[inline]
     fn on_expose(
         &self,
@@ -1330,6 +1740,17 @@ impl<'conn, Conn: connection::Connection> XConnection<'conn, Conn> {
         &self,
         event: &xproto::PropertyNotifyEvent,
     ) -> Option<Event> {
+        if event.window == self.screen.root && event.atom == self.atoms.RESOURCE_MANAGER {
+            self.database.replace(Database::new_from_default(self.conn).ok());
+            self.load_cursors();
+
+            return Some(Event::Property {
+                window: event.window,
+                kind: PropertyKind::Resources,
+                on_root: true,
+            });
+        }
+
         if event.state == xproto::Property::NEW_VALUE {
             if event.atom == self.atoms.WM_NAME || event.atom == self.atoms._NET_WM_NAME {
                 return Some(Event::Property {
@@ -1356,6 +1777,14 @@ impl<'conn, Conn: connection::Connection> XConnection<'conn, Conn> {
             }
         }
 
+        if event.atom == self.atoms._NET_WM_OPAQUE_REGION {
+            return Some(Event::Property {
+                window: event.window,
+                kind: PropertyKind::OpaqueRegion,
+                on_root: event.window == self.screen.root,
+            });
+        }
+
         if event.atom == self.atoms._NET_WM_STRUT || event.atom == self.atoms._NET_WM_STRUT_PARTIAL
         {
             return Some(Event::Property {
@@ -1385,20 +1814,17 @@ impl<'conn, Conn: connection::Connection> XConnection<'conn, Conn> {
                 return None;
             }
 
-            let mut state = None;
-
-            for i in 1..=2 {
-                if state.is_none() {
-                    if data[i] != 0 {
-                        state = self.get_window_state_from_atom(data[i] as Atom);
-                    }
-                }
-            }
+            // both properties may be set, e.g. to maximize along either axis at once
+            let states: Vec<WindowState> = data[1..=2]
+                .iter()
+                .filter(|&&atom| atom != 0)
+                .filter_map(|&atom| self.get_window_state_from_atom(atom as Atom))
+                .collect();
 
-            if let Some(state) = state {
+            if !states.is_empty() {
                 return Some(Event::StateRequest {
                     window: event.window,
-                    state,
+                    states,
                     action: match data[0] {
                         0 => ToggleAction::Remove,
                         1 => ToggleAction::Add,
@@ -1469,14 +1895,18 @@ impl<'conn, Conn: connection::Connection> XConnection<'conn, Conn> {
                 on_root: event.window == self.screen.root,
             });
         } else if event.type_ == self.atoms._NET_ACTIVE_WINDOW {
-            if let Some(&source) = data.get(0) {
-                if source <= 2 {
-                    return Some(Event::FocusRequest {
-                        window: event.window,
-                        on_root: event.window == self.screen.root,
-                    });
-                }
-            }
+            let source = match data.get(0) {
+                Some(0) => RequestSource::Legacy,
+                Some(1) => RequestSource::Application,
+                Some(2) => RequestSource::Pager,
+                _ => return None,
+            };
+
+            return Some(Event::FocusRequest {
+                window: event.window,
+                source,
+                on_root: event.window == self.screen.root,
+            });
         }
 
         None
@@ -1505,31 +1935,28 @@ impl<'conn, Conn: connection::Connection> Connection for XConnection<'conn, Conn
         self.conn.flush().is_ok()
     }
 
+    #[inline]
+    fn fd(&self) -> RawFd {
+        self.fd
+    }
+
     #[inline]
     fn step(&self) -> Option<Event> {
         self.conn
             .wait_for_event()
             .ok()
-            .and_then(|event| match event {
-                XEvent::ButtonPress(e) => self.on_button_press(&e),
-                XEvent::ButtonRelease(e) => self.on_button_release(&e),
-                XEvent::MotionNotify(e) => self.on_motion_notify(&e),
-                XEvent::KeyPress(e) => self.on_key_press(&e),
-                XEvent::MapRequest(e) => self.on_map_request(&e),
-                XEvent::MapNotify(e) => self.on_map_notify(&e),
-                XEvent::EnterNotify(e) => self.on_enter_notify(&e),
-                XEvent::LeaveNotify(e) => self.on_leave_notify(&e),
-                XEvent::DestroyNotify(e) => self.on_destroy_notify(&e),
-                XEvent::Expose(e) => self.on_expose(&e),
-                XEvent::UnmapNotify(e) => self.on_unmap_notify(&e),
-                XEvent::ConfigureRequest(e) => self.on_configure_request(&e),
-                XEvent::ConfigureNotify(e) => self.on_configure_notify(&e),
-                XEvent::PropertyNotify(e) => self.on_property_notify(&e),
-                XEvent::ClientMessage(e) => self.on_client_message(&e),
-                XEvent::MappingNotify(e) => self.on_mapping_notify(&e),
-                XEvent::RandrNotify(e) => self.on_randr_notify(&e),
-                _ => None,
-            })
+            .and_then(|event| self.translate_event(event))
+    }
+
+    #[inline]
+    fn poll(&self) -> Option<Event> {
+        while let Ok(Some(event)) = self.conn.poll_for_event() {
+            if let Some(event) = self.translate_event(event) {
+                return Some(event);
+            }
+        }
+
+        None
     }
 
     fn connected_outputs(&self) -> Vec<Screen> {
@@ -1557,7 +1984,18 @@ impl<'conn, Conn: connection::Connection> Connection for XConnection<'conn, Conn
                             },
                         };
 
-                        Screen::new(region, i)
+                        let screen = Screen::new(region, i);
+
+                        if let Some(&output) = reply.outputs.first() {
+                            if let Some(info) = randr::get_output_info(self.conn, output, 0)
+                                .ok()
+                                .and_then(|cookie| cookie.reply().ok())
+                            {
+                                screen.set_name(String::from_utf8_lossy(&info.name));
+                            }
+                        }
+
+                        screen
                     })
                     .filter(|screen| {
                         let region = screen.full_region();
@@ -1584,6 +2022,19 @@ impl<'conn, Conn: connection::Connection> Connection for XConnection<'conn, Conn
             })
     }
 
+    #[inline]
+    fn get_resource(
+        &self,
+        name: &str,
+        class: &str,
+    ) -> Option<String> {
+        self.database
+            .borrow()
+            .as_ref()
+            .and_then(|database| database.get_string(name, class))
+            .map(|value| value.trim().to_owned())
+    }
+
     #[inline]
     fn get_pointer_position(&self) -> Pos {
         self.conn
@@ -1653,8 +2104,16 @@ impl<'conn, Conn: connection::Connection> Connection for XConnection<'conn, Conn
     fn confine_pointer(
         &self,
         window: Window,
+        cursor: CursorShape,
     ) {
         if self.confined_to.get().is_none() {
+            let cursor = self
+                .cursors
+                .borrow()
+                .get(&cursor)
+                .copied()
+                .unwrap_or(x11rb::NONE);
+
             if self
                 .conn
                 .grab_pointer(
@@ -1664,7 +2123,7 @@ impl<'conn, Conn: connection::Connection> Connection for XConnection<'conn, Conn
                     xproto::GrabMode::ASYNC,
                     xproto::GrabMode::ASYNC,
                     self.screen.root,
-                    x11rb::NONE,
+                    cursor,
                     x11rb::CURRENT_TIME,
                 )
                 .is_ok()
@@ -1682,6 +2141,98 @@ impl<'conn, Conn: connection::Connection> Connection for XConnection<'conn, Conn
         }
     }
 
+    // unlike confine_pointer, nothing is grabbed and events keep going to the
+    // window's clients; the pointer is instead fenced in by barriers along the
+    // edges of the window, which have to be raised anew whenever it is moved
+    fn confine_pointer_to(
+        &self,
+        window: Window,
+    ) -> bool {
+        if !self.barriers_supported || self.confined_to.get().is_some() {
+            return false;
+        }
+
+        self.unconfine_pointer();
+
+        let region = match self
+            .conn
+            .translate_coordinates(window, self.screen.root, 0, 0)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .zip(self.get_window_geometry(window).ok())
+        {
+            Some((translated, geometry)) => Region {
+                pos: Pos {
+                    x: translated.dst_x as i32,
+                    y: translated.dst_y as i32,
+                },
+                dim: geometry.dim,
+            },
+            None => return false,
+        };
+
+        // barriers only hold a pointer back that is already on their inside
+        if !region.encompasses(self.get_pointer_position()) {
+            self.warp_pointer(Pos {
+                x: region.pos.x + region.dim.w / 2,
+                y: region.pos.y + region.dim.h / 2,
+            });
+        }
+
+        let coord = |value: i32| std::cmp::max(0, value) as u16;
+        let (left, top) = (coord(region.pos.x), coord(region.pos.y));
+        let (right, bottom) = (
+            coord(region.pos.x + region.dim.w),
+            coord(region.pos.y + region.dim.h),
+        );
+
+        let mut barriers = self.barriers.borrow_mut();
+
+        for &(x1, y1, x2, y2) in &[
+            (left, top, right, top),
+            (left, bottom, right, bottom),
+            (left, top, left, bottom),
+            (right, top, right, bottom),
+        ] {
+            let barrier = match self.conn.generate_id() {
+                Ok(barrier) => barrier,
+                Err(_) => break,
+            };
+
+            // no directions are permitted, and all devices are blocked
+            if xfixes::create_pointer_barrier(
+                self.conn,
+                barrier,
+                self.screen.root,
+                x1,
+                y1,
+                x2,
+                y2,
+                0u32,
+                &[],
+            )
+            .is_ok()
+            {
+                barriers.push(barrier);
+            }
+        }
+
+        let confined = barriers.len() == 4;
+        drop(barriers);
+
+        if !confined {
+            self.unconfine_pointer();
+        }
+
+        confined
+    }
+
+    fn unconfine_pointer(&self) {
+        self.barriers.borrow_mut().drain(..).for_each(|barrier| {
+            drop(xfixes::delete_pointer_barrier(self.conn, barrier));
+        });
+    }
+
     #[inline]
     fn release_pointer(&self) {
         if self.confined_to.get().is_some() {
@@ -1692,6 +2243,53 @@ impl<'conn, Conn: connection::Connection> Connection for XConnection<'conn, Conn
         }
     }
 
+    #[inline]
+    fn grab_input(&self) -> bool {
+        let pointer_grabbed = self
+            .conn
+            .grab_pointer(
+                false,
+                self.screen.root,
+                u32::from(EventMask::BUTTON_PRESS) as u16,
+                xproto::GrabMode::ASYNC,
+                xproto::GrabMode::ASYNC,
+                x11rb::NONE,
+                x11rb::NONE,
+                x11rb::CURRENT_TIME,
+            )
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .filter(|reply| reply.status == xproto::GrabStatus::SUCCESS)
+            .is_some();
+
+        let keyboard_grabbed = self
+            .conn
+            .grab_keyboard(
+                false,
+                self.screen.root,
+                x11rb::CURRENT_TIME,
+                xproto::GrabMode::ASYNC,
+                xproto::GrabMode::ASYNC,
+            )
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .filter(|reply| reply.status == xproto::GrabStatus::SUCCESS)
+            .is_some();
+
+        if !(pointer_grabbed && keyboard_grabbed) {
+            self.release_input();
+            return false;
+        }
+
+        true
+    }
+
+    #[inline]
+    fn release_input(&self) {
+        drop(self.conn.ungrab_pointer(x11rb::CURRENT_TIME));
+        drop(self.conn.ungrab_keyboard(x11rb::CURRENT_TIME));
+    }
+
     fn cleanup(&self) {
         drop(
             self.conn
@@ -1700,6 +2298,14 @@ impl<'conn, Conn: connection::Connection> Connection for XConnection<'conn, Conn
 
         drop(self.conn.destroy_window(self.check_window));
 
+        for window in self.hint_windows.borrow_mut().drain(..) {
+            drop(self.conn.destroy_window(window));
+        }
+
+        if let Some((window, _)) = self.debug_hud.take() {
+            drop(self.conn.destroy_window(window));
+        }
+
         drop(
             self.conn
                 .delete_property(self.screen.root, self.atoms._NET_ACTIVE_WINDOW),
@@ -1735,6 +2341,14 @@ impl<'conn, Conn: connection::Connection> Connection for XConnection<'conn, Conn
                 .delete_property(self.screen.root, self.atoms._NET_CLIENT_LIST),
         );
 
+        drop(
+            self.conn
+                .delete_property(self.screen.root, self.atoms._WZRD_WORKSPACE_OCCUPANCY),
+        );
+
+        drop(self.conn.delete_property(self.screen.root, self.atoms._WZRD_FOCUSED_TITLE));
+        drop(self.conn.delete_property(self.screen.root, self.atoms._WZRD_LAYOUT_SYMBOL));
+
         drop(self.conn);
     }
 
@@ -1742,61 +2356,53 @@ impl<'conn, Conn: connection::Connection> Connection for XConnection<'conn, Conn
     fn create_frame(
         &self,
         region: Region,
-    ) -> Window {
-        const ERR: &str = "unable to create frame";
-
-        let frame = self.conn.generate_id().expect(ERR);
+    ) -> Result<Window> {
+        let frame = self.conn.generate_id()?;
         let aux = xproto::CreateWindowAux::new()
             .backing_store(Some(xproto::BackingStore::ALWAYS))
             .event_mask(EventMask::EXPOSURE | EventMask::KEY_PRESS);
 
-        self.conn
-            .create_window(
-                x11rb::COPY_DEPTH_FROM_PARENT,
-                frame,
-                self.screen.root,
-                region.pos.x as i16,
-                region.pos.y as i16,
-                region.dim.w as u16,
-                region.dim.h as u16,
-                0,
-                xproto::WindowClass::INPUT_OUTPUT,
-                0,
-                &aux,
-            )
-            .expect(ERR);
+        self.conn.create_window(
+            x11rb::COPY_DEPTH_FROM_PARENT,
+            frame,
+            self.screen.root,
+            region.pos.x as i16,
+            region.pos.y as i16,
+            region.dim.w as u16,
+            region.dim.h as u16,
+            0,
+            xproto::WindowClass::INPUT_OUTPUT,
+            0,
+            &aux,
+        )?;
 
         self.flush();
 
-        frame
+        Ok(frame)
     }
 
     #[inline]
-    fn create_handle(&self) -> Window {
-        const ERR: &str = "unable to create handle";
-
-        let handle = self.conn.generate_id().expect(ERR);
+    fn create_handle(&self) -> Result<Window> {
+        let handle = self.conn.generate_id()?;
         let aux = xproto::CreateWindowAux::new().override_redirect(1);
 
-        self.conn
-            .create_window(
-                x11rb::COPY_DEPTH_FROM_PARENT,
-                handle,
-                self.screen.root,
-                -2,
-                -2,
-                1,
-                1,
-                0,
-                xproto::WindowClass::INPUT_ONLY,
-                0,
-                &aux,
-            )
-            .expect(ERR);
+        self.conn.create_window(
+            x11rb::COPY_DEPTH_FROM_PARENT,
+            handle,
+            self.screen.root,
+            -2,
+            -2,
+            1,
+            1,
+            0,
+            xproto::WindowClass::INPUT_ONLY,
+            0,
+            &aux,
+        )?;
 
         self.flush();
 
-        handle
+        Ok(handle)
     }
 
     #[inline]
@@ -1851,6 +2457,10 @@ impl<'conn, Conn: connection::Connection> Connection for XConnection<'conn, Conn
             self.conn
                 .delete_property(window, self.atoms._NET_WM_DESKTOP),
         );
+        drop(
+            self.conn
+                .delete_property(window, self.atoms._WZRD_CLIENT_MONITOR),
+        );
     }
 
     #[inline]
@@ -2106,6 +2716,55 @@ impl<'conn, Conn: connection::Connection> Connection for XConnection<'conn, Conn
         self.flush();
     }
 
+    fn ungrab_bindings(&self) {
+        drop(
+            self.conn
+                .ungrab_key(xproto::Grab::ANY, self.screen.root, xproto::ModMask::ANY),
+        );
+
+        drop(self.conn.ungrab_button(
+            xproto::ButtonIndex::ANY,
+            self.screen.root,
+            xproto::ModMask::ANY,
+        ));
+
+        self.flush();
+    }
+
+    #[inline]
+    fn set_window_input_shape(
+        &self,
+        window: Window,
+        regions: &[Region],
+    ) {
+        self.set_window_shape(window, shape::SK::INPUT, regions);
+    }
+
+    #[inline]
+    fn set_window_bounding_shape(
+        &self,
+        window: Window,
+        regions: &[Region],
+    ) {
+        self.set_window_shape(window, shape::SK::BOUNDING, regions);
+    }
+
+    fn reset_window_bounding_shape(
+        &self,
+        window: Window,
+    ) {
+        if self.shape_supported {
+            drop(self.conn.shape_mask(
+                shape::SO::SET,
+                shape::SK::BOUNDING,
+                window,
+                0,
+                0,
+                x11rb::NONE,
+            ));
+        }
+    }
+
     #[inline]
     fn regrab_buttons(
         &self,
@@ -2197,6 +2856,252 @@ impl<'conn, Conn: connection::Connection> Connection for XConnection<'conn, Conn
         }
     }
 
+    #[inline]
+    fn fill_window_regions(
+        &self,
+        window: Window,
+        color: u32,
+        regions: &[Region],
+    ) {
+        drop(self.conn.change_gc(
+            self.background_gc,
+            &xproto::ChangeGCAux::new().foreground(color),
+        ));
+
+        drop(
+            self.conn.poly_fill_rectangle(
+                window,
+                self.background_gc,
+                &regions
+                    .iter()
+                    .map(|region| xproto::Rectangle {
+                        x: region.pos.x as i16,
+                        y: region.pos.y as i16,
+                        width: region.dim.w as u16,
+                        height: region.dim.h as u16,
+                    })
+                    .collect::<Vec<xproto::Rectangle>>(),
+            ),
+        );
+    }
+
+    fn draw_outline(
+        &self,
+        region: Option<Region>,
+    ) {
+        let previous = self.outline.replace(region);
+
+        if previous == region {
+            return;
+        }
+
+        // other clients are kept from drawing over the outline while it is shown
+        if previous.is_none() {
+            drop(self.conn.grab_server());
+        }
+
+        drop(
+            self.conn.poly_rectangle(
+                self.screen.root,
+                self.outline_gc,
+                &previous
+                    .into_iter()
+                    .chain(region)
+                    .map(|region| xproto::Rectangle {
+                        x: region.pos.x as i16 + 1,
+                        y: region.pos.y as i16 + 1,
+                        width: (region.dim.w - 2).max(0) as u16,
+                        height: (region.dim.h - 2).max(0) as u16,
+                    })
+                    .collect::<Vec<xproto::Rectangle>>(),
+            ),
+        );
+
+        if region.is_none() {
+            drop(self.conn.ungrab_server());
+        }
+    }
+
+    // hints are centered on their regions, an empty set of hints hides them all
+    fn draw_hints(
+        &self,
+        hints: &[(Region, String)],
+    ) {
+        const PADDING: i32 = 4;
+
+        let mut windows = self.hint_windows.borrow_mut();
+        let surplus = hints.len().min(windows.len());
+
+        for window in windows.drain(surplus..) {
+            drop(self.conn.destroy_window(window));
+        }
+
+        for (i, (region, label)) in hints.iter().enumerate() {
+            let dim = Dim {
+                w: self.text_glyph.w * label.len() as i32 + 2 * PADDING,
+                h: self.text_glyph.h + 2 * PADDING,
+            };
+
+            let center = Pos::from_center_of_region(*region);
+            let pos = Pos {
+                x: center.x - dim.w / 2,
+                y: center.y - dim.h / 2,
+            };
+
+            let window = match windows.get(i) {
+                Some(&window) => window,
+                None => {
+                    let window = match self.conn.generate_id() {
+                        Ok(window) => window,
+                        Err(_) => break,
+                    };
+
+                    let aux = xproto::CreateWindowAux::new()
+                        .override_redirect(1)
+                        .background_pixel(self.screen.black_pixel)
+                        .border_pixel(self.screen.white_pixel);
+
+                    drop(self.conn.create_window(
+                        x11rb::COPY_DEPTH_FROM_PARENT,
+                        window,
+                        self.screen.root,
+                        0,
+                        0,
+                        1,
+                        1,
+                        1,
+                        xproto::WindowClass::INPUT_OUTPUT,
+                        0,
+                        &aux,
+                    ));
+
+                    windows.push(window);
+                    window
+                },
+            };
+
+            drop(
+                self.conn.configure_window(
+                    window,
+                    &xproto::ConfigureWindowAux::default()
+                        .x(pos.x)
+                        .y(pos.y)
+                        .width(dim.w as u32)
+                        .height(dim.h as u32)
+                        .stack_mode(xproto::StackMode::ABOVE),
+                ),
+            );
+
+            drop(self.conn.map_window(window));
+            drop(self.conn.clear_area(false, window, 0, 0, 0, 0));
+            drop(self.conn.image_text8(
+                window,
+                self.text_gc,
+                PADDING as i16,
+                (PADDING + self.text_ascent) as i16,
+                label.as_bytes(),
+            ));
+        }
+
+        self.flush();
+    }
+
+    // the window is only reconfigured when its region changes, so that redrawing the
+    // text does not generate events of its own
+    fn draw_debug_hud(
+        &self,
+        hud: Option<(Pos, &[String])>,
+    ) {
+        const PADDING: i32 = 4;
+
+        let (pos, lines) = match hud {
+            Some(hud) => hud,
+            None => {
+                if let Some((window, _)) = self.debug_hud.take() {
+                    drop(self.conn.destroy_window(window));
+                    self.flush();
+                }
+
+                return;
+            },
+        };
+
+        // core text requests take at most 255 characters
+        let lines: Vec<&[u8]> = lines
+            .iter()
+            .map(|line| &line.as_bytes()[..line.len().min(255)])
+            .collect();
+
+        let region = Region {
+            pos,
+            dim: Dim {
+                w: self.text_glyph.w * lines.iter().map(|line| line.len()).max().unwrap_or(0) as i32
+                    + 2 * PADDING,
+                h: self.text_glyph.h * lines.len() as i32 + 2 * PADDING,
+            },
+        };
+
+        let window = match self.debug_hud.get() {
+            Some((window, previous)) if previous == region => window,
+            Some((window, _)) => {
+                drop(self.conn.configure_window(
+                    window,
+                    &xproto::ConfigureWindowAux::default()
+                        .x(region.pos.x)
+                        .y(region.pos.y)
+                        .width(region.dim.w as u32)
+                        .height(region.dim.h as u32)
+                        .stack_mode(xproto::StackMode::ABOVE),
+                ));
+
+                window
+            },
+            None => {
+                let window = match self.conn.generate_id() {
+                    Ok(window) => window,
+                    Err(_) => return,
+                };
+
+                let aux = xproto::CreateWindowAux::new()
+                    .override_redirect(1)
+                    .background_pixel(self.screen.black_pixel);
+
+                drop(self.conn.create_window(
+                    x11rb::COPY_DEPTH_FROM_PARENT,
+                    window,
+                    self.screen.root,
+                    region.pos.x as i16,
+                    region.pos.y as i16,
+                    region.dim.w as u16,
+                    region.dim.h as u16,
+                    0,
+                    xproto::WindowClass::INPUT_OUTPUT,
+                    0,
+                    &aux,
+                ));
+
+                drop(self.conn.map_window(window));
+                window
+            },
+        };
+
+        self.debug_hud.set(Some((window, region)));
+
+        drop(self.conn.clear_area(false, window, 0, 0, 0, 0));
+
+        for (i, line) in lines.iter().enumerate() {
+            drop(self.conn.image_text8(
+                window,
+                self.text_gc,
+                PADDING as i16,
+                (PADDING + self.text_ascent + i as i32 * self.text_glyph.h) as i16,
+                line,
+            ));
+        }
+
+        self.flush();
+    }
+
     #[inline]
     fn update_window_offset(
         &self,
@@ -2905,6 +3810,27 @@ impl<'conn, Conn: connection::Connection> Connection for XConnection<'conn, Conn
         ));
     }
 
+    #[inline]
+    fn set_window_monitor(
+        &self,
+        window: Window,
+        monitor: Option<usize>,
+    ) {
+        match monitor {
+            Some(monitor) => drop(self.conn.change_property32(
+                xproto::PropMode::REPLACE,
+                window,
+                self.atoms._WZRD_CLIENT_MONITOR,
+                self.atoms.CARDINAL,
+                &[monitor as u32],
+            )),
+            None => drop(
+                self.conn
+                    .delete_property(window, self.atoms._WZRD_CLIENT_MONITOR),
+            ),
+        }
+    }
+
     #[inline]
     fn set_window_state(
         &self,
@@ -2957,38 +3883,27 @@ impl<'conn, Conn: connection::Connection> Connection for XConnection<'conn, Conn
     #[inline]
     fn set_desktop_geometry(
         &self,
-        geometries: &[&Region],
+        geometry: Dim,
     ) {
-        let mut areas = Vec::with_capacity(geometries.len());
-
-        geometries.iter().for_each(|geometry| {
-            areas.push(geometry.pos.x as u32);
-            areas.push(geometry.pos.y as u32);
-            areas.push(geometry.dim.w as u32);
-            areas.push(geometry.dim.h as u32);
-        });
-
         drop(self.conn.change_property32(
             xproto::PropMode::REPLACE,
             self.screen.root,
             self.atoms._NET_DESKTOP_GEOMETRY,
             self.atoms.CARDINAL,
-            &areas[..],
+            &[geometry.w as u32, geometry.h as u32],
         ));
     }
 
     #[inline]
     fn set_desktop_viewport(
         &self,
-        viewports: &[&Region],
+        viewports: &[Pos],
     ) {
-        let mut areas = Vec::with_capacity(viewports.len());
+        let mut areas = Vec::with_capacity(viewports.len() * 2);
 
         viewports.iter().for_each(|viewport| {
-            areas.push(viewport.pos.x as u32);
-            areas.push(viewport.pos.y as u32);
-            areas.push(viewport.dim.w as u32);
-            areas.push(viewport.dim.h as u32);
+            areas.push(viewport.x as u32);
+            areas.push(viewport.y as u32);
         });
 
         drop(self.conn.change_property32(
@@ -3000,6 +3915,23 @@ impl<'conn, Conn: connection::Connection> Connection for XConnection<'conn, Conn
         ));
     }
 
+    #[inline]
+    fn set_desktop_layout(
+        &self,
+        vertical: bool,
+        columns: usize,
+        rows: usize,
+    ) {
+        // orientation, columns, rows, starting corner (top left)
+        drop(self.conn.change_property32(
+            xproto::PropMode::REPLACE,
+            self.screen.root,
+            self.atoms._NET_DESKTOP_LAYOUT,
+            self.atoms.CARDINAL,
+            &[vertical as u32, columns as u32, rows as u32, 0],
+        ));
+    }
+
     #[inline]
     fn set_workarea(
         &self,
@@ -3045,6 +3977,60 @@ impl<'conn, Conn: connection::Connection> Connection for XConnection<'conn, Conn
         ));
     }
 
+    // a client count and an urgent client count for every workspace, in order
+    #[inline]
+    fn set_workspace_occupancy(
+        &self,
+        occupancy: &[(usize, usize)],
+    ) {
+        drop(
+            self.conn.change_property32(
+                xproto::PropMode::REPLACE,
+                self.screen.root,
+                self.atoms._WZRD_WORKSPACE_OCCUPANCY,
+                self.atoms.CARDINAL,
+                &occupancy
+                    .iter()
+                    .flat_map(|&(clients, urgent)| vec![clients as u32, urgent as u32])
+                    .collect::<Vec<u32>>(),
+            ),
+        );
+    }
+
+    #[inline]
+    fn set_focused_title(
+        &self,
+        title: Option<&str>,
+    ) {
+        drop(match title {
+            Some(title) => self.conn.change_property8(
+                xproto::PropMode::REPLACE,
+                self.screen.root,
+                self.atoms._WZRD_FOCUSED_TITLE,
+                self.atoms.UTF8_STRING,
+                title.as_bytes(),
+            ),
+            None => self.conn.delete_property(self.screen.root, self.atoms._WZRD_FOCUSED_TITLE),
+        });
+    }
+
+    #[inline]
+    fn set_layout_symbol(
+        &self,
+        symbol: Option<char>,
+    ) {
+        drop(match symbol {
+            Some(symbol) => self.conn.change_property8(
+                xproto::PropMode::REPLACE,
+                self.screen.root,
+                self.atoms._WZRD_LAYOUT_SYMBOL,
+                self.atoms.UTF8_STRING,
+                symbol.to_string().as_bytes(),
+            ),
+            None => self.conn.delete_property(self.screen.root, self.atoms._WZRD_LAYOUT_SYMBOL),
+        });
+    }
+
     #[inline]
     fn update_client_list(
         &self,
@@ -3074,6 +4060,33 @@ impl<'conn, Conn: connection::Connection> Connection for XConnection<'conn, Conn
     }
 
     #[inline]
+    fn get_window_opaque_region(
+        &self,
+        window: Window,
+    ) -> Vec<Region> {
+        self.conn
+            .get_property(
+                false,
+                window,
+                self.atoms._NET_WM_OPAQUE_REGION,
+                self.atoms.CARDINAL,
+                0,
+                u32::MAX,
+            )
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .and_then(|reply| reply.value32().map(|values| values.collect::<Vec<u32>>()))
+            .map(|values| {
+                values
+                    .chunks_exact(4)
+                    .map(|rect| {
+                        Region::new(rect[0] as i32, rect[1] as i32, rect[2] as i32, rect[3] as i32)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
     fn get_window_strut(
         &self,
         window: Window,
@@ -3109,10 +4122,7 @@ impl<'conn, Conn: connection::Connection> Connection for XConnection<'conn, Conn
                     }
 
                     struts.push(if width != 0 {
-                        Some(Strut {
-                            window,
-                            width,
-                        })
+                        Some(Strut::new(window, width))
                     } else {
                         None
                     });
@@ -3140,30 +4150,33 @@ impl<'conn, Conn: connection::Connection> Connection for XConnection<'conn, Conn
             .reply()
             .ok()
             .and_then(|strut_partial| {
-                let widths: Vec<u32> = strut_partial.value32()?.collect();
+                let values: Vec<u32> = strut_partial.value32()?.collect();
 
-                if widths.is_empty() {
+                if values.is_empty() {
                     return None;
                 }
 
-                let mut struts = Vec::with_capacity(1);
-
-                for (i, &width) in widths.iter().enumerate() {
-                    if i == 4 {
-                        break;
-                    }
-
-                    struts.push(if width != 0 {
-                        Some(Strut {
-                            window,
-                            width,
+                // left, right, top and bottom widths, followed by the start and end
+                // of the span of each of them
+                Some(
+                    values
+                        .iter()
+                        .take(4)
+                        .enumerate()
+                        .map(|(i, &width)| {
+                            if width == 0 {
+                                return None;
+                            }
+
+                            Some(match (values.get(4 + 2 * i), values.get(5 + 2 * i)) {
+                                (Some(&start), Some(&end)) => {
+                                    Strut::partial(window, width, start, end)
+                                },
+                                _ => Strut::new(window, width),
+                            })
                         })
-                    } else {
-                        None
-                    });
-                }
-
-                Some(struts)
+                        .collect(),
+                )
             })
     }
 